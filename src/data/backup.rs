@@ -0,0 +1,244 @@
+/*!
+Полная резервная копия кейпада.
+
+Копия содержит информацию об устройстве, все 4 профиля ОЗУ и ПЗУ,
+номер активного профиля и параметры калибровки стика. Копии хранятся
+в формате RON в поддиректории `backups` директории конфигурации.
+*/
+
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  data::{config_dir, device::Device, profiles::Profile, stick::Stick, storage::store_ron},
+  utils::time::unix_now,
+};
+
+/// Количество хранимых автоматических резервных копий
+pub const AUTO_BACKUPS_LIMIT: usize = 20;
+
+/// Суффикс имени файла автоматической резервной копии
+const AUTO_SUFFIX: &str = "-auto.ron";
+
+/// Полный снимок состояния кейпада
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Backup {
+  /// Время создания копии в секундах от начала эпохи Unix
+  pub created: u64,
+
+  /// Информация об устройстве, с которого снята копия
+  pub device: Device,
+
+  /// Профили ОЗУ (1..=4)
+  pub ram: Vec<Profile>,
+
+  /// Профили ПЗУ (1..=4)
+  pub rom: Vec<Profile>,
+
+  /// Номер активного профиля (1..=4)
+  pub active_profile_id: usize,
+
+  /// Параметры калибровки стика
+  pub stick: Stick,
+}
+
+/**
+Выбор данных для восстановления из резервной копии
+
+Каждый элемент массивов соответствует профилю 1..=4.
+*/
+#[derive(Debug, Clone, Default)]
+pub struct RestoreSelection {
+  /// Восстанавливаемые профили ОЗУ
+  pub ram: [bool; 4],
+
+  /// Восстанавливаемые профили ПЗУ
+  pub rom: [bool; 4],
+
+  /// Восстанавливать ли калибровку стика
+  pub calibration: bool,
+}
+
+impl RestoreSelection {
+  /**
+  Создает выбор по умолчанию для копии `backup`
+
+  Выбираются все профили; калибровка стика выбирается только при
+  восстановлении на тот же кейпад (совпадает серийный номер).
+  */
+  pub fn new(backup: &Backup, device: &Device) -> Self {
+    Self {
+      ram: [true; 4],
+      rom: [true; 4],
+      calibration: backup.device.serial_num == device.serial_num,
+    }
+  }
+
+  /// Проверяет, выбран ли хотя бы один профиль ПЗУ
+  pub fn has_rom(&self) -> bool {
+    self.rom.iter().any(|&r| r)
+  }
+}
+
+impl Backup {
+  /// Создает пустую копию с текущим временем создания
+  pub fn new() -> Self {
+    Self {
+      created: unix_now(),
+      ..Default::default()
+    }
+  }
+
+  /// Возвращает директорию хранения резервных копий
+  pub fn dir() -> PathBuf {
+    config_dir().join("backups")
+  }
+
+  /**
+  Сохраняет копию в директорию резервных копий
+
+  Имя файла строится из серийного номера кейпада и времени создания.
+  После сохранения автоматической копии старые автоматические копии
+  сверх `AUTO_BACKUPS_LIMIT` удаляются.
+
+  # Аргументы
+  * `is_auto` - Копия создана автоматически
+
+  # Возвращает
+  Путь к сохранённому файлу
+  */
+  pub fn save(&self, is_auto: bool) -> Result<PathBuf> {
+    self.save_to(&Self::dir(), is_auto)
+  }
+
+  /**
+  Сохраняет копию в указанную директорию
+
+  # Аргументы
+  * `dir` - Директория резервных копий
+  * `is_auto` - Копия создана автоматически
+  */
+  fn save_to(&self, dir: &Path, is_auto: bool) -> Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+
+    let suffix = if is_auto { AUTO_SUFFIX } else { ".ron" };
+    let path = dir.join(format!(
+      "keypad-{}-{}{}",
+      self.device.serial_num, self.created, suffix
+    ));
+
+    store_ron(&path, self)?;
+    info!("backup: резервная копия сохранена: {}", path.display());
+
+    if is_auto {
+      Self::prune_auto(dir);
+    }
+
+    Ok(path)
+  }
+
+  /**
+  Загружает резервную копию из файла
+
+  # Аргументы
+  * `path` - Путь к файлу копии
+  */
+  pub fn load_file(path: &Path) -> Result<Self> {
+    let backup: Self = ron::from_str(&fs::read_to_string(path)?)?;
+
+    if backup.ram.len() != 4 || backup.rom.len() != 4 {
+      anyhow::bail!("ожидается по 4 профиля ОЗУ и ПЗУ");
+    }
+
+    Ok(backup)
+  }
+
+  /// Удаляет самые старые автоматические копии сверх `AUTO_BACKUPS_LIMIT`
+  fn prune_auto(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+      return;
+    };
+
+    let mut auto = entries
+      .filter_map(|entry| entry.ok().map(|e| e.path()))
+      .filter(|path| {
+        path
+          .file_name()
+          .and_then(|name| name.to_str())
+          .is_some_and(|name| name.ends_with(AUTO_SUFFIX))
+      })
+      .filter_map(|path| Some((fs::metadata(&path).ok()?.modified().ok()?, path)))
+      .collect::<Vec<_>>();
+
+    if auto.len() <= AUTO_BACKUPS_LIMIT {
+      return;
+    }
+
+    auto.sort();
+    for (_, path) in &auto[..auto.len() - AUTO_BACKUPS_LIMIT] {
+      if let Err(err) = fs::remove_file(path) {
+        warn!("backup: не удалось удалить {}: {err}", path.display());
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("claws-backup-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+  }
+
+  #[test]
+  fn saves_and_loads() {
+    let dir = test_dir("roundtrip");
+    let mut backup = Backup::new();
+    backup.ram = (1..=4).map(|_| Profile::default()).collect();
+    backup.rom = backup.ram.clone();
+    backup.active_profile_id = 2;
+
+    let path = backup.save_to(&dir, false).unwrap();
+    assert_eq!(Backup::load_file(&path).unwrap(), backup);
+
+    backup.rom.pop();
+    let path = backup.save_to(&dir, false).unwrap();
+    assert!(Backup::load_file(&path).is_err());
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn prunes_old_auto_backups() {
+    let dir = test_dir("prune");
+    let mut backup = Backup::new();
+    backup.save_to(&dir, false).unwrap();
+    for created in 0..AUTO_BACKUPS_LIMIT as u64 + 3 {
+      backup.created = 1_000_000 + created;
+      backup.save_to(&dir, true).unwrap();
+    }
+
+    let mut names = fs::read_dir(&dir)
+      .unwrap()
+      .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+      .collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names.len(), AUTO_BACKUPS_LIMIT + 1);
+    assert!(names.iter().any(|name| !name.ends_with(AUTO_SUFFIX)));
+    assert!(!names.iter().any(|name| name.contains("-1000000-")));
+    assert!(
+      names
+        .iter()
+        .any(|name| name.contains(&format!("-{}-", backup.created)))
+    );
+    fs::remove_dir_all(&dir).unwrap();
+  }
+}
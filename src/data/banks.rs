@@ -0,0 +1,144 @@
+/*!
+Наборы профилей (банки) для одновременной записи в 4 ячейки кейпада.
+
+Набор сопоставляет ячейкам 1..=4 профили локальной библиотеки по имени.
+Профили берутся из библиотеки в момент записи, поэтому правки профиля
+библиотеки попадают во все наборы, в которые он входит.
+*/
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::data::{library::Library, profiles::Profile};
+
+/// Именованный набор из четырёх профилей для ячеек 1..=4
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Bank {
+  /// Имя набора
+  pub name: String,
+
+  /// Имена профилей библиотеки для ячеек 1..=4; `None` — в ячейку записывается пустой профиль
+  pub profiles: [Option<String>; 4],
+}
+
+/// Состояние записи набора на кейпад
+#[derive(Debug, Clone)]
+pub struct BankWrite {
+  /// Имя записываемого набора
+  pub name: String,
+
+  /// Профили ячеек 1..=4 на момент начала записи
+  pub profiles: [Profile; 4],
+
+  /// Сохранять профили в ПЗУ
+  pub to_rom: bool,
+
+  /// Номер профиля, активного до начала записи
+  pub active_id: usize,
+
+  /// Количество записанных ячеек (0..=4)
+  pub written: usize,
+
+  /// Ячейки, не прошедшие проверку после записи
+  pub failed: Vec<usize>,
+
+  /// Ожидается проверка ПЗУ после записи всех ячеек
+  pub verifying_rom: bool,
+}
+
+impl Default for Bank {
+  fn default() -> Self {
+    Self {
+      name: "Новый набор".to_string(),
+      profiles: Default::default(),
+    }
+  }
+}
+
+impl Bank {
+  /**
+  Подставляет профили библиотеки в ячейки набора
+
+  # Аргументы
+  * `library` - Профили библиотеки
+
+  # Возвращает
+  Профили ячеек 1..=4 или ошибку со списком ячеек,
+  профили которых не найдены или не определяются однозначно
+  */
+  pub fn resolve(&self, library: &[Profile]) -> Result<[Profile; 4]> {
+    let mut problems = Vec::new();
+    let profiles = std::array::from_fn(|i| match &self.profiles[i] {
+      None => Profile::default(),
+      Some(name) => match Library::find(library, name) {
+        Ok(idx) => library[idx].clone(),
+        Err(err) => {
+          problems.push(format!("ячейка {}: {err}", i + 1));
+          Profile::default()
+        }
+      },
+    });
+
+    if !problems.is_empty() {
+      bail!("набор «{}»: {}", self.name, problems.join("; "));
+    }
+    Ok(profiles)
+  }
+
+  /**
+  Переносит ссылки набора на новое имя профиля
+
+  # Возвращает
+  `true`, если набор изменился
+  */
+  pub fn rename(&mut self, old: &str, new: &str) -> bool {
+    let mut changed = false;
+    for name in self
+      .profiles
+      .iter_mut()
+      .flatten()
+      .filter(|name| *name == old)
+    {
+      *name = new.to_string();
+      changed = true;
+    }
+    changed
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::errors::library::ProfileRefError;
+
+  fn named(name: &str) -> Profile {
+    Profile {
+      name: name.to_string(),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn resolves_library_profiles() {
+    let mut library = vec![named("Krita"), named("Blender")];
+    let mut bank = Bank::default();
+    bank.profiles[0] = Some("Krita".to_string());
+    bank.profiles[2] = Some("Blender".to_string());
+
+    library[0].stick.deadzone = 10;
+    let profiles = bank.resolve(&library).unwrap();
+    assert_eq!(profiles[0], library[0]);
+    assert_eq!(profiles[1], Profile::default());
+    assert_eq!(profiles[2], library[1]);
+
+    assert!(bank.rename("Blender", "Blender 4"));
+    let err = bank.resolve(&library).unwrap_err().to_string();
+    assert!(err.contains("ячейка 3"), "{err}");
+
+    library.push(named("Krita"));
+    assert_eq!(
+      Library::find(&library, "Krita"),
+      Err(ProfileRefError::Ambiguous("Krita".into()))
+    );
+  }
+}
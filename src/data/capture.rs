@@ -0,0 +1,218 @@
+/*!
+Захват комбинаций с клавиатуры компьютера.
+
+Поддерживаются два режима: аккорд, записываемый после отпускания всех
+клавиш, и последовательный ввод, при котором каждое нажатие добавляет
+клавишу к комбинации. В обоих режимах автоповтор удерживаемой клавиши
+игнорируется, а модификаторы при желании упорядочиваются.
+*/
+
+use std::{fmt, time::Duration};
+
+use iced::keyboard::{Key, Location, key::Code};
+use serde::{Deserialize, Serialize};
+
+use crate::data::code::{KeyCategory, by_code, by_physical};
+
+/// Время ожидания первого нажатия в режиме аккорда
+const CHORD_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Время ожидания следующего нажатия в последовательном режиме
+const SEQUENTIAL_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Режим захвата комбинации
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CaptureMode {
+  /// Комбинация записывается целиком, когда отпущены все нажатые клавиши
+  #[default]
+  Chord,
+  /// Каждое нажатие добавляет клавишу, запись завершается по таймауту
+  Sequential,
+}
+
+impl CaptureMode {
+  /// Все режимы захвата
+  pub const ALL: [Self; 2] = [Self::Chord, Self::Sequential];
+
+  /**
+  Возвращает время ожидания нажатия
+
+  В режиме аккорда отсчёт идёт от начала записи и останавливается,
+  пока удерживается хотя бы одна клавиша. В последовательном режиме
+  отсчёт начинается заново после каждого нажатия.
+  */
+  pub fn timeout(&self) -> Duration {
+    match self {
+      Self::Chord => CHORD_TIMEOUT,
+      Self::Sequential => SEQUENTIAL_TIMEOUT,
+    }
+  }
+}
+
+impl fmt::Display for CaptureMode {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let title = match self {
+      Self::Chord => "Аккорд (после отпускания)",
+      Self::Sequential => "По нажатиям",
+    };
+    write!(f, "{title}")
+  }
+}
+
+/// Настройки захвата комбинаций
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct CaptureSettings {
+  /// Режим захвата
+  pub mode: CaptureMode,
+
+  /// Записывать модификаторы первыми в порядке Ctrl, Shift, Alt, Win
+  pub normalize_modifiers: bool,
+}
+
+impl Default for CaptureSettings {
+  fn default() -> Self {
+    Self {
+      mode: CaptureMode::default(),
+      normalize_modifiers: true,
+    }
+  }
+}
+
+/**
+Набираемый аккорд
+
+Физические клавиши хранятся в порядке нажатия. Событие отпускания
+в Iced не содержит физической клавиши, поэтому отпущенная клавиша
+находится по логической клавише и её расположению, запомненным при нажатии.
+Аккорд считается завершённым, когда отпущены все удерживаемые клавиши.
+*/
+#[derive(Debug, Clone, Default)]
+pub struct Chord {
+  /// Нажатые клавиши в порядке нажатия
+  pressed: Vec<Code>,
+
+  /// Удерживаемые сейчас клавиши
+  held: Vec<(Code, Key, Location)>,
+}
+
+impl Chord {
+  /**
+  Регистрирует нажатие клавиши
+
+  # Аргументы
+  * `code` - Физическая клавиша
+  * `key` - Логическая клавиша
+  * `location` - Расположение клавиши
+
+  # Возвращает
+  `false`, если клавиша уже удерживается (автоповтор)
+  */
+  pub fn press(&mut self, code: Code, key: Key, location: Location) -> bool {
+    if self.held.iter().any(|(held, ..)| *held == code) {
+      return false;
+    }
+    self.held.push((code, key, location));
+    if !self.pressed.contains(&code) {
+      self.pressed.push(code);
+    }
+    true
+  }
+
+  /**
+  Регистрирует отпускание клавиши
+
+  Если клавиша не найдена (например, раскладка сменилась во время набора),
+  отпущенной считается последняя нажатая.
+
+  # Аргументы
+  * `key` - Логическая клавиша
+  * `location` - Расположение клавиши
+
+  # Возвращает
+  `true`, если отпущены все нажатые клавиши
+  */
+  pub fn release(&mut self, key: &Key, location: Location) -> bool {
+    if self.held.is_empty() {
+      return false;
+    }
+    let idx = self
+      .held
+      .iter()
+      .position(|(_, held, held_location)| held == key && *held_location == location)
+      .unwrap_or(self.held.len() - 1);
+    self.held.remove(idx);
+    self.held.is_empty()
+  }
+
+  /// Проверяет, удерживается ли хотя бы одна клавиша
+  pub fn is_held(&self) -> bool {
+    !self.held.is_empty()
+  }
+
+  /**
+  Завершает аккорд и возвращает коды поддерживаемых клавиш
+
+  Клавиши, которых нет в протоколе устройства, пропускаются.
+  */
+  pub fn take_codes(&mut self) -> Vec<u8> {
+    let codes = self
+      .pressed
+      .iter()
+      .filter_map(|&code| by_physical(code).map(|key| key.code))
+      .collect();
+    *self = Self::default();
+    codes
+  }
+}
+
+/**
+Упорядочивает комбинацию: модификаторы первыми по возрастанию кода
+(Ctrl, Shift, Alt, Win, сначала левые), остальные клавиши в исходном порядке
+
+Пустые позиции (код 0) переносятся в конец.
+
+# Аргументы
+* `codes` - Коды клавиш комбинации
+*/
+pub fn normalize_modifiers(codes: &mut [u8]) {
+  let is_modifier =
+    |code: u8| by_code(code).is_some_and(|key| key.category == KeyCategory::Modifier);
+  codes.sort_by_key(|&code| match code {
+    0 => (2, 0),
+    code if is_modifier(code) => (0, code),
+    _ => (1, 0),
+  });
+}
+
+#[cfg(test)]
+mod tests {
+  use iced::keyboard::key::Named;
+
+  use super::*;
+
+  #[test]
+  fn chord_ignores_repeat_and_completes_on_release() {
+    let (ctrl, c) = (Key::Named(Named::Control), Key::Character("c".into()));
+    let mut chord = Chord::default();
+    assert!(chord.press(Code::ControlLeft, ctrl.clone(), Location::Left));
+    assert!(chord.press(Code::KeyC, c.clone(), Location::Standard));
+    assert!(!chord.press(Code::KeyC, c.clone(), Location::Standard));
+
+    // Клавиша, отпущенная и нажатая снова до конца аккорда, не автоповтор
+    assert!(!chord.release(&c, Location::Standard));
+    assert!(chord.press(Code::KeyC, c.clone(), Location::Standard));
+    assert!(!chord.release(&ctrl, Location::Left));
+    assert!(chord.is_held());
+    assert!(chord.release(&c, Location::Standard));
+    assert_eq!(chord.take_codes(), vec![0x80, b'c']);
+    assert!(!chord.release(&c, Location::Standard));
+  }
+
+  #[test]
+  fn modifiers_go_first() {
+    let mut codes = [b'z', 0x81, 0, b'a', 0x80, 0];
+    normalize_modifiers(&mut codes);
+    assert_eq!(codes, [0x80, 0x81, b'z', b'a', 0, 0]);
+  }
+}
@@ -0,0 +1,337 @@
+/*!
+Операции с назначениями кнопок и направлений стика.
+
+Ячейка — кнопка или направление стика профиля. Назначения ячеек можно
+копировать, вставлять, менять местами и перетаскивать. Подпись, заметка
+и цвет кнопки переносятся вместе с её назначением. Направление
+стика хранит одну клавишу, поэтому комбинация из нескольких клавиш
+в него не помещается.
+*/
+
+use iced::{Point, keyboard::Modifiers};
+
+use crate::data::{meta::ButtonMeta, profiles::Profile};
+
+/// Смещение курсора в пикселях, после которого нажатие на ячейку становится перетаскиванием
+const DRAG_THRESHOLD: f32 = 8.0;
+
+/// Назначение ячейки вместе с метаданными кнопки
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Content {
+  /// Комбинация клавиш
+  pub codes: [u8; 6],
+  /// Подпись, заметка и цвет кнопки; у направлений стика метаданных нет
+  pub meta: Option<ButtonMeta>,
+}
+
+/// Ячейка профиля
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cell {
+  /// Кнопка 1..=16
+  Button(usize),
+  /// Направление стика 1..=4
+  Stick(usize),
+}
+
+impl Cell {
+  /// Возвращает название ячейки для интерфейса
+  pub fn title(&self) -> String {
+    match self {
+      Self::Button(id) => format!("Кнопка #{id}"),
+      Self::Stick(id) => format!("Стик #{id}"),
+    }
+  }
+
+  /**
+  Возвращает назначение ячейки
+
+  Код направления стика возвращается первым элементом комбинации.
+  */
+  pub fn get(&self, profile: &Profile) -> [u8; 6] {
+    match *self {
+      Self::Button(id) => profile.buttons[id - 1],
+      Self::Stick(id) => [profile.stick.word[id - 1], 0, 0, 0, 0, 0],
+    }
+  }
+
+  /**
+  Записывает назначение в ячейку
+
+  # Аргументы
+  * `profile` - Изменяемый профиль
+  * `codes` - Комбинация клавиш
+
+  # Возвращает
+  Ошибку, если комбинация из нескольких клавиш назначается направлению стика
+  */
+  pub fn set(&self, profile: &mut Profile, codes: [u8; 6]) -> Result<(), String> {
+    match *self {
+      Self::Button(id) => profile.buttons[id - 1] = codes,
+      Self::Stick(id) => {
+        let mut keys = codes.into_iter().filter(|&code| code != 0);
+        let code = keys.next().unwrap_or(0);
+        if keys.next().is_some() {
+          return Err(format!(
+            "{}: направлению стика назначается одна клавиша",
+            self.title()
+          ));
+        }
+        profile.stick.word[id - 1] = code;
+      }
+    }
+    Ok(())
+  }
+
+  /// Возвращает назначение ячейки вместе с метаданными кнопки
+  pub fn content(&self, profile: &Profile) -> Content {
+    Content {
+      codes: self.get(profile),
+      meta: match *self {
+        Self::Button(id) => profile.meta.button(id).cloned(),
+        Self::Stick(_) => None,
+      },
+    }
+  }
+
+  /**
+  Записывает назначение и метаданные в ячейку
+
+  Метаданные кнопки заменяются переданными, а при их отсутствии удаляются.
+  Направлению стика метаданные не переносятся.
+
+  # Аргументы
+  * `profile` - Изменяемый профиль
+  * `content` - Назначение с метаданными
+
+  # Возвращает
+  Ошибку, если комбинация из нескольких клавиш назначается направлению стика
+  */
+  pub fn put(&self, profile: &mut Profile, content: Content) -> Result<(), String> {
+    self.set(profile, content.codes)?;
+    if let Self::Button(id) = *self {
+      profile.meta.update_button(id, |meta| {
+        *meta = ButtonMeta {
+          button: id,
+          ..content.meta.unwrap_or_default()
+        };
+      });
+    }
+    Ok(())
+  }
+
+  /// Проверяет, помещается ли комбинация в ячейку
+  pub fn fits(&self, codes: &[u8; 6]) -> bool {
+    match self {
+      Self::Button(_) => true,
+      Self::Stick(_) => codes.iter().filter(|&&code| code != 0).count() <= 1,
+    }
+  }
+}
+
+/**
+Меняет местами назначения двух ячеек
+
+# Возвращает
+Ошибку, если одно из назначений не помещается в другую ячейку; профиль
+в этом случае не меняется
+*/
+pub fn swap(profile: &mut Profile, a: Cell, b: Cell) -> Result<(), String> {
+  let (content_a, content_b) = (a.content(profile), b.content(profile));
+  if !b.fits(&content_a.codes) || !a.fits(&content_b.codes) {
+    return Err(format!(
+      "Нельзя поменять {} и {}: направлению стика назначается одна клавиша",
+      a.title(),
+      b.title()
+    ));
+  }
+  a.put(profile, content_b)?;
+  b.put(profile, content_a)
+}
+
+/**
+Вставляет скопированные назначения в выделенные ячейки
+
+Одно назначение вставляется во все выделенные ячейки, несколько —
+по порядку, пока хватает ячеек.
+
+# Аргументы
+* `profile` - Изменяемый профиль
+* `clipboard` - Скопированные назначения
+* `targets` - Выделенные ячейки в порядке выделения
+
+# Возвращает
+Ошибку для первой ячейки, в которую назначение не поместилось;
+остальные ячейки заполняются
+*/
+pub fn paste(profile: &mut Profile, clipboard: &[Content], targets: &[Cell]) -> Result<(), String> {
+  let mut result = Ok(());
+  let sources = clipboard.iter().cycle().take(match clipboard.len() {
+    1 => targets.len(),
+    len => len.min(targets.len()),
+  });
+
+  for (cell, content) in targets.iter().zip(sources) {
+    if let Err(err) = cell.put(profile, content.clone()) {
+      result = result.and(Err(err));
+    }
+  }
+  result
+}
+
+/// Выделение, буфер обмена и перетаскивание ячеек редактора
+#[derive(Debug, Clone, Default)]
+pub struct CellEditor {
+  /// Выделенные ячейки в порядке выделения
+  pub selected: Vec<Cell>,
+  /// Скопированные назначения с метаданными кнопок
+  pub clipboard: Vec<Content>,
+  /// Ячейка под курсором
+  pub hovered: Option<Cell>,
+  /// Ячейка, на которой нажата левая кнопка мыши
+  pub dragged: Option<Cell>,
+  /// Положение курсора при нажатии на ячейку
+  drag_origin: Point,
+  /// Курсор сдвинулся дальше `DRAG_THRESHOLD`: отпускание переносит назначение
+  drag_moved: bool,
+  /// Последнее положение курсора
+  pointer: Point,
+  /// Ячейка, для которой открыто контекстное меню
+  pub menu: Option<Cell>,
+  /// Результат последней операции
+  pub status: Option<String>,
+  /// Нажатые модификаторы клавиатуры
+  pub modifiers: Modifiers,
+}
+
+impl CellEditor {
+  /// Проверяет, выделена ли ячейка
+  pub fn is_selected(&self, cell: Cell) -> bool {
+    self.selected.contains(&cell)
+  }
+
+  /// Добавляет ячейку к выделению или снимает с неё выделение
+  pub fn toggle(&mut self, cell: Cell) {
+    match self.selected.iter().position(|&c| c == cell) {
+      Some(i) => {
+        self.selected.remove(i);
+      }
+      None => self.selected.push(cell),
+    }
+  }
+
+  /// Запоминает ячейку, на которой нажата левая кнопка мыши
+  pub fn drag_start(&mut self, cell: Cell) {
+    self.dragged = Some(cell);
+    self.drag_origin = self.pointer;
+    self.drag_moved = false;
+  }
+
+  /// Отслеживает перемещение курсора во время нажатия
+  pub fn drag_move(&mut self, position: Point) {
+    self.pointer = position;
+    if self.dragged.is_some() && self.drag_origin.distance(position) > DRAG_THRESHOLD {
+      self.drag_moved = true;
+    }
+  }
+
+  /**
+  Завершает нажатие на ячейку
+
+  # Возвращает
+  Ячейку, с которой перетаскивается назначение, или `None`,
+  если курсор почти не сдвинулся (обычный клик)
+  */
+  pub fn drag_end(&mut self) -> Option<Cell> {
+    let dragged = self.dragged.take();
+    dragged.filter(|_| std::mem::take(&mut self.drag_moved))
+  }
+
+  /// Копирует назначения выделенных ячеек в буфер обмена
+  pub fn copy(&mut self, profile: &Profile) {
+    if self.selected.is_empty() {
+      return;
+    }
+    self.clipboard = self
+      .selected
+      .iter()
+      .map(|cell| cell.content(profile))
+      .collect();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn drag_requires_movement() {
+    let mut editor = CellEditor::default();
+    editor.drag_move(Point::new(10.0, 10.0));
+    editor.drag_start(Cell::Button(1));
+    editor.drag_move(Point::new(13.0, 12.0));
+    assert_eq!(editor.drag_end(), None);
+
+    editor.drag_start(Cell::Button(1));
+    editor.drag_move(Point::new(60.0, 12.0));
+    assert_eq!(editor.drag_end(), Some(Cell::Button(1)));
+    assert_eq!(editor.drag_end(), None);
+  }
+
+  #[test]
+  fn swap_checks_stick_capacity() {
+    let mut profile = Profile::default();
+    profile.buttons[0] = [0x80, b'c', 0, 0, 0, 0];
+    profile.buttons[1] = [b'x', 0, 0, 0, 0, 0];
+
+    assert!(swap(&mut profile, Cell::Button(1), Cell::Stick(1)).is_err());
+    assert_eq!(profile.stick.word[0], 0);
+
+    swap(&mut profile, Cell::Button(2), Cell::Stick(1)).unwrap();
+    assert_eq!(profile.stick.word[0], b'x');
+    assert_eq!(profile.buttons[1], [0; 6]);
+  }
+
+  #[test]
+  fn meta_follows_assignment() {
+    let mut profile = Profile::default();
+    profile.buttons[0] = [b'z', 0, 0, 0, 0, 0];
+    profile
+      .meta
+      .update_button(1, |meta| meta.caption = "Отмена".to_string());
+
+    swap(&mut profile, Cell::Button(1), Cell::Button(2)).unwrap();
+    assert_eq!(profile.meta.caption(1), None);
+    assert_eq!(profile.meta.caption(2), Some("Отмена"));
+    assert_eq!(profile.meta.button(2).unwrap().button, 2);
+
+    let content = Cell::Button(2).content(&profile);
+    Cell::Button(3).put(&mut profile, content).unwrap();
+    assert_eq!(profile.meta.caption(3), Some("Отмена"));
+
+    Cell::Button(2)
+      .put(&mut profile, Content::default())
+      .unwrap();
+    assert_eq!(profile.buttons[1], [0; 6]);
+    assert!(profile.meta.button(2).is_none());
+  }
+
+  #[test]
+  fn paste_fills_selection() {
+    let mut profile = Profile::default();
+    let targets = [Cell::Button(3), Cell::Button(4), Cell::Stick(2)];
+
+    let codes = |codes| Content { codes, meta: None };
+    paste(&mut profile, &[codes([b'a', 0, 0, 0, 0, 0])], &targets).unwrap();
+    assert_eq!(profile.buttons[2], [b'a', 0, 0, 0, 0, 0]);
+    assert_eq!(profile.buttons[3], [b'a', 0, 0, 0, 0, 0]);
+    assert_eq!(profile.stick.word[1], b'a');
+
+    let clipboard = [
+      codes([b'b', 0, 0, 0, 0, 0]),
+      codes([0x80, b'v', 0, 0, 0, 0]),
+    ];
+    assert!(paste(&mut profile, &clipboard, &targets[1..]).is_err());
+    assert_eq!(profile.buttons[3], [b'b', 0, 0, 0, 0, 0]);
+    assert_eq!(profile.stick.word[1], b'a');
+  }
+}
@@ -1,163 +1,998 @@
 /*!
-Преобразование физических клавиш в коды, совместимые с протоколом устройства.
+Реестр клавиш протокола устройства.
 
-Этот модуль содержит маппинг между физическими кодами клавиш Iced
-и ASCII-кодами, которые понимает протокол устройства.
+Единственный источник сведений о клавишах: код протокола, каноническое
+название, синонимы, категория, физические клавиши Iced, которые дают
+этот код, код HID и названия QMK. Реестр используется при захвате нажатий,
+отображении подписей, разборе и проверке текстовых профилей, импорте
+и экспорте раскладок QMK.
+
+Коды протокола совпадают с кодами библиотеки Arduino Keyboard: печатные
+символы ASCII передаются как есть, служебные клавиши имеют коды от 0x80.
 */
 
+use std::fmt;
+
 use iced::keyboard::key::Code;
 
 /**
-Трейт преобразования кода клавиши в ASCII-совместимый код
+Категория клавиши
 
-Обеспечивает конвертацию между физическими кодами клавиш
-и байтовыми значениями, используемыми в протоколе устройства.
+Используется для группировки клавиш при выборе и проверке назначений.
 */
-pub trait CodeAscii {
-  /**
-  Возвращает байтовое представление кода клавиши
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KeyCategory {
+  /// Латинские буквы
+  #[default]
+  Letter,
+  /// Цифры основного ряда
+  Digit,
+  /// Знаки препинания и символы
+  Symbol,
+  /// Ctrl, Shift, Alt, Win
+  Modifier,
+  /// Enter, Esc, Backspace, Tab, Space, Insert, Del
+  Editing,
+  /// Стрелки, Home, End, PgUp, PgDn
+  Navigation,
+  /// F1–F24
+  Function,
+  /// Цифровая клавиатура
+  Numpad,
+  /// CapsLock, NumLock, ScrollLock, PrScr, Pause
+  System,
+  /// Устаревшие коды, см. `KEYS`
+  Legacy,
+}
 
-  # Возвращает
-  `Some(u8)` с ASCII-кодом клавиши или `None` если клавиша не поддерживается
-  */
-  fn to_ascii(&self) -> Option<u8>;
+impl KeyCategory {
+  /// Категории, доступные для выбора клавиш; устаревшие коды не предлагаются
+  pub const PICKER: [KeyCategory; 9] = [
+    KeyCategory::Letter,
+    KeyCategory::Digit,
+    KeyCategory::Symbol,
+    KeyCategory::Modifier,
+    KeyCategory::Editing,
+    KeyCategory::Navigation,
+    KeyCategory::Function,
+    KeyCategory::Numpad,
+    KeyCategory::System,
+  ];
+
+  /// Возвращает клавиши категории в порядке реестра
+  pub fn keys(&self) -> impl Iterator<Item = &'static Key> {
+    let category = *self;
+    KEYS.iter().filter(move |key| key.category == category)
+  }
 }
 
-impl CodeAscii for Code {
+impl fmt::Display for KeyCategory {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let title = match self {
+      KeyCategory::Letter => "Буквы",
+      KeyCategory::Digit => "Цифры",
+      KeyCategory::Symbol => "Символы",
+      KeyCategory::Modifier => "Модификаторы",
+      KeyCategory::Editing => "Редактирование",
+      KeyCategory::Navigation => "Навигация",
+      KeyCategory::Function => "Функциональные",
+      KeyCategory::Numpad => "Цифровой блок",
+      KeyCategory::System => "Системные",
+      KeyCategory::Legacy => "Устаревшие",
+    };
+    write!(f, "{title}")
+  }
+}
+
+/**
+Клавиша протокола устройства
+*/
+#[derive(Debug, PartialEq, Eq)]
+pub struct Key {
+  /// Код клавиши в протоколе устройства
+  pub code: u8,
+  /// Каноническое название для текстовых профилей, без `+` и пробелов
+  pub name: &'static str,
+  /// Короткая подпись для кнопок кейпада
+  pub title: &'static str,
+  /// Синонимы названия, принимаемые при разборе
+  pub aliases: &'static [&'static str],
+  /// Категория клавиши
+  pub category: KeyCategory,
+  /// Физические клавиши Iced, нажатие которых даёт этот код
+  pub physical: &'static [Code],
+  /// Код HID клавиши или 0, если её набирают с Shift или кода HID нет
+  pub usage: u8,
+  /// Названия клавиши в QMK: первое каноническое, остальные синонимы
+  pub qmk: &'static [&'static str],
+}
+
+impl Key {
   /**
-  Преобразует физический код клавиши в ASCII-совместимый байт
-
-  Поддерживает широкий спектр клавиш включая:
-  - Буквы и цифры (стандартные ASCII)
-  - Специальные клавиши (Enter, Space, Tab и т.д.)
-  - Функциональные клавиши (F1-F24)
-  - Клавиши модификаторы (Ctrl, Alt, Shift, Win)
-  - Клавиши навигации (стрелки, Home, End, Page Up/Down)
-  - Цифровую клавиатуру
-
-  # Возвращает
-  `Some(u8)` с кодом клавиши или `None` для неподдерживаемых клавиш
+  Проверяет, соответствует ли клавише название
+
+  Сравнение без учёта регистра с каноническим названием и синонимами.
+
+  # Аргументы
+  * `name` - Проверяемое название
   */
-  fn to_ascii(&self) -> Option<u8> {
-    match self {
-      Code::Backquote => Some(b'`'),
-      Code::Backslash | Code::IntlBackslash => Some(b'\\'),
-      Code::BracketLeft => Some(b'['),
-      Code::BracketRight => Some(b']'),
-      Code::Comma => Some(b','),
-      Code::Digit0 => Some(b'0'),
-      Code::Digit1 => Some(b'1'),
-      Code::Digit2 => Some(b'2'),
-      Code::Digit3 => Some(b'3'),
-      Code::Digit4 => Some(b'4'),
-      Code::Digit5 => Some(b'5'),
-      Code::Digit6 => Some(b'6'),
-      Code::Digit7 => Some(b'7'),
-      Code::Digit8 => Some(b'8'),
-      Code::Digit9 => Some(b'9'),
-      Code::Equal => Some(b'='),
-      Code::KeyA => Some(b'a'),
-      Code::KeyB => Some(b'b'),
-      Code::KeyC => Some(b'c'),
-      Code::KeyD => Some(b'd'),
-      Code::KeyE => Some(b'e'),
-      Code::KeyF => Some(b'f'),
-      Code::KeyG => Some(b'g'),
-      Code::KeyH => Some(b'h'),
-      Code::KeyI => Some(b'i'),
-      Code::KeyJ => Some(b'j'),
-      Code::KeyK => Some(b'k'),
-      Code::KeyL => Some(b'l'),
-      Code::KeyM => Some(b'm'),
-      Code::KeyN => Some(b'n'),
-      Code::KeyO => Some(b'o'),
-      Code::KeyP => Some(b'p'),
-      Code::KeyQ => Some(b'q'),
-      Code::KeyR => Some(b'r'),
-      Code::KeyS => Some(b's'),
-      Code::KeyT => Some(b't'),
-      Code::KeyU => Some(b'u'),
-      Code::KeyV => Some(b'v'),
-      Code::KeyW => Some(b'w'),
-      Code::KeyX => Some(b'x'),
-      Code::KeyY => Some(b'y'),
-      Code::KeyZ => Some(b'z'),
-      Code::Minus | Code::NumpadSubtract => Some(b'-'),
-      Code::Period => Some(b'.'),
-      Code::Quote => Some(b'\''),
-      Code::Semicolon => Some(b';'),
-      Code::Slash | Code::NumpadDivide => Some(b'/'),
-      Code::AltLeft => Some(0x82),
-      Code::AltRight => Some(0x86),
-      Code::Backspace => Some(0xb2),
-      Code::CapsLock => Some(0xc1),
-      Code::ControlLeft => Some(0x80),
-      Code::ControlRight => Some(0x84),
-      Code::Enter => Some(0xb0),
-      Code::SuperLeft => Some(0x83),
-      Code::SuperRight => Some(0x87),
-      Code::ShiftLeft => Some(0x81),
-      Code::ShiftRight => Some(0x85),
-      Code::Space => Some(b' '),
-      Code::Tab => Some(0xb3),
-      Code::Delete => Some(0xd4),
-      Code::End => Some(0xd5),
-      Code::Home => Some(0xd2),
-      Code::Insert => Some(0xd1),
-      Code::PageDown => Some(0xd6),
-      Code::PageUp => Some(0xd3),
-      Code::ArrowDown => Some(0xd9),
-      Code::ArrowLeft => Some(0xd8),
-      Code::ArrowRight => Some(0xd7),
-      Code::ArrowUp => Some(0xda),
-      Code::NumLock => Some(0xdb),
-      Code::Numpad0 => Some(0xea),
-      Code::Numpad1 => Some(0xe1),
-      Code::Numpad2 => Some(0xe2),
-      Code::Numpad3 => Some(0xe3),
-      Code::Numpad4 => Some(0xe4),
-      Code::Numpad5 => Some(0xe5),
-      Code::Numpad6 => Some(0xe6),
-      Code::Numpad7 => Some(0xe7),
-      Code::Numpad8 => Some(0xe8),
-      Code::Numpad9 => Some(0xe9),
-      Code::NumpadAdd => Some(0xdf),
-      Code::NumpadComma => Some(b','),
-      Code::NumpadEnter => Some(0xe0),
-      Code::NumpadEqual => Some(b'='),
-      Code::NumpadHash => Some(b'#'),
-      Code::NumpadMultiply | Code::NumpadStar => Some(b'*'),
-      Code::NumpadParenLeft => Some(b'('),
-      Code::NumpadParenRight => Some(b')'),
-      Code::Escape => Some(0xb1),
-      Code::PrintScreen => Some(0xce),
-      Code::ScrollLock => Some(0xcf),
-      Code::Pause => Some(0xd0),
-      Code::F1 => Some(0xc2),
-      Code::F2 => Some(0xc3),
-      Code::F3 => Some(0xc4),
-      Code::F4 => Some(0xc5),
-      Code::F5 => Some(0xc6),
-      Code::F6 => Some(0xc7),
-      Code::F7 => Some(0xc8),
-      Code::F8 => Some(0xc9),
-      Code::F9 => Some(0xca),
-      Code::F10 => Some(0xcb),
-      Code::F11 => Some(0xcc),
-      Code::F12 => Some(0xcd),
-      Code::F13 => Some(0xf0),
-      Code::F14 => Some(0xf1),
-      Code::F15 => Some(0xf2),
-      Code::F16 => Some(0xf3),
-      Code::F17 => Some(0xf4),
-      Code::F18 => Some(0xf5),
-      Code::F19 => Some(0xf6),
-      Code::F20 => Some(0xf7),
-      Code::F21 => Some(0xf8),
-      Code::F22 => Some(0xf9),
-      Code::F23 => Some(0xfa),
-      Code::F24 => Some(0xfb),
-      _ => None,
+  pub fn is_named(&self, name: &str) -> bool {
+    self.name.eq_ignore_ascii_case(name)
+      || self
+        .aliases
+        .iter()
+        .any(|alias| alias.eq_ignore_ascii_case(name))
+  }
+}
+
+macro_rules! key {
+  ($code:expr, $name:expr, $title:expr, $category:ident, $usage:expr, [$($qmk:expr),*], [$($physical:expr),*] $(, [$($alias:expr),*])?) => {
+    Key {
+      code: $code,
+      name: $name,
+      title: $title,
+      aliases: &[$($($alias),*)?],
+      category: KeyCategory::$category,
+      physical: &[$($physical),*],
+      usage: $usage,
+      qmk: &[$($qmk),*],
+    }
+  };
+}
+
+/**
+Все клавиши протокола устройства
+
+Каждый код, каноническое название, синоним, физическая клавиша, код HID
+и название QMK встречаются в реестре не более одного раза.
+
+Коды 16 и 27 — управляющие символы ASCII (DLE и ESC), а не коды клавиш
+протокола: Del и Esc передаются кодами 0xD4 и 0xB1. Такие коды встречаются
+в старых профилях и раньше отображались как «Del» и «Esc», поэтому они
+оставлены в категории `KeyCategory::Legacy`: профили с ними по-прежнему
+читаются и отображаются, но захват клавиш и названия «Del»/«Esc» дают
+правильные коды.
+
+Заглавные латинские буквы (коды 65–90) в реестр не входят: устройство
+передаёт их с зажатым Shift, и для них используется запасная подпись.
+*/
+pub static KEYS: &[Key] = &[
+  key!(b'a', "A", "a", Letter, 0x04, ["KC_A"], [Code::KeyA]),
+  key!(b'b', "B", "b", Letter, 0x05, ["KC_B"], [Code::KeyB]),
+  key!(b'c', "C", "c", Letter, 0x06, ["KC_C"], [Code::KeyC]),
+  key!(b'd', "D", "d", Letter, 0x07, ["KC_D"], [Code::KeyD]),
+  key!(b'e', "E", "e", Letter, 0x08, ["KC_E"], [Code::KeyE]),
+  key!(b'f', "F", "f", Letter, 0x09, ["KC_F"], [Code::KeyF]),
+  key!(b'g', "G", "g", Letter, 0x0A, ["KC_G"], [Code::KeyG]),
+  key!(b'h', "H", "h", Letter, 0x0B, ["KC_H"], [Code::KeyH]),
+  key!(b'i', "I", "i", Letter, 0x0C, ["KC_I"], [Code::KeyI]),
+  key!(b'j', "J", "j", Letter, 0x0D, ["KC_J"], [Code::KeyJ]),
+  key!(b'k', "K", "k", Letter, 0x0E, ["KC_K"], [Code::KeyK]),
+  key!(b'l', "L", "l", Letter, 0x0F, ["KC_L"], [Code::KeyL]),
+  key!(b'm', "M", "m", Letter, 0x10, ["KC_M"], [Code::KeyM]),
+  key!(b'n', "N", "n", Letter, 0x11, ["KC_N"], [Code::KeyN]),
+  key!(b'o', "O", "o", Letter, 0x12, ["KC_O"], [Code::KeyO]),
+  key!(b'p', "P", "p", Letter, 0x13, ["KC_P"], [Code::KeyP]),
+  key!(b'q', "Q", "q", Letter, 0x14, ["KC_Q"], [Code::KeyQ]),
+  key!(b'r', "R", "r", Letter, 0x15, ["KC_R"], [Code::KeyR]),
+  key!(b's', "S", "s", Letter, 0x16, ["KC_S"], [Code::KeyS]),
+  key!(b't', "T", "t", Letter, 0x17, ["KC_T"], [Code::KeyT]),
+  key!(b'u', "U", "u", Letter, 0x18, ["KC_U"], [Code::KeyU]),
+  key!(b'v', "V", "v", Letter, 0x19, ["KC_V"], [Code::KeyV]),
+  key!(b'w', "W", "w", Letter, 0x1A, ["KC_W"], [Code::KeyW]),
+  key!(b'x', "X", "x", Letter, 0x1B, ["KC_X"], [Code::KeyX]),
+  key!(b'y', "Y", "y", Letter, 0x1C, ["KC_Y"], [Code::KeyY]),
+  key!(b'z', "Z", "z", Letter, 0x1D, ["KC_Z"], [Code::KeyZ]),
+  key!(b'0', "0", "0", Digit, 0x27, ["KC_0"], [Code::Digit0]),
+  key!(b'1', "1", "1", Digit, 0x1E, ["KC_1"], [Code::Digit1]),
+  key!(b'2', "2", "2", Digit, 0x1F, ["KC_2"], [Code::Digit2]),
+  key!(b'3', "3", "3", Digit, 0x20, ["KC_3"], [Code::Digit3]),
+  key!(b'4', "4", "4", Digit, 0x21, ["KC_4"], [Code::Digit4]),
+  key!(b'5', "5", "5", Digit, 0x22, ["KC_5"], [Code::Digit5]),
+  key!(b'6', "6", "6", Digit, 0x23, ["KC_6"], [Code::Digit6]),
+  key!(b'7', "7", "7", Digit, 0x24, ["KC_7"], [Code::Digit7]),
+  key!(b'8', "8", "8", Digit, 0x25, ["KC_8"], [Code::Digit8]),
+  key!(b'9', "9", "9", Digit, 0x26, ["KC_9"], [Code::Digit9]),
+  key!(b'!', "!", "!", Symbol, 0, ["KC_EXLM", "KC_EXCLAIM"], []),
+  key!(
+    b'"',
+    "\"",
+    "\"",
+    Symbol,
+    0,
+    ["KC_DQUO", "KC_DOUBLE_QUOTE", "KC_DQT"],
+    []
+  ),
+  key!(b'#', "#", "#", Symbol, 0, ["KC_HASH"], [Code::NumpadHash]),
+  key!(b'$', "$", "$", Symbol, 0, ["KC_DLR", "KC_DOLLAR"], []),
+  key!(b'%', "%", "%", Symbol, 0, ["KC_PERC", "KC_PERCENT"], []),
+  key!(b'&', "&", "&", Symbol, 0, ["KC_AMPR", "KC_AMPERSAND"], []),
+  key!(
+    b'\'',
+    "'",
+    "'",
+    Symbol,
+    0x34,
+    ["KC_QUOT", "KC_QUOTE"],
+    [Code::Quote]
+  ),
+  key!(
+    b'(',
+    "(",
+    "(",
+    Symbol,
+    0,
+    ["KC_LPRN", "KC_LEFT_PAREN"],
+    [Code::NumpadParenLeft]
+  ),
+  key!(
+    b')',
+    ")",
+    ")",
+    Symbol,
+    0,
+    ["KC_RPRN", "KC_RIGHT_PAREN"],
+    [Code::NumpadParenRight]
+  ),
+  key!(b'*', "*", "*", Symbol, 0, ["KC_ASTR", "KC_ASTERISK"], []),
+  key!(b'+', "Plus", "+", Symbol, 0, ["KC_PLUS"], []),
+  key!(
+    b',',
+    ",",
+    ",",
+    Symbol,
+    0x36,
+    ["KC_COMM", "KC_COMMA"],
+    [Code::Comma, Code::NumpadComma]
+  ),
+  key!(
+    b'-',
+    "-",
+    "-",
+    Symbol,
+    0x2D,
+    ["KC_MINS", "KC_MINUS"],
+    [Code::Minus]
+  ),
+  key!(b'.', ".", ".", Symbol, 0x37, ["KC_DOT"], [Code::Period]),
+  key!(
+    b'/',
+    "/",
+    "/",
+    Symbol,
+    0x38,
+    ["KC_SLSH", "KC_SLASH"],
+    [Code::Slash]
+  ),
+  key!(b':', ":", ":", Symbol, 0, ["KC_COLN", "KC_COLON"], []),
+  key!(
+    b';',
+    ";",
+    ";",
+    Symbol,
+    0x33,
+    ["KC_SCLN", "KC_SEMICOLON"],
+    [Code::Semicolon]
+  ),
+  key!(
+    b'<',
+    "<",
+    "<",
+    Symbol,
+    0,
+    ["KC_LABK", "KC_LEFT_ANGLE_BRACKET", "KC_LT"],
+    []
+  ),
+  key!(
+    b'=',
+    "=",
+    "=",
+    Symbol,
+    0x2E,
+    ["KC_EQL", "KC_EQUAL"],
+    [Code::Equal, Code::NumpadEqual]
+  ),
+  key!(
+    b'>',
+    ">",
+    ">",
+    Symbol,
+    0,
+    ["KC_RABK", "KC_RIGHT_ANGLE_BRACKET", "KC_GT"],
+    []
+  ),
+  key!(b'?', "?", "?", Symbol, 0, ["KC_QUES", "KC_QUESTION"], []),
+  key!(b'@', "@", "@", Symbol, 0, ["KC_AT"], []),
+  key!(
+    b'[',
+    "[",
+    "[",
+    Symbol,
+    0x2F,
+    ["KC_LBRC", "KC_LEFT_BRACKET", "KC_LBRACKET"],
+    [Code::BracketLeft]
+  ),
+  key!(
+    b'\\',
+    "\\",
+    "\\",
+    Symbol,
+    0x31,
+    ["KC_BSLS", "KC_BACKSLASH"],
+    [Code::Backslash, Code::IntlBackslash]
+  ),
+  key!(
+    b']',
+    "]",
+    "]",
+    Symbol,
+    0x30,
+    ["KC_RBRC", "KC_RIGHT_BRACKET", "KC_RBRACKET"],
+    [Code::BracketRight]
+  ),
+  key!(b'^', "^", "^", Symbol, 0, ["KC_CIRC", "KC_CIRCUMFLEX"], []),
+  key!(b'_', "_", "_", Symbol, 0, ["KC_UNDS", "KC_UNDERSCORE"], []),
+  key!(
+    b'`',
+    "`",
+    "`",
+    Symbol,
+    0x35,
+    ["KC_GRV", "KC_GRAVE"],
+    [Code::Backquote]
+  ),
+  key!(
+    b'{',
+    "{",
+    "{",
+    Symbol,
+    0,
+    ["KC_LCBR", "KC_LEFT_CURLY_BRACE"],
+    []
+  ),
+  key!(b'|', "|", "|", Symbol, 0, ["KC_PIPE"], []),
+  key!(
+    b'}',
+    "}",
+    "}",
+    Symbol,
+    0,
+    ["KC_RCBR", "KC_RIGHT_CURLY_BRACE"],
+    []
+  ),
+  key!(b'~', "~", "~", Symbol, 0, ["KC_TILD", "KC_TILDE"], []),
+  key!(
+    0x80,
+    "LCtrl",
+    "LCtrl",
+    Modifier,
+    0xE0,
+    ["KC_LCTL", "KC_LEFT_CTRL", "KC_LCTRL"],
+    [Code::ControlLeft],
+    ["Ctrl", "Control", "LControl"]
+  ),
+  key!(
+    0x81,
+    "LShift",
+    "LShift",
+    Modifier,
+    0xE1,
+    ["KC_LSFT", "KC_LEFT_SHIFT", "KC_LSHIFT"],
+    [Code::ShiftLeft],
+    ["Shift"]
+  ),
+  key!(
+    0x82,
+    "LAlt",
+    "LAlt",
+    Modifier,
+    0xE2,
+    ["KC_LALT", "KC_LEFT_ALT", "KC_LOPT"],
+    [Code::AltLeft],
+    ["Alt", "Option"]
+  ),
+  key!(
+    0x83,
+    "LWin",
+    "LWin",
+    Modifier,
+    0xE3,
+    ["KC_LGUI", "KC_LEFT_GUI", "KC_LCMD", "KC_LWIN"],
+    [Code::SuperLeft],
+    ["Win", "Super", "Meta", "Cmd", "LSuper"]
+  ),
+  key!(
+    0x84,
+    "RCtrl",
+    "RCtrl",
+    Modifier,
+    0xE4,
+    ["KC_RCTL", "KC_RIGHT_CTRL", "KC_RCTRL"],
+    [Code::ControlRight],
+    ["RControl"]
+  ),
+  key!(
+    0x85,
+    "RShift",
+    "RShift",
+    Modifier,
+    0xE5,
+    ["KC_RSFT", "KC_RIGHT_SHIFT", "KC_RSHIFT"],
+    [Code::ShiftRight]
+  ),
+  key!(
+    0x86,
+    "RAlt",
+    "RAlt",
+    Modifier,
+    0xE6,
+    ["KC_RALT", "KC_RIGHT_ALT", "KC_ROPT", "KC_ALGR"],
+    [Code::AltRight],
+    ["AltGr"]
+  ),
+  key!(
+    0x87,
+    "RWin",
+    "RWin",
+    Modifier,
+    0xE7,
+    ["KC_RGUI", "KC_RIGHT_GUI", "KC_RCMD", "KC_RWIN"],
+    [Code::SuperRight],
+    ["RSuper"]
+  ),
+  key!(
+    b' ',
+    "Space",
+    "Space",
+    Editing,
+    0x2C,
+    ["KC_SPC", "KC_SPACE"],
+    [Code::Space]
+  ),
+  key!(
+    0xB0,
+    "Enter",
+    "Enter",
+    Editing,
+    0x28,
+    ["KC_ENT", "KC_ENTER"],
+    [Code::Enter],
+    ["Return"]
+  ),
+  key!(
+    0xB1,
+    "Esc",
+    "Esc",
+    Editing,
+    0x29,
+    ["KC_ESC", "KC_ESCAPE"],
+    [Code::Escape],
+    ["Escape"]
+  ),
+  key!(
+    0xB2,
+    "Backspace",
+    "Backspace",
+    Editing,
+    0x2A,
+    ["KC_BSPC", "KC_BACKSPACE", "KC_BSPACE"],
+    [Code::Backspace],
+    ["BkSp"]
+  ),
+  key!(0xB3, "Tab", "Tab", Editing, 0x2B, ["KC_TAB"], [Code::Tab]),
+  key!(
+    0xD1,
+    "Insert",
+    "Insert",
+    Editing,
+    0x49,
+    ["KC_INS", "KC_INSERT"],
+    [Code::Insert],
+    ["Ins"]
+  ),
+  key!(
+    0xD4,
+    "Del",
+    "Del",
+    Editing,
+    0x4C,
+    ["KC_DEL", "KC_DELETE"],
+    [Code::Delete],
+    ["Delete"]
+  ),
+  key!(
+    0xD2,
+    "Home",
+    "Home",
+    Navigation,
+    0x4A,
+    ["KC_HOME"],
+    [Code::Home]
+  ),
+  key!(
+    0xD5,
+    "End",
+    "End",
+    Navigation,
+    0x4D,
+    ["KC_END"],
+    [Code::End]
+  ),
+  key!(
+    0xD3,
+    "PgUp",
+    "PgUp",
+    Navigation,
+    0x4B,
+    ["KC_PGUP", "KC_PAGE_UP"],
+    [Code::PageUp],
+    ["PageUp"]
+  ),
+  key!(
+    0xD6,
+    "PgDn",
+    "PgDn",
+    Navigation,
+    0x4E,
+    ["KC_PGDN", "KC_PAGE_DOWN", "KC_PGDOWN"],
+    [Code::PageDown],
+    ["PageDown"]
+  ),
+  key!(
+    0xDA,
+    "Up",
+    "↑",
+    Navigation,
+    0x52,
+    ["KC_UP"],
+    [Code::ArrowUp],
+    ["↑", "ArrowUp"]
+  ),
+  key!(
+    0xD7,
+    "Right",
+    "→",
+    Navigation,
+    0x4F,
+    ["KC_RGHT", "KC_RIGHT"],
+    [Code::ArrowRight],
+    ["→", "ArrowRight"]
+  ),
+  key!(
+    0xD9,
+    "Down",
+    "↓",
+    Navigation,
+    0x51,
+    ["KC_DOWN"],
+    [Code::ArrowDown],
+    ["↓", "ArrowDown"]
+  ),
+  key!(
+    0xD8,
+    "Left",
+    "←",
+    Navigation,
+    0x50,
+    ["KC_LEFT"],
+    [Code::ArrowLeft],
+    ["←", "ArrowLeft"]
+  ),
+  key!(0xC2, "F1", "F1", Function, 0x3A, ["KC_F1"], [Code::F1]),
+  key!(0xC3, "F2", "F2", Function, 0x3B, ["KC_F2"], [Code::F2]),
+  key!(0xC4, "F3", "F3", Function, 0x3C, ["KC_F3"], [Code::F3]),
+  key!(0xC5, "F4", "F4", Function, 0x3D, ["KC_F4"], [Code::F4]),
+  key!(0xC6, "F5", "F5", Function, 0x3E, ["KC_F5"], [Code::F5]),
+  key!(0xC7, "F6", "F6", Function, 0x3F, ["KC_F6"], [Code::F6]),
+  key!(0xC8, "F7", "F7", Function, 0x40, ["KC_F7"], [Code::F7]),
+  key!(0xC9, "F8", "F8", Function, 0x41, ["KC_F8"], [Code::F8]),
+  key!(0xCA, "F9", "F9", Function, 0x42, ["KC_F9"], [Code::F9]),
+  key!(0xCB, "F10", "F10", Function, 0x43, ["KC_F10"], [Code::F10]),
+  key!(0xCC, "F11", "F11", Function, 0x44, ["KC_F11"], [Code::F11]),
+  key!(0xCD, "F12", "F12", Function, 0x45, ["KC_F12"], [Code::F12]),
+  key!(0xF0, "F13", "F13", Function, 0x68, ["KC_F13"], [Code::F13]),
+  key!(0xF1, "F14", "F14", Function, 0x69, ["KC_F14"], [Code::F14]),
+  key!(0xF2, "F15", "F15", Function, 0x6A, ["KC_F15"], [Code::F15]),
+  key!(0xF3, "F16", "F16", Function, 0x6B, ["KC_F16"], [Code::F16]),
+  key!(0xF4, "F17", "F17", Function, 0x6C, ["KC_F17"], [Code::F17]),
+  key!(0xF5, "F18", "F18", Function, 0x6D, ["KC_F18"], [Code::F18]),
+  key!(0xF6, "F19", "F19", Function, 0x6E, ["KC_F19"], [Code::F19]),
+  key!(0xF7, "F20", "F20", Function, 0x6F, ["KC_F20"], [Code::F20]),
+  key!(0xF8, "F21", "F21", Function, 0x70, ["KC_F21"], [Code::F21]),
+  key!(0xF9, "F22", "F22", Function, 0x71, ["KC_F22"], [Code::F22]),
+  key!(0xFA, "F23", "F23", Function, 0x72, ["KC_F23"], [Code::F23]),
+  key!(0xFB, "F24", "F24", Function, 0x73, ["KC_F24"], [Code::F24]),
+  key!(
+    0xDC,
+    "NumSlash",
+    "Num/",
+    Numpad,
+    0x54,
+    ["KC_PSLS", "KC_KP_SLASH"],
+    [Code::NumpadDivide],
+    ["NumDivide"]
+  ),
+  key!(
+    0xDD,
+    "NumStar",
+    "Num*",
+    Numpad,
+    0x55,
+    ["KC_PAST", "KC_KP_ASTERISK"],
+    [Code::NumpadMultiply, Code::NumpadStar],
+    ["NumMultiply"]
+  ),
+  key!(
+    0xDE,
+    "NumMinus",
+    "Num-",
+    Numpad,
+    0x56,
+    ["KC_PMNS", "KC_KP_MINUS"],
+    [Code::NumpadSubtract],
+    ["NumSubtract"]
+  ),
+  key!(
+    0xDF,
+    "NumPlus",
+    "Num+",
+    Numpad,
+    0x57,
+    ["KC_PPLS", "KC_KP_PLUS"],
+    [Code::NumpadAdd],
+    ["NumAdd"]
+  ),
+  key!(
+    0xE0,
+    "NumEnter",
+    "NumEnter",
+    Numpad,
+    0x58,
+    ["KC_PENT", "KC_KP_ENTER"],
+    [Code::NumpadEnter]
+  ),
+  key!(
+    0xE1,
+    "Num1",
+    "Num1",
+    Numpad,
+    0x59,
+    ["KC_P1", "KC_KP_1"],
+    [Code::Numpad1]
+  ),
+  key!(
+    0xE2,
+    "Num2",
+    "Num2",
+    Numpad,
+    0x5A,
+    ["KC_P2", "KC_KP_2"],
+    [Code::Numpad2]
+  ),
+  key!(
+    0xE3,
+    "Num3",
+    "Num3",
+    Numpad,
+    0x5B,
+    ["KC_P3", "KC_KP_3"],
+    [Code::Numpad3]
+  ),
+  key!(
+    0xE4,
+    "Num4",
+    "Num4",
+    Numpad,
+    0x5C,
+    ["KC_P4", "KC_KP_4"],
+    [Code::Numpad4]
+  ),
+  key!(
+    0xE5,
+    "Num5",
+    "Num5",
+    Numpad,
+    0x5D,
+    ["KC_P5", "KC_KP_5"],
+    [Code::Numpad5]
+  ),
+  key!(
+    0xE6,
+    "Num6",
+    "Num6",
+    Numpad,
+    0x5E,
+    ["KC_P6", "KC_KP_6"],
+    [Code::Numpad6]
+  ),
+  key!(
+    0xE7,
+    "Num7",
+    "Num7",
+    Numpad,
+    0x5F,
+    ["KC_P7", "KC_KP_7"],
+    [Code::Numpad7]
+  ),
+  key!(
+    0xE8,
+    "Num8",
+    "Num8",
+    Numpad,
+    0x60,
+    ["KC_P8", "KC_KP_8"],
+    [Code::Numpad8]
+  ),
+  key!(
+    0xE9,
+    "Num9",
+    "Num9",
+    Numpad,
+    0x61,
+    ["KC_P9", "KC_KP_9"],
+    [Code::Numpad9]
+  ),
+  key!(
+    0xEA,
+    "Num0",
+    "Num0",
+    Numpad,
+    0x62,
+    ["KC_P0", "KC_KP_0"],
+    [Code::Numpad0]
+  ),
+  key!(
+    0xEB,
+    "NumDot",
+    "Num.",
+    Numpad,
+    0x63,
+    ["KC_PDOT", "KC_KP_DOT"],
+    [Code::NumpadDecimal],
+    ["NumDecimal"]
+  ),
+  key!(
+    0xC1,
+    "CapsLock",
+    "CapsLock",
+    System,
+    0x39,
+    ["KC_CAPS", "KC_CAPS_LOCK", "KC_CAPSLOCK"],
+    [Code::CapsLock],
+    ["Caps"]
+  ),
+  key!(
+    0xDB,
+    "NumLock",
+    "NumLock",
+    System,
+    0x53,
+    ["KC_NUM", "KC_NUM_LOCK", "KC_NLCK"],
+    [Code::NumLock]
+  ),
+  key!(
+    0xCF,
+    "ScrollLock",
+    "ScrollLock",
+    System,
+    0x47,
+    ["KC_SCRL", "KC_SCROLL_LOCK", "KC_SLCK"],
+    [Code::ScrollLock]
+  ),
+  key!(
+    0xCE,
+    "PrScr",
+    "PrScr",
+    System,
+    0x46,
+    ["KC_PSCR", "KC_PRINT_SCREEN"],
+    [Code::PrintScreen],
+    ["PrintScreen"]
+  ),
+  key!(
+    0xD0,
+    "Pause",
+    "Pause",
+    System,
+    0x48,
+    ["KC_PAUS", "KC_PAUSE", "KC_BRK"],
+    [Code::Pause],
+    ["Break"]
+  ),
+  key!(0x10, "LegacyDel", "Del", Legacy, 0, [], []),
+  key!(0x1B, "LegacyEsc", "Esc", Legacy, 0, [], []),
+];
+
+/**
+Возвращает клавишу по коду протокола
+
+# Аргументы
+* `code` - Код клавиши
+*/
+pub fn by_code(code: u8) -> Option<&'static Key> {
+  KEYS.iter().find(|key| key.code == code)
+}
+
+/**
+Приводит комбинацию к виду для сравнения
+
+Правые модификаторы заменяются левыми, пустые позиции и повторы
+отбрасываются, коды сортируются.
+*/
+pub fn chord_set(codes: &[u8]) -> Vec<u8> {
+  let mut chord = codes
+    .iter()
+    .filter(|&&code| code != 0)
+    .map(|&code| match code {
+      0x84..=0x87 => code - 4,
+      code => code,
+    })
+    .collect::<Vec<_>>();
+  chord.sort_unstable();
+  chord.dedup();
+  chord
+}
+
+/**
+Возвращает клавишу по названию или синониму без учёта регистра
+
+# Аргументы
+* `name` - Название клавиши
+*/
+pub fn by_name(name: &str) -> Option<&'static Key> {
+  KEYS.iter().find(|key| key.is_named(name))
+}
+
+/**
+Возвращает клавишу, соответствующую физической клавише Iced
+
+# Аргументы
+* `physical` - Физический код нажатой клавиши
+
+# Возвращает
+`Some(&Key)` или `None`, если клавиша не поддерживается устройством
+*/
+pub fn by_physical(physical: Code) -> Option<&'static Key> {
+  KEYS.iter().find(|key| key.physical.contains(&physical))
+}
+
+/**
+Возвращает подпись клавиши для отображения
+
+Клавиши вне реестра подписываются печатным символом ASCII,
+а остальные коды — в шестнадцатеричном виде `0xNN`.
+
+# Аргументы
+* `code` - Код клавиши
+*/
+pub fn code_to_title(code: u8) -> String {
+  match by_code(code) {
+    Some(key) => key.title.to_string(),
+    None if code.is_ascii_graphic() => char::from(code).to_string(),
+    None => format!("0x{code:02X}"),
+  }
+}
+
+/**
+Возвращает код клавиши по названию
+
+Принимает названия и синонимы из `KEYS` без учёта регистра
+и шестнадцатеричную запись `0xNN`.
+
+# Аргументы
+* `name` - Название клавиши
+
+# Возвращает
+`Some(u8)` с кодом клавиши или `None` для неизвестного названия
+*/
+pub fn name_to_code(name: &str) -> Option<u8> {
+  if let Some(hex) = name.strip_prefix("0x").or_else(|| name.strip_prefix("0X")) {
+    return u8::from_str_radix(hex, 16).ok();
+  }
+
+  by_name(name).map(|key| key.code)
+}
+
+/**
+Возвращает название клавиши для текстового формата профилей
+
+Коды вне реестра записываются в шестнадцатеричном виде `0xNN`,
+поэтому преобразование без потерь.
+
+# Аргументы
+* `code` - Код клавиши
+*/
+pub fn code_to_name(code: u8) -> String {
+  match by_code(code) {
+    Some(key) => key.name.to_string(),
+    None => format!("0x{code:02X}"),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn every_code_round_trips() {
+    for code in 0..=u8::MAX {
+      let name = code_to_name(code);
+      assert_eq!(name_to_code(&name), Some(code), "{name}");
+      assert_eq!(
+        name_to_code(&name.to_lowercase()),
+        Some(code),
+        "{name} в нижнем регистре"
+      );
+    }
+  }
+
+  #[test]
+  fn every_key_is_unique() {
+    for (i, key) in KEYS.iter().enumerate() {
+      for other in &KEYS[i + 1..] {
+        assert_ne!(key.code, other.code, "{} и {}", key.name, other.name);
+        assert!(!other.is_named(key.name), "{} и {}", key.name, other.name);
+        for alias in key.aliases {
+          assert!(
+            !other.is_named(alias),
+            "{alias}: {} и {}",
+            key.name,
+            other.name
+          );
+        }
+        for physical in key.physical {
+          assert!(
+            !other.physical.contains(physical),
+            "{physical:?}: {} и {}",
+            key.name,
+            other.name
+          );
+        }
+        assert!(
+          key.usage == 0 || key.usage != other.usage,
+          "0x{:02X}: {} и {}",
+          key.usage,
+          key.name,
+          other.name
+        );
+        for qmk in key.qmk {
+          assert!(
+            !other.qmk.contains(qmk),
+            "{qmk}: {} и {}",
+            key.name,
+            other.name
+          );
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn every_key_resolves_to_itself() {
+    for key in KEYS {
+      assert_eq!(by_code(key.code), Some(key));
+      assert_eq!(by_name(key.name), Some(key));
+      for alias in key.aliases {
+        assert_eq!(by_name(alias), Some(key), "{alias}");
+      }
+      for physical in key.physical {
+        assert_eq!(by_physical(*physical), Some(key), "{physical:?}");
+      }
+      assert!(
+        !key.name.is_empty() && !key.name.contains(['+', ' ']) && !key.name.starts_with("0x"),
+        "{}",
+        key.name
+      );
+    }
+  }
+
+  #[test]
+  fn numpad_has_titles() {
+    for code in 0xDC..=0xEB {
+      assert!(code_to_title(code).starts_with("Num"), "{code:#04X}");
     }
+    assert_eq!(code_to_title(0x10), "Del");
+    assert_eq!(code_to_title(0xFF), "0xFF");
+    assert_eq!(code_to_title(b'A'), "A");
   }
 }
@@ -0,0 +1,269 @@
+/*!
+Сравнение и слияние двух профилей.
+
+Профили для сравнения берутся из библиотеки, ячеек ОЗУ и ПЗУ кейпада
+или из файла. Сравнение показывает отличающиеся имя, мёртвую зону,
+кнопки и направления стика; любое отличающееся поле можно перенести
+из одного профиля в другой.
+*/
+
+use std::fmt;
+
+use crate::data::{cells::Cell, profiles::Profile};
+
+/// Источник сравниваемого профиля
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+  /// Профиль библиотеки по индексу
+  Library(usize),
+  /// Профиль ОЗУ кейпада 1..=4
+  Ram(usize),
+  /// Профиль ПЗУ кейпада 1..=4
+  Rom(usize),
+  /// Профиль из открытого файла по индексу в списке загруженных
+  File(usize),
+}
+
+/// Элемент списка выбора источника
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceEntry {
+  /// Источник профиля
+  pub source: Source,
+  /// Подпись в списке
+  pub title: String,
+}
+
+impl fmt::Display for SourceEntry {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", self.title)
+  }
+}
+
+/// Сравниваемое поле профиля
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+  /// Имя профиля
+  Name,
+  /// Мёртвая зона стика
+  Deadzone,
+  /// Кнопка или направление стика
+  Cell(Cell),
+}
+
+impl Field {
+  /// Все поля профиля в порядке отображения
+  pub fn all() -> impl Iterator<Item = Self> {
+    [Self::Name, Self::Deadzone]
+      .into_iter()
+      .chain((1..=16).map(|id| Self::Cell(Cell::Button(id))))
+      .chain((1..=4).map(|id| Self::Cell(Cell::Stick(id))))
+  }
+
+  /// Возвращает название поля для интерфейса
+  pub fn title(&self) -> String {
+    match self {
+      Self::Name => "Имя".to_string(),
+      Self::Deadzone => "Мёртвая зона".to_string(),
+      Self::Cell(cell) => cell.title(),
+    }
+  }
+
+  /// Проверяет, отличается ли поле в двух профилях
+  pub fn differs(&self, a: &Profile, b: &Profile) -> bool {
+    match self {
+      Self::Name => a.name != b.name,
+      Self::Deadzone => a.stick.deadzone != b.stick.deadzone,
+      Self::Cell(cell) => cell.get(a) != cell.get(b),
+    }
+  }
+
+  /**
+  Переносит значение поля из одного профиля в другой
+
+  # Аргументы
+  * `from` - Профиль, из которого берётся значение
+  * `to` - Изменяемый профиль
+  */
+  pub fn copy(&self, from: &Profile, to: &mut Profile) {
+    match self {
+      Self::Name => to.name = from.name.clone(),
+      Self::Deadzone => to.stick.deadzone = from.stick.deadzone,
+      // Ячейки одного вида: назначение всегда помещается
+      Self::Cell(cell) => {
+        let _ = cell.set(to, cell.get(from));
+      }
+    }
+  }
+}
+
+/// Возвращает поля, которые отличаются в двух профилях
+pub fn diff(a: &Profile, b: &Profile) -> Vec<Field> {
+  Field::all().filter(|field| field.differs(a, b)).collect()
+}
+
+/// Сторона сравнения: источник и снимок профиля
+#[derive(Debug, Clone)]
+pub struct Side {
+  /// Источник профиля
+  pub source: Source,
+  /// Профиль с учётом перенесённых в него полей
+  pub profile: Profile,
+}
+
+/// Профиль, загруженный из файла для сравнения
+#[derive(Debug, Clone)]
+pub struct FileProfile {
+  /// Имя файла
+  pub file: String,
+  /// Профиль из файла
+  pub profile: Profile,
+}
+
+/// Состояние экрана сравнения
+#[derive(Debug, Clone, Default)]
+pub struct Comparison {
+  /// Левый профиль
+  pub left: Option<Side>,
+  /// Правый профиль
+  pub right: Option<Side>,
+  /// Профили из открытых файлов
+  pub files: Vec<FileProfile>,
+  /// Показывать только отличающиеся поля
+  pub only_differences: bool,
+}
+
+impl Comparison {
+  /// Возвращает сторону сравнения
+  pub fn side(&self, right: bool) -> Option<&Side> {
+    match right {
+      true => self.right.as_ref(),
+      false => self.left.as_ref(),
+    }
+  }
+
+  /// Возвращает изменяемую сторону сравнения
+  pub fn side_mut(&mut self, right: bool) -> &mut Option<Side> {
+    match right {
+      true => &mut self.right,
+      false => &mut self.left,
+    }
+  }
+
+  /// Возвращает отличающиеся поля, если выбраны оба профиля
+  pub fn diff(&self) -> Vec<Field> {
+    match (&self.left, &self.right) {
+      (Some(left), Some(right)) => diff(&left.profile, &right.profile),
+      _ => Vec::new(),
+    }
+  }
+
+  /**
+  Обновляет стороны после удаления профиля библиотеки
+
+  Сторона с удалённым профилем сбрасывается, индексы следующих профилей сдвигаются.
+
+  # Аргументы
+  * `idx` - Индекс удалённого профиля
+  */
+  pub fn library_removed(&mut self, idx: usize) {
+    for side in [&mut self.left, &mut self.right] {
+      match side.as_ref().map(|side| side.source) {
+        Some(Source::Library(i)) if i == idx => *side = None,
+        Some(Source::Library(i)) if i > idx => {
+          if let Some(side) = side.as_mut() {
+            side.source = Source::Library(i - 1);
+          }
+        }
+        _ => {}
+      }
+    }
+  }
+
+  /**
+  Обновляет стороны после перечитывания библиотеки
+
+  Сторона, профиль которой не найден в перечитанной библиотеке, сбрасывается.
+
+  # Аргументы
+  * `remap` - Новый индекс профиля по прежнему индексу
+  */
+  pub fn library_remap(&mut self, remap: impl Fn(usize) -> Option<usize>) {
+    for side in [&mut self.left, &mut self.right] {
+      if let Some(Source::Library(i)) = side.as_ref().map(|side| side.source) {
+        match remap(i) {
+          Some(idx) => {
+            if let Some(side) = side.as_mut() {
+              side.source = Source::Library(idx);
+            }
+          }
+          None => *side = None,
+        }
+      }
+    }
+  }
+
+  /**
+  Переносит поле из одного профиля в другой
+
+  # Аргументы
+  * `field` - Переносимое поле
+  * `to_right` - Направление: из левого в правый (`true`) или обратно
+
+  # Возвращает
+  Сторону, получившую значение, или `None`, если профили не выбраны
+  */
+  pub fn pick(&mut self, field: Field, to_right: bool) -> Option<&Side> {
+    let (Some(left), Some(right)) = (self.left.as_mut(), self.right.as_mut()) else {
+      return None;
+    };
+    let (from, to) = match to_right {
+      true => (left, right),
+      false => (right, left),
+    };
+    field.copy(&from.profile, &mut to.profile);
+    Some(to)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn diff_and_pick() {
+    let left = Profile {
+      name: "GIMP".to_string(),
+      ..Default::default()
+    };
+    let mut right = left.clone();
+    right.buttons[2] = [0x80, b'z', 0, 0, 0, 0];
+    right.stick.word[3] = b'a';
+    right.stick.deadzone += 5;
+
+    assert_eq!(
+      diff(&left, &right),
+      vec![
+        Field::Deadzone,
+        Field::Cell(Cell::Button(3)),
+        Field::Cell(Cell::Stick(4))
+      ]
+    );
+
+    let mut comparison = Comparison {
+      left: Some(Side {
+        source: Source::Library(0),
+        profile: left,
+      }),
+      right: Some(Side {
+        source: Source::Ram(1),
+        profile: right,
+      }),
+      ..Default::default()
+    };
+    let side = comparison
+      .pick(Field::Cell(Cell::Button(3)), false)
+      .unwrap();
+    assert_eq!(side.source, Source::Library(0));
+    assert_eq!(comparison.diff().len(), 2);
+  }
+}
@@ -1,10 +1,12 @@
+use serde::{Deserialize, Serialize};
+
 /**
 Информация об устройстве, полученная по запросу через протокол
 
 Содержит основные характеристики подключенного устройства,
 включая версию прошивки, модель и серийные данные.
 */
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct Device {
   /// Версия прошивки
   pub firmware_version: u16,
@@ -2,39 +2,209 @@
 Диалоги открытия/чтения профилей из файловой системы.
 
 Этот модуль предоставляет функциональность для работы с файловыми диалогами,
-позволяя пользователю выбирать и загружать профили из TOML-файлов.
+позволяя пользователю выбирать и загружать профили из файлов RON, JSON и TOML.
 */
 
-use std::path::Path;
+use std::{fs, path::Path};
 
 use iced::Task;
 
-use crate::{assets::APPLICATION_NAME, data::profiles::Profile, ui::update::Message};
+use crate::{
+  data::{
+    backup::Backup,
+    banks::Bank,
+    config_dir,
+    keymaps::{Keymap, KeymapFormat},
+    layout::KeyboardLayout,
+    profiles::Profile,
+    qmk,
+    readable::{ProfileFormat, import_file, to_string},
+    settings::{Settings, StorageBackend},
+    storage::write_atomic,
+  },
+  ui::update::Message,
+};
 
 impl Profile {
   /**
-  Открывает асинхронный диалог выбора TOML-файла профиля и инициирует его загрузку
+  Открывает асинхронный диалог выбора файла профилей и инициирует его загрузку
 
   Диалог открывается в директории конфигурации приложения и фильтрует
-  только файлы с расширением .ron.
+  файлы с расширениями .ron, .json и .toml.
 
   # Возвращает
   Асинхронную задачу, которая при завершении отправит сообщение
-  `Message::ProfileFileWrite` с загруженным профилем
+  `Message::ProfileImported` с профилями файла любой поддерживаемой версии
   */
   pub fn open_load_file_dialog() -> Task<Message> {
-    let file_path = confy::get_configuration_file_path(APPLICATION_NAME, None).unwrap();
-    let dir_path = file_path.parent().unwrap().to_path_buf();
     Task::future(
       rfd::AsyncFileDialog::new()
-        .add_filter("Config Formats", &["ron"])
-        .set_directory(dir_path)
+        .add_filter("Config Formats", &["ron", "json", "toml"])
+        .set_directory(config_dir())
         .pick_file(),
     )
     .then(|handle| match handle {
-      Some(ref handle) => {
-        let profile = Profile::load_file(Self::load_file_handle(handle));
-        Task::done(Message::ProfileImported(profile))
+      Some(ref handle) => match import_file(Self::load_file_handle(handle)) {
+        Ok(library) => Task::done(Message::ProfileImported(library)),
+        Err(err) => Task::done(Message::LibraryStatus(format!(
+          "Не удалось импортировать профили: {err}"
+        ))),
+      },
+      None => Task::none(),
+    })
+  }
+
+  /**
+  Открывает асинхронный диалог выбора файла профилей для сравнения
+
+  # Аргументы
+  * `right` - Сторона сравнения, для которой выбирается профиль
+
+  # Возвращает
+  Асинхронную задачу, которая при завершении отправит сообщение
+  `Message::CompareFileLoaded` с профилями файла
+  */
+  pub fn open_compare_file_dialog(right: bool) -> Task<Message> {
+    Task::future(
+      rfd::AsyncFileDialog::new()
+        .add_filter("Config Formats", &["ron", "json", "toml"])
+        .set_directory(config_dir())
+        .pick_file(),
+    )
+    .then(move |handle| match handle {
+      Some(ref handle) => match import_file(Self::load_file_handle(handle)) {
+        Ok(library) => Task::done(Message::CompareFileLoaded(
+          right,
+          handle.file_name(),
+          library.profiles,
+        )),
+        Err(err) => Task::done(Message::LibraryStatus(format!(
+          "Не удалось открыть файл для сравнения: {err}"
+        ))),
+      },
+      None => Task::none(),
+    })
+  }
+
+  /**
+  Открывает асинхронный диалог сохранения и экспортирует профили и наборы
+  в текстовом формате
+
+  Формат файла (RON, JSON или TOML) определяется по выбранному расширению,
+  без расширения используется `format`.
+
+  # Аргументы
+  * `profiles` - Экспортируемые профили
+  * `banks` - Экспортируемые наборы
+  * `format` - Формат по умолчанию
+  * `layout` - Раскладка для подписей клавиш в файле
+
+  # Возвращает
+  Асинхронную задачу, которая при завершении отправит сообщение
+  `Message::LibraryStatus` с результатом экспорта
+  */
+  pub fn open_save_file_dialog(
+    profiles: Vec<Profile>,
+    banks: Vec<Bank>,
+    format: ProfileFormat,
+    layout: KeyboardLayout,
+  ) -> Task<Message> {
+    let mut dialog = rfd::AsyncFileDialog::new()
+      .set_directory(config_dir())
+      .set_file_name(format!("profiles.{}", format.extension()))
+      .add_filter(format.to_string(), &[format.extension()]);
+    for other in ProfileFormat::ALL.into_iter().filter(|f| *f != format) {
+      dialog = dialog.add_filter(other.to_string(), &[other.extension()]);
+    }
+
+    Task::future(dialog.save_file()).then(move |handle| match handle {
+      Some(handle) => {
+        let path = handle.path().to_path_buf();
+        let format = ProfileFormat::from_path(&path).unwrap_or(format);
+        let status = match to_string(&profiles, &banks, format, layout)
+          .and_then(|text| write_atomic(&path, &text))
+        {
+          Ok(()) => format!("Профили экспортированы: {}", path.display()),
+          Err(err) => format!("Не удалось экспортировать профили: {err}"),
+        };
+        Task::done(Message::LibraryStatus(status))
+      }
+      None => Task::none(),
+    })
+  }
+
+  /**
+  Открывает асинхронный диалог выбора раскладки QMK или VIA и импортирует её
+
+  Если в файле нет имени раскладки, профиль называется по имени файла.
+
+  # Возвращает
+  Асинхронную задачу, которая при завершении отправит сообщение
+  `Message::QmkImported` с профилем и непереносимыми клавишами
+  */
+  pub fn open_qmk_import_dialog() -> Task<Message> {
+    Task::future(
+      rfd::AsyncFileDialog::new()
+        .add_filter("QMK/VIA JSON", &["json"])
+        .pick_file(),
+    )
+    .then(|handle| match handle {
+      Some(handle) => {
+        let path = handle.path();
+        let name = path
+          .file_stem()
+          .map(|stem| stem.to_string_lossy().into_owned())
+          .unwrap_or_default();
+        let import = fs::read_to_string(path)
+          .map_err(anyhow::Error::from)
+          .and_then(|text| qmk::import(&text, &name));
+        match import {
+          Ok(import) => Task::done(Message::QmkImported(import)),
+          Err(err) => Task::done(Message::LibraryStatus(format!(
+            "Не удалось импортировать раскладку QMK: {err:#}"
+          ))),
+        }
+      }
+      None => Task::none(),
+    })
+  }
+
+  /**
+  Открывает асинхронный диалог сохранения и экспортирует профиль
+  в раскладку QMK `keymap.json`
+
+  # Аргументы
+  * `profile` - Экспортируемый профиль
+
+  # Возвращает
+  Асинхронную задачу, которая при завершении отправит сообщение
+  `Message::LibraryStatus` с результатом экспорта и непереносимыми кнопками
+  */
+  pub fn open_qmk_export_dialog(profile: Profile) -> Task<Message> {
+    Task::future(
+      rfd::AsyncFileDialog::new()
+        .set_directory(config_dir())
+        .set_file_name("keymap.json")
+        .add_filter("QMK keymap.json", &["json"])
+        .save_file(),
+    )
+    .then(move |handle| match handle {
+      Some(handle) => {
+        let path = handle.path().to_path_buf();
+        let status = match qmk::export(&profile)
+          .and_then(|(text, skipped)| write_atomic(&path, &text).map(|_| skipped))
+        {
+          Ok(skipped) if skipped.is_empty() => {
+            format!("Раскладка QMK сохранена: {}", path.display())
+          }
+          Ok(skipped) => format!(
+            "Раскладка QMK сохранена: {}. Не перенесены: {}",
+            path.display(),
+            skipped.join("; ")
+          ),
+          Err(err) => format!("Не удалось экспортировать раскладку QMK: {err:#}"),
+        };
+        Task::done(Message::LibraryStatus(status))
       }
       None => Task::none(),
     })
@@ -53,3 +223,83 @@ impl Profile {
     handle.path()
   }
 }
+
+impl Backup {
+  /**
+  Открывает асинхронный диалог выбора файла резервной копии и загружает его
+
+  Диалог открывается в директории резервных копий.
+
+  # Возвращает
+  Асинхронную задачу, которая при завершении отправит сообщение
+  `Message::BackupRestoreLoaded` с загруженной копией
+  */
+  pub fn open_load_file_dialog() -> Task<Message> {
+    Task::future(
+      rfd::AsyncFileDialog::new()
+        .add_filter("Claws Backup", &["ron"])
+        .set_directory(Self::dir())
+        .pick_file(),
+    )
+    .then(|handle| match handle {
+      Some(handle) => match Backup::load_file(handle.path()) {
+        Ok(backup) => Task::done(Message::BackupRestoreLoaded(backup)),
+        Err(err) => Task::done(Message::BackupStatus(format!(
+          "Не удалось прочитать резервную копию: {err}"
+        ))),
+      },
+      None => Task::none(),
+    })
+  }
+}
+
+impl Keymap {
+  /**
+  Открывает асинхронный диалог выбора файла сочетаний клавиш приложения
+
+  Формат файла определяется по имени и содержимому, поэтому фильтр
+  по расширению не задаётся: `shortcutsrc` и `menurc` GIMP его не имеют.
+
+  # Возвращает
+  Асинхронную задачу, которая при завершении отправит сообщение
+  `Message::KeymapLoaded` с командами раскладки
+  */
+  pub fn open_file_dialog() -> Task<Message> {
+    Task::future(rfd::AsyncFileDialog::new().pick_file()).then(|handle| match handle {
+      Some(handle) => {
+        let path = handle.path();
+        let keymap = fs::read_to_string(path)
+          .map_err(anyhow::Error::from)
+          .and_then(|text| match KeymapFormat::detect(path, &text) {
+            Some(format) => Keymap::parse(format, handle.file_name(), &text),
+            None => anyhow::bail!("формат файла не распознан"),
+          });
+        match keymap {
+          Ok(keymap) => Task::done(Message::KeymapLoaded(keymap)),
+          Err(err) => Task::done(Message::KeymapStatus(format!(
+            "Не удалось импортировать сочетания: {err:#}"
+          ))),
+        }
+      }
+      None => Task::none(),
+    })
+  }
+}
+
+impl Settings {
+  /**
+  Открывает асинхронный диалог выбора директории для хранения профилей
+
+  # Возвращает
+  Асинхронную задачу, которая при выборе директории отправит сообщение
+  `Message::StorageChanged` с новым местом хранения
+  */
+  pub fn open_storage_dir_dialog() -> Task<Message> {
+    Task::future(rfd::AsyncFileDialog::new().pick_folder()).then(|handle| match handle {
+      Some(handle) => Task::done(Message::StorageChanged(StorageBackend::Directory(
+        handle.path().to_path_buf(),
+      ))),
+      None => Task::none(),
+    })
+  }
+}
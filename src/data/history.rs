@@ -0,0 +1,288 @@
+/*!
+История изменений профилей в редакторе.
+
+Для каждого профиля библиотеки хранится список состояний за сессию:
+первое — состояние до первого изменения, каждое следующее — после
+очередного изменения. Отмена и повтор перемещают текущую позицию
+по списку, а выбор записи в истории возвращает профиль к ней.
+Частые однотипные изменения (ползунок мёртвой зоны, ввод имени)
+объединяются в одну запись.
+*/
+
+use std::time::{Duration, Instant};
+
+use crate::data::profiles::Profile;
+
+/// Максимальное количество записей истории одного профиля
+const HISTORY_LIMIT: usize = 100;
+
+/// Интервал, в течение которого однотипные изменения объединяются
+const COALESCE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Вид изменения профиля
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+  /// Состояние до первого изменения
+  Initial,
+  /// Изменение комбинации кнопки 1..=16
+  Button(usize),
+  /// Изменение нескольких кнопок сразу
+  Buttons,
+  /// Изменение направления стика 1..=4
+  Stick(usize),
+  /// Изменение мёртвой зоны стика
+  Deadzone,
+  /// Переименование профиля
+  Rename,
+  /// Изменение описания, тегов или подписей кнопок
+  Metadata,
+  /// Запись профиля в ячейку кейпада 1..=4 (ОЗУ или ПЗУ)
+  SlotWrite(usize, bool),
+  /// Профиль изменён вне редактора: импорт или другой экземпляр программы
+  External,
+}
+
+impl Change {
+  /**
+  Определяет вид изменения по двум состояниям профиля
+
+  # Возвращает
+  `None`, если профили не отличаются
+  */
+  pub fn between(old: &Profile, new: &Profile) -> Option<Self> {
+    let buttons = (0..old.buttons.len())
+      .filter(|&i| old.buttons[i] != new.buttons[i])
+      .collect::<Vec<_>>();
+    let stick = (0..old.stick.word.len()).find(|&i| old.stick.word[i] != new.stick.word[i]);
+
+    match () {
+      _ if buttons.len() > 1 => Some(Self::Buttons),
+      _ if buttons.len() == 1 => Some(Self::Button(buttons[0] + 1)),
+      _ if stick.is_some() => stick.map(|i| Self::Stick(i + 1)),
+      _ if old.stick.deadzone != new.stick.deadzone => Some(Self::Deadzone),
+      _ if old.name != new.name => Some(Self::Rename),
+      _ if old.meta != new.meta => Some(Self::Metadata),
+      _ => None,
+    }
+  }
+}
+
+/// Запись истории
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+  /// Вид изменения
+  pub change: Change,
+  /// Состояние профиля после изменения
+  pub profile: Profile,
+  /// Время изменения
+  pub time: Instant,
+}
+
+impl HistoryEntry {
+  /// Возвращает описание записи для списка истории
+  pub fn title(&self) -> String {
+    match self.change {
+      Change::Initial => "Исходное состояние".to_string(),
+      Change::Button(id) => format!("Кнопка #{id}"),
+      Change::Buttons => "Несколько кнопок".to_string(),
+      Change::Stick(id) => format!("Стик #{id}"),
+      Change::Deadzone => format!("Мёртвая зона: {}%", self.profile.stick.deadzone),
+      Change::Rename => format!("Имя: {}", self.profile.name),
+      Change::Metadata => "Описание и подписи".to_string(),
+      Change::SlotWrite(id, true) => format!("Запись в ПЗУ {id}"),
+      Change::SlotWrite(id, false) => format!("Запись в ОЗУ {id}"),
+      Change::External => "Изменён вне редактора".to_string(),
+    }
+  }
+}
+
+/// История изменений одного профиля
+#[derive(Debug, Clone)]
+pub struct History {
+  /// Записи истории, первая — исходное состояние
+  entries: Vec<HistoryEntry>,
+  /// Индекс текущего состояния
+  position: usize,
+}
+
+impl History {
+  /**
+  Создает историю с исходным состоянием профиля
+
+  # Аргументы
+  * `profile` - Профиль до первого изменения
+  */
+  pub fn new(profile: Profile) -> Self {
+    Self {
+      entries: vec![HistoryEntry {
+        change: Change::Initial,
+        profile,
+        time: Instant::now(),
+      }],
+      position: 0,
+    }
+  }
+
+  /**
+  Добавляет состояние после изменения
+
+  Отменённые записи после текущей позиции удаляются. Изменение того же
+  вида, что и предыдущее, сделанное в течение `COALESCE_INTERVAL`,
+  заменяет предыдущую запись.
+
+  # Аргументы
+  * `change` - Вид изменения
+  * `profile` - Состояние профиля после изменения
+  */
+  pub fn record(&mut self, change: Change, profile: Profile) {
+    // После отмены запись, к которой вернулись, не заменяется
+    let is_latest = self.position == self.entries.len() - 1;
+    self.entries.truncate(self.position + 1);
+
+    let now = Instant::now();
+    if let Some(last) = self.entries.last_mut()
+      && is_latest
+      && self.position > 0
+      && last.change == change
+      && !matches!(change, Change::SlotWrite(..))
+      && now.duration_since(last.time) < COALESCE_INTERVAL
+    {
+      last.profile = profile;
+      last.time = now;
+      return;
+    }
+
+    self.entries.push(HistoryEntry {
+      change,
+      profile,
+      time: now,
+    });
+    if self.entries.len() > HISTORY_LIMIT {
+      self.entries.remove(0);
+      self.entries[0].change = Change::Initial;
+    }
+    self.position = self.entries.len() - 1;
+  }
+
+  /**
+  Сверяет историю с загруженным профилем
+
+  Если профиль отличается от текущего состояния истории, отличие
+  записывается как внешнее изменение.
+
+  # Аргументы
+  * `profile` - Загруженный профиль
+  */
+  pub fn sync(&mut self, profile: &Profile) {
+    if self.current() != profile {
+      self.record(Change::External, profile.clone());
+    }
+  }
+
+  /// Возвращает текущее состояние профиля
+  pub fn current(&self) -> &Profile {
+    &self.entries[self.position].profile
+  }
+
+  /**
+  Переходит к записи истории
+
+  # Аргументы
+  * `position` - Индекс записи
+
+  # Возвращает
+  Состояние профиля в этой записи или `None` для несуществующего индекса
+  */
+  pub fn jump(&mut self, position: usize) -> Option<&Profile> {
+    let entry = self.entries.get(position)?;
+    self.position = position;
+    Some(&entry.profile)
+  }
+
+  /// Отменяет последнее изменение и возвращает предыдущее состояние
+  pub fn undo(&mut self) -> Option<&Profile> {
+    self.jump(self.position.checked_sub(1)?)
+  }
+
+  /// Повторяет отменённое изменение и возвращает следующее состояние
+  pub fn redo(&mut self) -> Option<&Profile> {
+    self.jump(self.position + 1)
+  }
+
+  /// Проверяет, есть ли изменение для отмены
+  pub fn can_undo(&self) -> bool {
+    self.position > 0
+  }
+
+  /// Проверяет, есть ли отменённое изменение для повтора
+  pub fn can_redo(&self) -> bool {
+    self.position + 1 < self.entries.len()
+  }
+
+  /// Возвращает записи истории
+  pub fn entries(&self) -> &[HistoryEntry] {
+    &self.entries
+  }
+
+  /// Возвращает индекс текущего состояния
+  pub fn position(&self) -> usize {
+    self.position
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn undo_redo_and_branching() {
+    let initial = Profile::default();
+    let mut history = History::new(initial.clone());
+
+    let mut first = initial.clone();
+    first.buttons[0] = [b'a', 0, 0, 0, 0, 0];
+    history.record(Change::between(&initial, &first).unwrap(), first.clone());
+
+    let mut second = first.clone();
+    second.stick.word[0] = 0xDA;
+    history.record(Change::between(&first, &second).unwrap(), second.clone());
+
+    assert_eq!(history.undo(), Some(&first));
+    assert_eq!(history.undo(), Some(&initial));
+    assert_eq!(history.undo(), None);
+    assert_eq!(history.redo(), Some(&first));
+
+    // Новое изменение после отмены удаляет отменённую ветку
+    let mut renamed = first.clone();
+    renamed.name = "GIMP".to_string();
+    history.record(Change::Rename, renamed.clone());
+    assert!(!history.can_redo());
+    assert_eq!(history.entries().len(), 3);
+  }
+
+  #[test]
+  fn coalesces_repeated_changes() {
+    let mut history = History::new(Profile::default());
+    for deadzone in 10..20 {
+      let mut profile = Profile::default();
+      profile.stick.deadzone = deadzone;
+      history.record(Change::Deadzone, profile);
+    }
+
+    assert_eq!(history.entries().len(), 2);
+    assert_eq!(history.entries()[1].profile.stick.deadzone, 19);
+
+    // Изменение после отмены не заменяет запись, к которой вернулись
+    let mut profile = history.entries()[1].profile.clone();
+    profile.stick.word[0] = 0xDA;
+    history.record(Change::Stick(1), profile);
+    history.undo();
+    let mut profile = Profile::default();
+    profile.stick.deadzone = 30;
+    history.record(Change::Deadzone, profile);
+
+    assert_eq!(history.entries().len(), 3);
+    assert_eq!(history.entries()[1].profile.stick.deadzone, 19);
+    assert_eq!(history.entries()[2].profile.stick.deadzone, 30);
+  }
+}
@@ -0,0 +1,803 @@
+/*!
+Импорт раскладок клавиш приложений.
+
+Файлы сочетаний клавиш приложений разбираются в список команд, чтобы
+назначать кнопкам команды по названию, не зная их сочетаний. Поддерживаются:
+- VS Code: `keybindings.json`
+- Blender: экспорт раскладки (`.py`)
+- GIMP: `shortcutsrc` (GIMP 3) и `menurc` (GIMP 2)
+- Krita: `.shortcuts` и `kritashortcutsrc`
+
+Названия клавиш каждого приложения переводятся в названия текстового
+формата профилей. Сочетания, которые нельзя передать кейпадом (кнопки мыши,
+последовательности нажатий, неизвестные клавиши), не попадают в список
+и перечисляются в `Keymap::skipped`.
+*/
+
+use std::{collections::HashSet, fmt, path::Path};
+
+use anyhow::{Result, bail};
+use serde::Deserialize;
+
+use crate::data::{code::by_name, readable::parse_combination};
+
+/// Формат файла раскладки
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeymapFormat {
+  /// `keybindings.json` VS Code
+  VsCode,
+  /// Экспорт раскладки Blender
+  Blender,
+  /// `shortcutsrc` или `menurc` GIMP
+  Gimp,
+  /// `.shortcuts` Krita
+  Krita,
+}
+
+impl KeymapFormat {
+  /**
+  Определяет формат раскладки по имени и содержимому файла
+
+  # Аргументы
+  * `path` - Путь к файлу
+  * `text` - Содержимое файла
+
+  # Возвращает
+  Формат или `None`, если файл не похож ни на одну поддерживаемую раскладку
+  */
+  pub fn detect(path: &Path, text: &str) -> Option<Self> {
+    let extension = path.extension().and_then(|ext| ext.to_str());
+    if extension == Some("py") || text.contains("keyconfig_data") {
+      return Some(Self::Blender);
+    }
+    if text.contains("(action ") || text.contains("(gtk_accel_path ") {
+      return Some(Self::Gimp);
+    }
+    if extension == Some("shortcuts") || text.contains("[Shortcuts]") {
+      return Some(Self::Krita);
+    }
+    let text = text.trim_start();
+    (extension == Some("json") || text.starts_with('[') || text.starts_with("//"))
+      .then_some(Self::VsCode)
+  }
+}
+
+impl fmt::Display for KeymapFormat {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let title = match self {
+      Self::VsCode => "VS Code",
+      Self::Blender => "Blender",
+      Self::Gimp => "GIMP",
+      Self::Krita => "Krita",
+    };
+    write!(f, "{title}")
+  }
+}
+
+/// Команда приложения с сочетанием клавиш
+#[derive(Debug, Clone, PartialEq)]
+pub struct Command {
+  /// Идентификатор команды в приложении, например `edit-undo`
+  pub id: String,
+  /// Название команды для списка и подписи кнопки
+  pub title: String,
+  /// Область действия: раскладка Blender или группа действий GIMP
+  pub context: String,
+  /// Сочетание в текстовом формате профилей, например `LCtrl+Z`
+  pub keys: String,
+  /// Коды сочетания для записи в кнопку
+  pub codes: [u8; 6],
+}
+
+/// Импортированная раскладка приложения
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keymap {
+  /// Формат файла
+  pub format: KeymapFormat,
+  /// Имя файла
+  pub file: String,
+  /// Команды, которые можно назначить кнопкам
+  pub commands: Vec<Command>,
+  /// Пропущенные сочетания: команда и причина
+  pub skipped: Vec<String>,
+}
+
+/// Сочетание команды до перевода в коды устройства
+struct Binding {
+  id: String,
+  context: String,
+  /// Названия клавиш в текстовом формате профилей или причина пропуска
+  keys: Result<Vec<String>, String>,
+}
+
+impl Keymap {
+  /**
+  Разбирает файл раскладки
+
+  # Аргументы
+  * `format` - Формат файла
+  * `file` - Имя файла для отображения
+  * `text` - Содержимое файла
+
+  # Возвращает
+  Раскладку или ошибку, если файл повреждён или не содержит ни одного сочетания
+  */
+  pub fn parse(format: KeymapFormat, file: String, text: &str) -> Result<Self> {
+    let bindings = match format {
+      KeymapFormat::VsCode => vscode(text)?,
+      KeymapFormat::Blender => blender(text),
+      KeymapFormat::Gimp => gimp(text),
+      KeymapFormat::Krita => krita(text),
+    };
+    if bindings.is_empty() {
+      bail!("в файле нет сочетаний клавиш {format}");
+    }
+
+    let mut seen = HashSet::new();
+    let mut commands = Vec::new();
+    let mut skipped = Vec::new();
+    for binding in bindings {
+      let codes = binding.keys.and_then(|keys| {
+        let keys = keys.join("+");
+        parse_combination(&keys)
+          .map(|codes| (keys, codes))
+          .map_err(|err| format!("{err:#}"))
+      });
+      match codes {
+        Ok((keys, codes)) => {
+          // Одна команда встречается в нескольких раскладках с тем же сочетанием
+          if seen.insert((binding.id.clone(), binding.context.clone(), codes)) {
+            commands.push(Command {
+              title: title(&binding.id),
+              id: binding.id,
+              context: binding.context,
+              keys,
+              codes,
+            });
+          }
+        }
+        Err(reason) => skipped.push(format!("{}: {reason}", binding.id)),
+      }
+    }
+
+    Ok(Self {
+      format,
+      file,
+      commands,
+      skipped,
+    })
+  }
+
+  /**
+  Ищет команды по названию, идентификатору, области или сочетанию
+
+  # Аргументы
+  * `query` - Текст запроса без учёта регистра
+
+  # Возвращает
+  Индексы найденных команд в порядке файла
+  */
+  pub fn search(&self, query: &str) -> Vec<usize> {
+    let query = query.trim().to_lowercase();
+    self
+      .commands
+      .iter()
+      .enumerate()
+      .filter(|(_, command)| {
+        [&command.title, &command.id, &command.context, &command.keys]
+          .into_iter()
+          .any(|field| field.to_lowercase().contains(&query))
+      })
+      .map(|(idx, _)| idx)
+      .collect()
+  }
+}
+
+/**
+Составляет название команды из идентификатора
+
+Берётся последняя часть идентификатора, слова разделяются по `_`, `-`
+и смене регистра: `editor.action.commentLine` — «Comment line».
+*/
+fn title(id: &str) -> String {
+  let last = id
+    .rsplit(['.', '/'])
+    .find(|part| !part.is_empty())
+    .unwrap_or(id);
+
+  let mut words = String::new();
+  let mut prev_lower = false;
+  for c in last.chars() {
+    match c {
+      '_' | '-' => {
+        words.push(' ');
+        prev_lower = false;
+      }
+      c if c.is_uppercase() && prev_lower => {
+        words.push(' ');
+        words.extend(c.to_lowercase());
+        prev_lower = false;
+      }
+      c => {
+        words.push(c);
+        prev_lower = c.is_lowercase() || c.is_ascii_digit();
+      }
+    }
+  }
+
+  let words = words.split_whitespace().collect::<Vec<_>>().join(" ");
+  let mut chars = words.chars();
+  match chars.next() {
+    Some(first) => first.to_uppercase().chain(chars).collect(),
+    None => id.to_string(),
+  }
+}
+
+/**
+Переводит название клавиши приложения в название текстового формата
+
+# Аргументы
+* `name` - Название клавиши без модификаторов
+* `aliases` - Названия клавиш приложения, которых нет среди синонимов реестра
+*/
+fn key(name: &str, aliases: &[(&str, &str)]) -> Result<String, String> {
+  let name = aliases
+    .iter()
+    .find(|(alias, _)| alias.eq_ignore_ascii_case(name))
+    .map_or(name, |(_, key)| key);
+  match by_name(name) {
+    Some(key) => Ok(key.name.to_string()),
+    None => Err(format!("клавиша «{name}» не поддерживается кейпадом")),
+  }
+}
+
+/// Клавиши VS Code, названия которых отличаются от реестра
+const VSCODE_KEYS: &[(&str, &str)] = &[
+  ("numpad0", "Num0"),
+  ("numpad1", "Num1"),
+  ("numpad2", "Num2"),
+  ("numpad3", "Num3"),
+  ("numpad4", "Num4"),
+  ("numpad5", "Num5"),
+  ("numpad6", "Num6"),
+  ("numpad7", "Num7"),
+  ("numpad8", "Num8"),
+  ("numpad9", "Num9"),
+  ("numpad_add", "NumPlus"),
+  ("numpad_subtract", "NumMinus"),
+  ("numpad_multiply", "NumStar"),
+  ("numpad_divide", "NumSlash"),
+  ("numpad_decimal", "NumDot"),
+];
+
+/// Сочетание `keybindings.json`
+#[derive(Deserialize)]
+struct VsCodeBinding {
+  key: String,
+  command: String,
+}
+
+/**
+Удаляет из JSONC комментарии и висячие запятые
+
+`keybindings.json` VS Code допускает и то и другое, а `serde_json` — нет.
+*/
+fn strip_jsonc(text: &str) -> String {
+  let mut out = String::with_capacity(text.len());
+  let mut chars = text.chars().peekable();
+  let mut in_string = false;
+  while let Some(c) = chars.next() {
+    match c {
+      '"' => {
+        in_string = !in_string;
+        out.push(c);
+      }
+      '\\' if in_string => {
+        out.push(c);
+        out.extend(chars.next());
+      }
+      '/' if !in_string && chars.peek() == Some(&'/') => {
+        for c in chars.by_ref() {
+          if c == '\n' {
+            out.push(c);
+            break;
+          }
+        }
+      }
+      '/' if !in_string && chars.peek() == Some(&'*') => {
+        chars.next();
+        let mut prev = ' ';
+        for c in chars.by_ref() {
+          if prev == '*' && c == '/' {
+            break;
+          }
+          prev = c;
+        }
+      }
+      ']' | '}' if !in_string => {
+        let trimmed = out.trim_end().len();
+        if out[..trimmed].ends_with(',') {
+          out.truncate(trimmed - 1);
+        }
+        out.push(c);
+      }
+      c => out.push(c),
+    }
+  }
+  out
+}
+
+/// Разбирает `keybindings.json` VS Code
+fn vscode(text: &str) -> Result<Vec<Binding>> {
+  let bindings: Vec<VsCodeBinding> = serde_json::from_str(&strip_jsonc(text))?;
+
+  Ok(
+    bindings
+      .into_iter()
+      // Команды с `-` отменяют сочетание по умолчанию
+      .filter(|binding| !binding.command.starts_with('-') && !binding.key.is_empty())
+      .map(|binding| {
+        let keys = match binding.key.trim().contains(' ') {
+          true => Err(format!(
+            "последовательность нажатий «{}» не поддерживается",
+            binding.key
+          )),
+          false => binding
+            .key
+            .split('+')
+            .map(|name| key(name, VSCODE_KEYS))
+            .collect(),
+        };
+        Binding {
+          id: binding.command,
+          context: String::new(),
+          keys,
+        }
+      })
+      .collect(),
+  )
+}
+
+/// Клавиши Blender, названия которых отличаются от реестра
+const BLENDER_KEYS: &[(&str, &str)] = &[
+  ("ZERO", "0"),
+  ("ONE", "1"),
+  ("TWO", "2"),
+  ("THREE", "3"),
+  ("FOUR", "4"),
+  ("FIVE", "5"),
+  ("SIX", "6"),
+  ("SEVEN", "7"),
+  ("EIGHT", "8"),
+  ("NINE", "9"),
+  ("NUMPAD_0", "Num0"),
+  ("NUMPAD_1", "Num1"),
+  ("NUMPAD_2", "Num2"),
+  ("NUMPAD_3", "Num3"),
+  ("NUMPAD_4", "Num4"),
+  ("NUMPAD_5", "Num5"),
+  ("NUMPAD_6", "Num6"),
+  ("NUMPAD_7", "Num7"),
+  ("NUMPAD_8", "Num8"),
+  ("NUMPAD_9", "Num9"),
+  ("NUMPAD_PERIOD", "NumDot"),
+  ("NUMPAD_SLASH", "NumSlash"),
+  ("NUMPAD_ASTERIX", "NumStar"),
+  ("NUMPAD_MINUS", "NumMinus"),
+  ("NUMPAD_PLUS", "NumPlus"),
+  ("NUMPAD_ENTER", "NumEnter"),
+  ("LEFT_ARROW", "Left"),
+  ("RIGHT_ARROW", "Right"),
+  ("UP_ARROW", "Up"),
+  ("DOWN_ARROW", "Down"),
+  ("PAGE_UP", "PgUp"),
+  ("PAGE_DOWN", "PgDn"),
+  ("BACK_SPACE", "Backspace"),
+  ("RET", "Enter"),
+  ("MINUS", "-"),
+  ("EQUAL", "="),
+  ("COMMA", ","),
+  ("PERIOD", "."),
+  ("SLASH", "/"),
+  ("BACK_SLASH", "\\"),
+  ("SEMI_COLON", ";"),
+  ("QUOTE", "'"),
+  ("ACCENT_GRAVE", "`"),
+  ("LEFT_BRACKET", "["),
+  ("RIGHT_BRACKET", "]"),
+];
+
+/**
+Возвращает значение поля словаря события Blender
+
+# Аргументы
+* `event` - Содержимое словаря между `{` и `}`
+* `field` - Имя поля без кавычек
+*/
+fn blender_field<'a>(event: &'a str, field: &str) -> Option<&'a str> {
+  event.split(',').find_map(|pair| {
+    let (name, value) = pair.split_once(':')?;
+    (name.trim().trim_matches('"') == field).then(|| value.trim().trim_matches(['\'', '"']))
+  })
+}
+
+/**
+Разбирает экспорт раскладки Blender
+
+Элемент раскладки записывается кортежем
+`("оператор", {"type": 'A', "value": 'PRESS', "ctrl": True}, ...)`,
+а заголовок раскладки — `("3D View", {"space_type": ...}, ...)`.
+*/
+fn blender(text: &str) -> Vec<Binding> {
+  let mut bindings = Vec::new();
+  let mut context = String::new();
+
+  for (start, _) in text.match_indices("(\"") {
+    let rest = &text[start + 2..];
+    let Some((name, rest)) = rest.split_once('"') else {
+      continue;
+    };
+    let Some(rest) = rest.trim_start().strip_prefix(',') else {
+      continue;
+    };
+    let Some(event) = rest.trim_start().strip_prefix('{') else {
+      continue;
+    };
+    let Some((event, _)) = event.split_once('}') else {
+      continue;
+    };
+
+    if blender_field(event, "space_type").is_some() {
+      context = name.to_string();
+      continue;
+    }
+    let Some(kind) = blender_field(event, "type") else {
+      continue;
+    };
+    if !matches!(blender_field(event, "value"), Some("PRESS" | "CLICK")) {
+      continue;
+    }
+
+    let mut keys = Vec::new();
+    for (modifier, key) in [
+      ("ctrl", "LCtrl"),
+      ("shift", "LShift"),
+      ("alt", "LAlt"),
+      ("oskey", "LWin"),
+    ] {
+      if matches!(blender_field(event, modifier), Some("True" | "1")) {
+        keys.push(Ok(key.to_string()));
+      }
+    }
+    keys.push(match kind.contains("MOUSE") || kind.starts_with("NDOF") {
+      true => Err(format!("{kind} — не клавиша клавиатуры")),
+      false => key(kind, BLENDER_KEYS),
+    });
+
+    bindings.push(Binding {
+      id: name.to_string(),
+      context: context.clone(),
+      keys: keys.into_iter().collect(),
+    });
+  }
+
+  bindings
+}
+
+/// Клавиши GTK, названия которых отличаются от реестра
+const GTK_KEYS: &[(&str, &str)] = &[
+  ("Page_Up", "PgUp"),
+  ("Page_Down", "PgDn"),
+  ("BackSpace", "Backspace"),
+  ("KP_0", "Num0"),
+  ("KP_1", "Num1"),
+  ("KP_2", "Num2"),
+  ("KP_3", "Num3"),
+  ("KP_4", "Num4"),
+  ("KP_5", "Num5"),
+  ("KP_6", "Num6"),
+  ("KP_7", "Num7"),
+  ("KP_8", "Num8"),
+  ("KP_9", "Num9"),
+  ("KP_Add", "NumPlus"),
+  ("KP_Subtract", "NumMinus"),
+  ("KP_Multiply", "NumStar"),
+  ("KP_Divide", "NumSlash"),
+  ("KP_Decimal", "NumDot"),
+  ("KP_Enter", "NumEnter"),
+  ("Print", "PrScr"),
+  ("plus", "Plus"),
+  ("minus", "-"),
+  ("equal", "="),
+  ("comma", ","),
+  ("period", "."),
+  ("slash", "/"),
+  ("backslash", "\\"),
+  ("bracketleft", "["),
+  ("bracketright", "]"),
+  ("semicolon", ";"),
+  ("apostrophe", "'"),
+  ("grave", "`"),
+  ("less", "<"),
+  ("greater", ">"),
+  ("question", "?"),
+  ("exclam", "!"),
+  ("asterisk", "*"),
+  ("underscore", "_"),
+  ("parenleft", "("),
+  ("parenright", ")"),
+  ("braceleft", "{"),
+  ("braceright", "}"),
+  ("bar", "|"),
+  ("numbersign", "#"),
+  ("dollar", "$"),
+  ("percent", "%"),
+  ("ampersand", "&"),
+  ("at", "@"),
+  ("asciicircum", "^"),
+  ("asciitilde", "~"),
+  ("quotedbl", "\""),
+  ("colon", ":"),
+];
+
+/// Разбирает сочетание GTK вида `<Primary><Shift>z`
+fn gtk_accel(accel: &str) -> Result<Vec<String>, String> {
+  let mut keys = Vec::new();
+  let mut rest = accel.trim();
+  while let Some(modifier) = rest.strip_prefix('<') {
+    let Some((modifier, tail)) = modifier.split_once('>') else {
+      return Err(format!("повреждённое сочетание «{accel}»"));
+    };
+    let key = match modifier.to_lowercase().as_str() {
+      "primary" | "control" | "ctrl" => "LCtrl",
+      "shift" => "LShift",
+      "alt" | "mod1" => "LAlt",
+      "super" | "meta" => "LWin",
+      _ => return Err(format!("модификатор «{modifier}» не поддерживается")),
+    };
+    keys.push(key.to_string());
+    rest = tail;
+  }
+  keys.push(key(rest, GTK_KEYS)?);
+  Ok(keys)
+}
+
+/**
+Разбирает `shortcutsrc` GIMP 3 или `menurc` GIMP 2
+
+Строки `(action "edit-undo" "<Primary>z")` содержат сочетания GIMP 3,
+строки `(gtk_accel_path "<Actions>/edit/edit-undo" "<Primary>z")` — GIMP 2.
+В `menurc` сочетания по умолчанию закомментированы `;`, изменённые — нет;
+изменённое сочетание заменяет сочетание по умолчанию.
+*/
+fn gimp(text: &str) -> Vec<Binding> {
+  let mut changed = Vec::new();
+  let mut defaults = Vec::new();
+
+  for line in text.lines() {
+    let line = line.trim();
+    let (line, target) = match line.strip_prefix(';') {
+      Some(line) => (line.trim_start(), &mut defaults),
+      None => (line, &mut changed),
+    };
+    let Some(line) = line
+      .strip_prefix("(action ")
+      .or_else(|| line.strip_prefix("(gtk_accel_path "))
+    else {
+      continue;
+    };
+
+    let quoted = line.split('"').skip(1).step_by(2).collect::<Vec<_>>();
+    let Some((path, accels)) = quoted.split_first() else {
+      continue;
+    };
+    let (context, id) = match path.strip_prefix("<Actions>/") {
+      Some(path) => path.split_once('/').unwrap_or(("", path)),
+      None => ("", *path),
+    };
+
+    for accel in accels.iter().filter(|accel| !accel.is_empty()) {
+      target.push(Binding {
+        id: id.to_string(),
+        context: context.to_string(),
+        keys: gtk_accel(accel),
+      });
+    }
+  }
+
+  let ids = changed
+    .iter()
+    .map(|binding| binding.id.clone())
+    .collect::<HashSet<_>>();
+  changed.extend(
+    defaults
+      .into_iter()
+      .filter(|binding| !ids.contains(&binding.id)),
+  );
+  changed
+}
+
+/// Клавиши Qt, названия которых отличаются от реестра
+const QT_KEYS: &[(&str, &str)] = &[("PgDown", "PgDn"), ("Print", "PrScr"), ("+", "Plus")];
+
+/// Клавиши цифрового блока Qt, записываемые с модификатором `Num`
+const QT_NUMPAD_KEYS: &[(&str, &str)] = &[
+  ("0", "Num0"),
+  ("1", "Num1"),
+  ("2", "Num2"),
+  ("3", "Num3"),
+  ("4", "Num4"),
+  ("5", "Num5"),
+  ("6", "Num6"),
+  ("7", "Num7"),
+  ("8", "Num8"),
+  ("9", "Num9"),
+  ("+", "NumPlus"),
+  ("-", "NumMinus"),
+  ("*", "NumStar"),
+  ("/", "NumSlash"),
+  (".", "NumDot"),
+  ("Enter", "NumEnter"),
+];
+
+/// Разбирает сочетание Qt вида `Ctrl+Shift+Z`; клавиша `+` записывается как `Ctrl++`
+fn qt_sequence(sequence: &str) -> Result<Vec<String>, String> {
+  let (modifiers, last) = match sequence.strip_suffix("++") {
+    Some(modifiers) => (modifiers, "+"),
+    None if sequence == "+" => ("", "+"),
+    None => sequence.rsplit_once('+').unwrap_or(("", sequence)),
+  };
+
+  let mut keys = Vec::new();
+  let mut numpad = false;
+  for modifier in modifiers.split('+').filter(|m| !m.is_empty()) {
+    match modifier {
+      "Num" => numpad = true,
+      modifier => keys.push(key(modifier, &[])?),
+    }
+  }
+  keys.push(match numpad {
+    true => key(last, QT_NUMPAD_KEYS)?,
+    false => key(last, QT_KEYS)?,
+  });
+  Ok(keys)
+}
+
+/**
+Разбирает `.shortcuts` или `kritashortcutsrc` Krita
+
+Файл в формате INI: `edit_undo=Ctrl+Z; Ctrl+Y` в секции `[Shortcuts]`,
+`none` — сочетание не назначено.
+*/
+fn krita(text: &str) -> Vec<Binding> {
+  let mut bindings = Vec::new();
+  let mut in_shortcuts = true;
+
+  for line in text.lines().map(str::trim) {
+    if let Some(section) = line.strip_prefix('[') {
+      in_shortcuts = section.trim_end_matches(']') == "Shortcuts";
+      continue;
+    }
+    let Some((id, value)) = line.split_once('=').filter(|_| in_shortcuts) else {
+      continue;
+    };
+    for sequence in value.split("; ").map(str::trim) {
+      if sequence.is_empty() || sequence.eq_ignore_ascii_case("none") {
+        continue;
+      }
+      let keys = match sequence.contains(", ") {
+        true => Err(format!(
+          "последовательность нажатий «{sequence}» не поддерживается"
+        )),
+        false => qt_sequence(sequence),
+      };
+      bindings.push(Binding {
+        id: id.trim().to_string(),
+        context: String::new(),
+        keys,
+      });
+    }
+  }
+
+  bindings
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn keys(keymap: &Keymap) -> Vec<(&str, &str)> {
+    keymap
+      .commands
+      .iter()
+      .map(|command| (command.id.as_str(), command.keys.as_str()))
+      .collect()
+  }
+
+  #[test]
+  fn parses_vscode_keybindings() {
+    let text = r#"// Place your key bindings in this file
+      [
+        { "key": "ctrl+shift+/", "command": "editor.action.commentLine", "when": "editorTextFocus" },
+        { "key": "ctrl+k ctrl+c", "command": "editor.action.addCommentLine" },
+        { "key": "ctrl+numpad_add", "command": "-workbench.action.zoomIn" },
+        { "key": "alt+pageup", "command": "scrollPageUp" }, /* в конце */
+      ]"#;
+    let path = Path::new("keybindings.json");
+    let format = KeymapFormat::detect(path, text).unwrap();
+    let keymap = Keymap::parse(format, "keybindings.json".into(), text).unwrap();
+
+    assert_eq!(
+      keys(&keymap),
+      [
+        ("editor.action.commentLine", "LCtrl+LShift+/"),
+        ("scrollPageUp", "LAlt+PgUp")
+      ]
+    );
+    assert_eq!(keymap.commands[0].title, "Comment line");
+    assert_eq!(keymap.skipped.len(), 1);
+    assert_eq!(keymap.search("COMMENT"), [0]);
+  }
+
+  #[test]
+  fn parses_blender_keymap() {
+    let text = r#"keyconfig_data = [
+      ("3D View",
+       {"space_type": 'VIEW_3D', "region_type": 'WINDOW'},
+       {"items":
+        [("view3d.select", {"type": 'LEFTMOUSE', "value": 'CLICK'}, None),
+         ("object.duplicate_move", {"type": 'D', "value": 'PRESS', "shift": True}, None),
+         ("view3d.view_axis", {"type": 'NUMPAD_1', "value": 'PRESS', "ctrl": True},
+          {"properties": [("type", 'BACK')]}),
+         ],
+        },
+       ),
+    ]"#;
+    let format = KeymapFormat::detect(Path::new("blender.py"), text).unwrap();
+    let keymap = Keymap::parse(format, "blender.py".into(), text).unwrap();
+
+    assert_eq!(
+      keys(&keymap),
+      [
+        ("object.duplicate_move", "LShift+D"),
+        ("view3d.view_axis", "LCtrl+Num1")
+      ]
+    );
+    assert_eq!(keymap.commands[0].context, "3D View");
+    assert_eq!(keymap.commands[0].title, "Duplicate move");
+    assert_eq!(keymap.skipped.len(), 1);
+  }
+
+  #[test]
+  fn parses_gimp_and_krita_shortcuts() {
+    let menurc = r#"; (gtk_accel_path "<Actions>/edit/edit-undo" "<Primary>z")
+      ; (gtk_accel_path "<Actions>/edit/edit-redo" "<Primary>y")
+      (gtk_accel_path "<Actions>/edit/edit-redo" "<Primary><Shift>z")
+      (action "view-zoom-in" "plus" "KP_Add")"#;
+    let format = KeymapFormat::detect(Path::new("menurc"), menurc).unwrap();
+    let gimp = Keymap::parse(format, "menurc".into(), menurc).unwrap();
+    assert_eq!(format, KeymapFormat::Gimp);
+    assert_eq!(
+      keys(&gimp),
+      [
+        ("edit-redo", "LCtrl+LShift+Z"),
+        ("view-zoom-in", "Plus"),
+        ("view-zoom-in", "NumPlus"),
+        ("edit-undo", "LCtrl+Z"),
+      ]
+    );
+
+    let shortcuts = "[Shortcuts]\nedit_undo=Ctrl+Z\nzoom_in=Ctrl++; Num++\nreset=none\n\
+                     [Other]\nignored=Ctrl+Q\n";
+    let format = KeymapFormat::detect(Path::new("my.shortcuts"), shortcuts).unwrap();
+    let krita = Keymap::parse(format, "my.shortcuts".into(), shortcuts).unwrap();
+    assert_eq!(
+      keys(&krita),
+      [
+        ("edit_undo", "LCtrl+Z"),
+        ("zoom_in", "LCtrl+Plus"),
+        ("zoom_in", "NumPlus")
+      ]
+    );
+  }
+}
@@ -0,0 +1,188 @@
+/*!
+Раскладки клавиатуры для подписей клавиш.
+
+Устройство передаёт коды клавиш US-раскладки, а символ, который в итоге
+будет набран, зависит от раскладки, включённой в системе пользователя.
+Раскладка влияет только на подписи: коды, отправляемые устройству,
+не меняются.
+*/
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::code::code_to_title;
+
+/// Клавиши ЙЦУКЕН, отличающиеся от US: символ US и символ той же клавиши
+const RUSSIAN: &[(u8, char)] = &[
+  (b'`', 'ё'),
+  (b'~', 'Ё'),
+  (b'q', 'й'),
+  (b'w', 'ц'),
+  (b'e', 'у'),
+  (b'r', 'к'),
+  (b't', 'е'),
+  (b'y', 'н'),
+  (b'u', 'г'),
+  (b'i', 'ш'),
+  (b'o', 'щ'),
+  (b'p', 'з'),
+  (b'[', 'х'),
+  (b'{', 'Х'),
+  (b']', 'ъ'),
+  (b'}', 'Ъ'),
+  (b'a', 'ф'),
+  (b's', 'ы'),
+  (b'd', 'в'),
+  (b'f', 'а'),
+  (b'g', 'п'),
+  (b'h', 'р'),
+  (b'j', 'о'),
+  (b'k', 'л'),
+  (b'l', 'д'),
+  (b';', 'ж'),
+  (b':', 'Ж'),
+  (b'\'', 'э'),
+  (b'"', 'Э'),
+  (b'z', 'я'),
+  (b'x', 'ч'),
+  (b'c', 'с'),
+  (b'v', 'м'),
+  (b'b', 'и'),
+  (b'n', 'т'),
+  (b'm', 'ь'),
+  (b',', 'б'),
+  (b'<', 'Б'),
+  (b'.', 'ю'),
+  (b'>', 'Ю'),
+  (b'/', '.'),
+  (b'?', ','),
+  (b'@', '"'),
+  (b'#', '№'),
+  (b'$', ';'),
+  (b'^', ':'),
+  (b'&', '?'),
+  (b'|', '/'),
+];
+
+/// Клавиши немецкой раскладки (QWERTZ), отличающиеся от US
+const GERMAN: &[(u8, char)] = &[
+  (b'`', '^'),
+  (b'~', '°'),
+  (b'y', 'z'),
+  (b'z', 'y'),
+  (b'-', 'ß'),
+  (b'_', '?'),
+  (b'=', '´'),
+  (b'+', '`'),
+  (b'[', 'ü'),
+  (b'{', 'Ü'),
+  (b']', '+'),
+  (b'}', '*'),
+  (b';', 'ö'),
+  (b':', 'Ö'),
+  (b'\'', 'ä'),
+  (b'"', 'Ä'),
+  (b'\\', '#'),
+  (b'|', '\''),
+  (b'/', '-'),
+  (b'?', '_'),
+  (b'<', ';'),
+  (b'>', ':'),
+  (b'@', '"'),
+  (b'#', '§'),
+  (b'^', '&'),
+  (b'&', '/'),
+  (b'*', '('),
+  (b'(', ')'),
+  (b')', '='),
+];
+
+/**
+Раскладка клавиатуры, в которой отображаются подписи клавиш
+*/
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum KeyboardLayout {
+  /// Английская (US), подписи совпадают с кодами
+  #[default]
+  Us,
+  /// Русская (ЙЦУКЕН)
+  Russian,
+  /// Немецкая (QWERTZ)
+  German,
+}
+
+impl KeyboardLayout {
+  /// Все поддерживаемые раскладки
+  pub const ALL: [Self; 3] = [Self::Us, Self::Russian, Self::German];
+
+  /**
+  Возвращает символ, который набирает клавиша в этой раскладке
+
+  # Аргументы
+  * `code` - Код клавиши
+
+  # Возвращает
+  `Some(char)`, если символ отличается от символа US-раскладки, иначе `None`
+  */
+  pub fn char_for(&self, code: u8) -> Option<char> {
+    let table = match self {
+      Self::Us => return None,
+      Self::Russian => RUSSIAN,
+      Self::German => GERMAN,
+    };
+
+    // Заглавные буквы передаются с Shift и дают заглавный символ той же клавиши
+    let (lower, upper) = (code.to_ascii_lowercase(), code.is_ascii_uppercase());
+    table
+      .iter()
+      .find(|(us, _)| *us == lower)
+      .map(|(_, c)| match upper {
+        true => c.to_uppercase().next().unwrap_or(*c),
+        false => *c,
+      })
+  }
+
+  /**
+  Возвращает подпись клавиши с учётом раскладки
+
+  Если в раскладке клавиша набирает другой символ, он указывается
+  в скобках после подписи кода, например `; (ж)`.
+
+  # Аргументы
+  * `code` - Код клавиши
+  */
+  pub fn label(&self, code: u8) -> String {
+    match self.char_for(code) {
+      Some(c) => format!("{} ({c})", code_to_title(code)),
+      None => code_to_title(code),
+    }
+  }
+}
+
+impl fmt::Display for KeyboardLayout {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let title = match self {
+      Self::Us => "Английская (US)",
+      Self::Russian => "Русская (ЙЦУКЕН)",
+      Self::German => "Немецкая (QWERTZ)",
+    };
+    write!(f, "{title}")
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn labels_show_layout_character() {
+    assert_eq!(KeyboardLayout::Us.label(b';'), ";");
+    assert_eq!(KeyboardLayout::Russian.label(b';'), "; (ж)");
+    assert_eq!(KeyboardLayout::Russian.label(b'q'), "q (й)");
+    assert_eq!(KeyboardLayout::Russian.label(b'Q'), "Q (Й)");
+    assert_eq!(KeyboardLayout::Russian.label(b'1'), "1");
+    assert_eq!(KeyboardLayout::German.label(b'z'), "z (y)");
+    assert_eq!(KeyboardLayout::German.label(0x80), "LCtrl");
+  }
+}
@@ -0,0 +1,860 @@
+/*!
+Локальная библиотека профилей с версионированной схемой хранения.
+
+Библиотека хранится в файле `profiles.ron` директории конфигурации и содержит
+номер версии схемы. При выборе хранилища `StorageBackend::Directory` профили
+хранятся отдельными файлами в выбранной директории, а в `profiles.ron`
+обновляются только наборы; профили единого файла при этом не меняются. При загрузке файлы старых версий последовательно
+переводятся в текущую цепочкой миграций, поэтому изменения модели профиля
+не приводят к потере сохранённых библиотек.
+
+История версий схемы:
+1. До версии приложения 2.1.3 включительно — список профилей без обёртки
+2. Структура с полями `version`, `profiles` и `banks`
+3. Профили содержат локальные метаданные `meta`
+4. Наборы ссылаются на профили библиотеки по имени вместо копий профилей
+*/
+
+use std::{
+  collections::{HashMap, HashSet},
+  fs,
+  hash::{DefaultHasher, Hash, Hasher},
+  path::{Path, PathBuf},
+  time::Duration,
+};
+
+use anyhow::{Result, bail};
+use log::{info, warn};
+use ron::{Map, Value};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  assets::APPLICATION_NAME,
+  data::{
+    banks::Bank,
+    config_dir,
+    profiles::Profile,
+    readable::ReadableProfile,
+    settings::StorageBackend,
+    storage::{list_backups, quarantine, rotate_backup, write_atomic},
+  },
+  errors::library::{LibraryError, ProfileRefError},
+};
+
+/// Текущая версия схемы библиотеки
+pub const LIBRARY_VERSION: u32 = 4;
+
+/// Количество хранимых резервных копий библиотеки
+pub const LIBRARY_BACKUPS_LIMIT: usize = 10;
+
+/// Минимальный интервал между резервными копиями библиотеки
+const LIBRARY_BACKUP_INTERVAL: Duration = Duration::from_secs(60);
+
+/**
+Миграции схемы библиотеки
+
+Элемент с индексом `i` переводит данные версии `i + 1` в версию `i + 2`.
+При изменении модели профиля достаточно увеличить `LIBRARY_VERSION`
+и добавить сюда функцию перевода из предыдущей версии.
+*/
+const MIGRATIONS: [fn(Value) -> Result<Value>; LIBRARY_VERSION as usize - 1] =
+  [migrate_v1, migrate_v2, migrate_v3];
+
+/// Локальная библиотека профилей и наборов
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Library {
+  /// Версия схемы, в которой сохранена библиотека
+  pub version: u32,
+
+  /// Профили библиотеки
+  pub profiles: Vec<Profile>,
+
+  /// Наборы профилей
+  pub banks: Vec<Bank>,
+}
+
+/// Файл профиля в директории в том виде, в котором он был прочитан или записан
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfileFile {
+  /// Путь к файлу
+  pub path: PathBuf,
+
+  /// Содержимое файла
+  pub text: String,
+
+  /// Профиль, записанный в файле
+  pub profile: Profile,
+}
+
+/**
+Снимок директории профилей на момент последней загрузки или сохранения
+
+По снимку сохранение отличает свои файлы от изменённых извне:
+файлы, изменённые или добавленные после снимка, не перезаписываются и не удаляются.
+*/
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DirSnapshot {
+  /// Отпечаток директории (см. `Library::signature`)
+  pub signature: u64,
+
+  /// Файлы профилей
+  pub files: Vec<ProfileFile>,
+}
+
+impl DirSnapshot {
+  /// Возвращает профили снимка в порядке файлов
+  pub fn profiles(&self) -> Vec<Profile> {
+    self.files.iter().map(|file| file.profile.clone()).collect()
+  }
+}
+
+/// Заголовок версионированного файла для определения версии схемы
+#[derive(Deserialize)]
+struct Header {
+  version: u32,
+}
+
+impl Default for Library {
+  fn default() -> Self {
+    Self {
+      version: LIBRARY_VERSION,
+      profiles: Vec::new(),
+      banks: Vec::new(),
+    }
+  }
+}
+
+impl Library {
+  /**
+  Создает библиотеку текущей версии
+
+  # Аргументы
+  * `profiles` - Профили библиотеки
+  * `banks` - Наборы профилей
+  */
+  pub fn new(profiles: Vec<Profile>, banks: Vec<Bank>) -> Self {
+    Self {
+      version: LIBRARY_VERSION,
+      profiles,
+      banks,
+    }
+  }
+
+  /**
+  Находит профиль библиотеки по имени
+
+  # Аргументы
+  * `profiles` - Профили библиотеки
+  * `name` - Имя профиля
+
+  # Возвращает
+  Индекс профиля или ошибку, если профиля нет или имя носят несколько профилей
+  */
+  pub fn find(profiles: &[Profile], name: &str) -> Result<usize, ProfileRefError> {
+    let mut found = profiles
+      .iter()
+      .enumerate()
+      .filter(|(_, profile)| profile.name == name)
+      .map(|(idx, _)| idx);
+
+    match (found.next(), found.next()) {
+      (Some(idx), None) => Ok(idx),
+      (Some(_), Some(_)) => Err(ProfileRefError::Ambiguous(name.to_string())),
+      (None, _) => Err(ProfileRefError::Missing(name.to_string())),
+    }
+  }
+
+  /**
+  Добавляет профили и наборы этой библиотеки к существующим без дубликатов
+
+  Профили и наборы, уже имеющиеся в библиотеке, пропускаются.
+  Профиль с именем, занятым другим профилем, получает суффикс ` (2)`, ` (3)` и т.д.;
+  ссылки добавляемых наборов переносятся на новое имя.
+
+  # Аргументы
+  * `profiles` - Профили существующей библиотеки
+  * `banks` - Наборы существующей библиотеки
+
+  # Возвращает
+  Количество добавленных и пропущенных элементов
+  */
+  pub fn merge_into(self, profiles: &mut Vec<Profile>, banks: &mut Vec<Bank>) -> (usize, usize) {
+    let (mut added, mut skipped) = (0, 0);
+    let mut renamed = Vec::new();
+
+    for mut profile in self.profiles {
+      profile.name = profile.name.trim_end_matches('\0').to_string();
+      if profiles.contains(&profile) {
+        skipped += 1;
+        continue;
+      }
+
+      let base = profile.name.clone();
+      for i in 2.. {
+        if !profiles.iter().any(|p| p.name == profile.name) {
+          break;
+        }
+        profile.name = format!("{base} ({i})");
+      }
+      if profile.name != base {
+        renamed.push((base, profile.name.clone()));
+      }
+
+      profiles.push(profile);
+      added += 1;
+    }
+
+    for mut bank in self.banks {
+      for (old, new) in &renamed {
+        bank.rename(old, new);
+      }
+      match banks.contains(&bank) {
+        true => skipped += 1,
+        false => {
+          banks.push(bank);
+          added += 1;
+        }
+      }
+    }
+
+    (added, skipped)
+  }
+
+  /// Возвращает путь к файлу библиотеки
+  pub fn path() -> Result<PathBuf> {
+    Ok(confy::get_configuration_file_path(
+      APPLICATION_NAME,
+      "profiles",
+    )?)
+  }
+
+  /// Возвращает директорию резервных копий библиотеки
+  pub fn backups_dir() -> PathBuf {
+    config_dir().join("library-backups")
+  }
+
+  /**
+  Загружает библиотеку из хранилища
+
+  Если файла библиотеки ещё нет, возвращается пустая библиотека.
+  Повреждённый файл переносится в карантин, чтобы следующее сохранение
+  не затёрло его содержимое. Файл более новой версии схемы остаётся
+  на месте, а возвращается ошибка `LibraryError::NewerVersion`.
+
+  # Аргументы
+  * `storage` - Место хранения профилей
+
+  # Возвращает
+  Библиотеку текущей версии и снимок директории профилей
+  или ошибку чтения/разбора файла
+  */
+  pub async fn load(storage: StorageBackend) -> Result<(Self, DirSnapshot)> {
+    let path = Self::path()?;
+    let mut library = match path.exists() {
+      true => {
+        let text = fs::read_to_string(&path)?;
+        match Self::parse(&text) {
+          Ok(library) => library,
+          // Файл более новой версии цел: его нельзя ни переносить, ни перезаписывать
+          Err(err) if err.is::<LibraryError>() => return Err(err),
+          Err(err) => {
+            let quarantined = quarantine(&path)?;
+            bail!("{err}; файл перемещён в {}", quarantined.display())
+          }
+        }
+      }
+      false => Self::default(),
+    };
+
+    let snapshot = match storage {
+      StorageBackend::Directory(dir) => {
+        let snapshot = Self::load_dir(&dir)?;
+        library.profiles = snapshot.profiles();
+        snapshot
+      }
+      StorageBackend::Single => DirSnapshot::default(),
+    };
+
+    Ok((library, snapshot))
+  }
+
+  /**
+  Сохраняет библиотеку в хранилище
+
+  Запись атомарная; предыдущая версия файла сохраняется
+  в директорию резервных копий библиотеки. При хранении в директории
+  в `profiles.ron` обновляются только наборы, а профили единого файла
+  остаются нетронутыми.
+
+  # Аргументы
+  * `storage` - Место хранения профилей
+  * `snapshot` - Снимок директории профилей при последней загрузке или сохранении
+
+  # Возвращает
+  Новый снимок директории профилей или ошибку записи
+  */
+  pub fn save(&self, storage: &StorageBackend, snapshot: &DirSnapshot) -> Result<DirSnapshot> {
+    match storage {
+      StorageBackend::Single => {
+        self.save_main()?;
+        Ok(DirSnapshot::default())
+      }
+      StorageBackend::Directory(dir) => {
+        let snapshot = Self::save_dir(dir, &self.profiles, snapshot)?;
+
+        let path = Self::path()?;
+        let profiles = match path.exists() {
+          true => Self::load_file(&path)?.profiles,
+          false => Vec::new(),
+        };
+        Self::new(profiles, self.banks.clone()).save_main()?;
+
+        Ok(snapshot)
+      }
+    }
+  }
+
+  /**
+  Загружает профили из директории, по одному профилю на файл `*.ron`
+
+  Нечитаемые файлы пропускаются и не изменяются.
+
+  # Аргументы
+  * `dir` - Директория профилей
+
+  # Возвращает
+  Снимок директории с прочитанными профилями
+  */
+  pub fn load_dir(dir: &Path) -> Result<DirSnapshot> {
+    let signature = Self::signature(&StorageBackend::Directory(dir.to_path_buf()));
+    let mut paths = Self::dir_files(dir)?;
+    paths.sort();
+
+    let files = paths
+      .into_iter()
+      .filter_map(|path| {
+        let res = fs::read_to_string(&path)
+          .map_err(anyhow::Error::from)
+          .and_then(|text| Ok((Self::parse_profile(&text)?, text)));
+        match res {
+          Ok((profile, text)) => Some(ProfileFile {
+            path,
+            text,
+            profile,
+          }),
+          Err(err) => {
+            warn!("library: профиль {} не читается: {err}", path.display());
+            None
+          }
+        }
+      })
+      .collect();
+
+    Ok(DirSnapshot { signature, files })
+  }
+
+  /**
+  Сохраняет профили в директорию, по одному профилю на файл
+
+  Профили записываются в текстовом формате с названиями клавиш
+  (`data::readable`). Записываются только изменённые и новые профили:
+  изменённый профиль остаётся в своём файле, новый получает имя файла
+  по имени профиля. Файлы удалённых профилей удаляются, только если
+  их содержимое не менялось после снимка. Файлы, изменённые или
+  добавленные извне, не перезаписываются и не удаляются.
+
+  # Аргументы
+  * `dir` - Директория профилей
+  * `profiles` - Сохраняемые профили
+  * `snapshot` - Снимок директории при последней загрузке или сохранении
+
+  # Возвращает
+  Снимок директории после сохранения
+  */
+  pub fn save_dir(dir: &Path, profiles: &[Profile], snapshot: &DirSnapshot) -> Result<DirSnapshot> {
+    fs::create_dir_all(dir)?;
+
+    let on_disk = Self::dir_files(dir)?
+      .into_iter()
+      .filter_map(|path| Some((fs::read_to_string(&path).ok()?, path)))
+      .map(|(text, path)| (path, text))
+      .collect::<HashMap<_, _>>();
+    let is_intact = |file: &ProfileFile| on_disk.get(&file.path) == Some(&file.text);
+
+    // Файл каждого профиля из снимка: сначала неизменённые профили, затем изменённые по имени
+    let mut claimed = vec![None; profiles.len()];
+    let mut is_claimed = vec![false; snapshot.files.len()];
+    for (idx, profile) in profiles.iter().enumerate() {
+      let found = (0..snapshot.files.len())
+        .find(|&i| !is_claimed[i] && snapshot.files[i].profile == *profile);
+      if let Some(i) = found {
+        is_claimed[i] = true;
+        claimed[idx] = Some((i, false));
+      }
+    }
+    for (idx, profile) in profiles.iter().enumerate() {
+      if claimed[idx].is_some() {
+        continue;
+      }
+      let found = (0..snapshot.files.len()).find(|&i| {
+        let file = &snapshot.files[i];
+        !is_claimed[i] && file.profile.name == profile.name && is_intact(file)
+      });
+      if let Some(i) = found {
+        is_claimed[i] = true;
+        claimed[idx] = Some((i, true));
+      }
+    }
+
+    let mut taken = on_disk.keys().cloned().collect::<HashSet<_>>();
+    for (file, _) in snapshot.files.iter().zip(&is_claimed).filter(|(_, c)| !**c) {
+      // Файлы, изменённые извне после снимка, не удаляются
+      if is_intact(file) {
+        fs::remove_file(&file.path)?;
+        taken.remove(&file.path);
+        info!("library: удалён файл профиля {}", file.path.display());
+      }
+    }
+
+    let mut files = Vec::with_capacity(profiles.len());
+    for (profile, claim) in profiles.iter().zip(claimed) {
+      let path = match claim {
+        Some((i, false)) => {
+          files.push(snapshot.files[i].clone());
+          continue;
+        }
+        Some((i, true)) => snapshot.files[i].path.clone(),
+        None => Self::unique_file(dir, &profile.name, &taken),
+      };
+
+      let text = ron::ser::to_string_pretty(
+        &ReadableProfile::from(profile),
+        ron::ser::PrettyConfig::default(),
+      )?;
+      write_atomic(&path, &text)?;
+      taken.insert(path.clone());
+      files.push(ProfileFile {
+        path,
+        text,
+        profile: profile.clone(),
+      });
+    }
+
+    Ok(DirSnapshot {
+      signature: Self::signature(&StorageBackend::Directory(dir.to_path_buf())),
+      files,
+    })
+  }
+
+  /**
+  Вычисляет отпечаток содержимого директории профилей
+
+  Отпечаток меняется при добавлении, удалении и изменении файлов
+  и используется для отслеживания внешних изменений.
+  Для единого файла библиотеки всегда возвращает 0.
+
+  # Аргументы
+  * `storage` - Место хранения профилей
+  */
+  pub fn signature(storage: &StorageBackend) -> u64 {
+    let StorageBackend::Directory(dir) = storage else {
+      return 0;
+    };
+
+    let mut files = Self::dir_files(dir)
+      .unwrap_or_default()
+      .into_iter()
+      .filter_map(|path| {
+        let meta = fs::metadata(&path).ok()?;
+        Some((path, meta.len(), meta.modified().ok()))
+      })
+      .collect::<Vec<_>>();
+    files.sort();
+
+    let mut hasher = DefaultHasher::new();
+    files.hash(&mut hasher);
+    hasher.finish()
+  }
+
+  /// Сохраняет библиотеку в основной файл `profiles.ron`, если его содержимое изменилось
+  fn save_main(&self) -> Result<()> {
+    let path = Self::path()?;
+    let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+    if fs::read_to_string(&path).is_ok_and(|old| old == text) {
+      return Ok(());
+    }
+
+    if let Err(err) = rotate_backup(
+      &path,
+      &Self::backups_dir(),
+      LIBRARY_BACKUPS_LIMIT,
+      LIBRARY_BACKUP_INTERVAL,
+    ) {
+      warn!("library: не удалось сохранить резервную копию: {err}");
+    }
+
+    write_atomic(&path, &text)
+  }
+
+  /**
+  Восстанавливает библиотеку из самой новой читаемой резервной копии
+
+  # Возвращает
+  Библиотеку и путь к копии, из которой она восстановлена
+  */
+  pub async fn restore_latest() -> Result<(Self, PathBuf)> {
+    for path in list_backups(&Self::backups_dir()) {
+      match Self::load_file(&path) {
+        Ok(library) => return Ok((library, path)),
+        Err(err) => warn!("library: копия {} не читается: {err}", path.display()),
+      }
+    }
+
+    bail!("нет читаемых резервных копий библиотеки")
+  }
+
+  /**
+  Загружает библиотеку из указанного файла
+
+  # Аргументы
+  * `path` - Путь к файлу библиотеки
+  */
+  pub fn load_file(path: &Path) -> Result<Self> {
+    Self::parse(&fs::read_to_string(path)?)
+  }
+
+  /**
+  Разбирает профиль из содержимого отдельного файла
+
+  Поддерживается текстовый формат с названиями клавиш и двоичный вид
+  `Profile`, в котором профили сохранялись ранее.
+  */
+  fn parse_profile(text: &str) -> Result<Profile> {
+    match ron::from_str::<ReadableProfile>(text) {
+      Ok(readable) => Profile::try_from(&readable),
+      Err(_) => Ok(ron::from_str(text)?),
+    }
+  }
+
+  /// Возвращает файлы `*.ron` директории профилей
+  fn dir_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    Ok(
+      fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "ron"))
+        .collect(),
+    )
+  }
+
+  /**
+  Строит свободное имя файла профиля по имени профиля
+
+  Символы, недопустимые в именах файлов, заменяются на `_`;
+  при совпадении имён добавляется суффикс `-2`, `-3` и т.д.
+  */
+  fn unique_file(dir: &Path, name: &str, taken: &HashSet<PathBuf>) -> PathBuf {
+    let stem = name
+      .trim_end_matches('\0')
+      .chars()
+      .map(
+        |c| match c.is_alphanumeric() || matches!(c, '-' | '_' | ' ') {
+          true => c,
+          false => '_',
+        },
+      )
+      .collect::<String>();
+    let stem = match stem.trim() {
+      "" => "profile",
+      stem => stem,
+    };
+
+    (1..)
+      .map(|i| match i {
+        1 => dir.join(format!("{stem}.ron")),
+        i => dir.join(format!("{stem}-{i}.ron")),
+      })
+      .find(|path| !taken.contains(path))
+      .unwrap()
+  }
+
+  /**
+  Разбирает библиотеку из текста RON любой поддерживаемой версии
+
+  # Аргументы
+  * `text` - Содержимое файла библиотеки
+
+  # Возвращает
+  Библиотеку текущей версии или ошибку, если файл повреждён
+  или создан более новой версией приложения
+  */
+  pub fn parse(text: &str) -> Result<Self> {
+    let mut value: Value = ron::from_str(text)?;
+
+    let mut version = match value {
+      Value::Seq(_) => 1,
+      _ => value.clone().into_rust::<Header>()?.version,
+    };
+
+    if version > LIBRARY_VERSION {
+      return Err(LibraryError::NewerVersion(version).into());
+    }
+    if version == 0 {
+      bail!("неподдерживаемая версия библиотеки профилей: {version}");
+    }
+
+    while version < LIBRARY_VERSION {
+      value = MIGRATIONS[version as usize - 1](value)?;
+      version += 1;
+      info!("library: библиотека переведена в версию {version}");
+    }
+
+    Ok(value.into_rust()?)
+  }
+}
+
+/// Версия 1 → 2: список профилей оборачивается в структуру с версией и наборами
+fn migrate_v1(value: Value) -> Result<Value> {
+  let mut map = Map::new();
+  map.insert(Value::from("version"), Value::Number(2u32.into()));
+  map.insert(Value::from("profiles"), value);
+  map.insert(Value::from("banks"), Value::Seq(Vec::new()));
+
+  Ok(Value::Map(map))
+}
+
+/**
+Версия 2 → 3: добавлены метаданные профилей
+
+Поле `meta` заполняется значением по умолчанию при разборе,
+меняется только номер версии.
+*/
+fn migrate_v2(value: Value) -> Result<Value> {
+  let Value::Map(mut map) = value else {
+    bail!("библиотека версии 2 должна быть структурой");
+  };
+  map.insert(Value::from("version"), Value::Number(3u32.into()));
+
+  Ok(Value::Map(map))
+}
+
+/**
+Версия 3 → 4: наборы ссылаются на профили библиотеки по имени
+
+Пустой профиль ячейки становится пустой ссылкой. Профиль набора, совпадающий
+с профилем библиотеки, заменяется его именем; остальные профили наборов
+добавляются в библиотеку под свободным именем, чтобы не потерять их содержимое.
+*/
+fn migrate_v3(value: Value) -> Result<Value> {
+  /// Набор версии 3 с копиями профилей
+  #[derive(Deserialize)]
+  struct BankV3 {
+    name: String,
+    profiles: [Profile; 4],
+  }
+
+  let Value::Map(mut map) = value else {
+    bail!("библиотека версии 3 должна быть структурой");
+  };
+  let mut profiles: Vec<Profile> = match map.get(&Value::from("profiles")) {
+    Some(value) => value.clone().into_rust()?,
+    None => Vec::new(),
+  };
+  let banks: Vec<BankV3> = match map.get(&Value::from("banks")) {
+    Some(value) => value.clone().into_rust()?,
+    None => Vec::new(),
+  };
+
+  let banks = banks
+    .into_iter()
+    .map(|bank| {
+      let names = bank.profiles.map(|mut profile| {
+        profile.name = profile.name.trim_end_matches('\0').to_string();
+        if profile == Profile::default() {
+          return None;
+        }
+        if let Some(found) = profiles.iter().find(|p| **p == profile) {
+          return Some(found.name.clone());
+        }
+
+        let base = profile.name.clone();
+        for i in 2.. {
+          if !profiles.iter().any(|p| p.name == profile.name) {
+            break;
+          }
+          profile.name = format!("{base} ({i})");
+        }
+        profiles.push(profile);
+        profiles.last().map(|p| p.name.clone())
+      });
+      Bank {
+        name: bank.name,
+        profiles: names,
+      }
+    })
+    .collect::<Vec<_>>();
+
+  map.insert(Value::from("version"), Value::Number(4u32.into()));
+  map.insert(Value::from("profiles"), to_value(&profiles)?);
+  map.insert(Value::from("banks"), to_value(&banks)?);
+
+  Ok(Value::Map(map))
+}
+
+/// Переводит сериализуемое значение в дерево RON для миграций
+fn to_value<T: Serialize>(value: &T) -> Result<Value> {
+  Ok(ron::from_str(&ron::to_string(value)?)?)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::data::meta::ButtonColor;
+
+  const V1_2_1_3: &str = include_str!("../../tests/fixtures/profiles-v1-2.1.3.ron");
+  const V2: &str = include_str!("../../tests/fixtures/profiles-v2.ron");
+  const V3: &str = include_str!("../../tests/fixtures/profiles-v3.ron");
+  const V4: &str = include_str!("../../tests/fixtures/profiles-v4.ron");
+
+  #[test]
+  fn migrates_bare_profile_list_from_2_1_3() {
+    let library = Library::parse(V1_2_1_3).unwrap();
+
+    assert_eq!(library.version, LIBRARY_VERSION);
+    assert_eq!(library.profiles.len(), 2);
+    assert_eq!(library.profiles[0].name, "Krita");
+    assert_eq!(library.profiles[0].buttons[0], [0xE0, 0x7A, 0, 0, 0, 0]);
+    assert_eq!(library.profiles[1].stick.word, [0xDA, 0xD7, 0xD9, 0xD8]);
+    assert_eq!(library.profiles[1].stick.deadzone, 30);
+    assert!(library.banks.is_empty());
+  }
+
+  #[test]
+  fn migrates_v2_without_metadata() {
+    let library = Library::parse(V2).unwrap();
+
+    assert_eq!(library.version, LIBRARY_VERSION);
+    assert_eq!(library.profiles.len(), 1);
+    assert!(library.profiles[0].meta.is_empty());
+    assert_eq!(library.banks.len(), 1);
+    assert_eq!(library.banks[0].name, "Рисование");
+    assert_eq!(
+      library.banks[0].profiles,
+      [Some("Krita".to_string()), None, None, None]
+    );
+  }
+
+  #[test]
+  fn migrates_v3_banks_to_references() {
+    let library = Library::parse(V3).unwrap();
+
+    assert_eq!(library.version, LIBRARY_VERSION);
+    assert_eq!(library.profiles, Library::parse(V4).unwrap().profiles);
+    assert!(library.banks.is_empty());
+  }
+
+  #[test]
+  fn loads_current_version() {
+    let library = Library::parse(V4).unwrap();
+    let meta = &library.profiles[0].meta;
+    assert_eq!(library.banks[0].profiles[0].as_deref(), Some("Krita"));
+
+    assert_eq!(meta.description, "Рисование в Krita");
+    assert_eq!(meta.tags, ["krita", "рисование"]);
+    assert_eq!(meta.caption(1), Some("Отмена"));
+    assert_eq!(meta.button(1).unwrap().color, Some(ButtonColor::Red));
+    assert!(meta.button(2).is_none());
+
+    let text = ron::ser::to_string_pretty(&library, ron::ser::PrettyConfig::default()).unwrap();
+    assert_eq!(Library::parse(&text).unwrap(), library);
+  }
+
+  #[test]
+  fn saved_library_parses_back() {
+    let library = Library::parse(V1_2_1_3).unwrap();
+    let text = ron::ser::to_string_pretty(&library, ron::ser::PrettyConfig::default()).unwrap();
+
+    assert_eq!(Library::parse(&text).unwrap(), library);
+  }
+
+  #[test]
+  fn rejects_newer_version() {
+    let text = "(version: 99, profiles: [], banks: [])";
+    let err = Library::parse(text).unwrap_err();
+
+    assert!(matches!(
+      err.downcast_ref(),
+      Some(LibraryError::NewerVersion(99))
+    ));
+  }
+
+  fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("claws-library-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+  }
+
+  fn named(name: &str) -> Profile {
+    Profile {
+      name: name.to_string(),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn saves_and_loads_directory() {
+    let dir = test_dir("roundtrip");
+    let storage = StorageBackend::Directory(dir.clone());
+    let profiles = vec![named("Krita"), named("Blender")];
+
+    let saved = Library::save_dir(&dir, &profiles, &DirSnapshot::default()).unwrap();
+    let loaded = Library::load_dir(&dir).unwrap();
+    assert_eq!(saved.signature, Library::signature(&storage));
+    assert_eq!(loaded.signature, saved.signature);
+    let mut names = loaded
+      .profiles()
+      .into_iter()
+      .map(|p| p.name)
+      .collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, ["Blender", "Krita"]);
+
+    // Неизменённые профили не перезаписываются, даже в прежнем формате
+    let legacy = dir.join("Krita.ron");
+    fs::write(&legacy, ron::to_string(&profiles[0]).unwrap()).unwrap();
+    let loaded = Library::load_dir(&dir).unwrap();
+    assert_ne!(loaded.signature, saved.signature);
+    let text = fs::read_to_string(&legacy).unwrap();
+    Library::save_dir(&dir, &loaded.profiles(), &loaded).unwrap();
+    assert_eq!(fs::read_to_string(&legacy).unwrap(), text);
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn keeps_files_changed_outside() {
+    let dir = test_dir("external");
+    let profiles = vec![named("Krita"), named("Blender"), named("GIMP")];
+    let snapshot = Library::save_dir(&dir, &profiles, &DirSnapshot::default()).unwrap();
+
+    // Извне изменён файл Krita и добавлен новый профиль
+    let mut edited = named("Krita");
+    edited.buttons[0][0] = 0x04;
+    fs::write(dir.join("Krita.ron"), ron::to_string(&edited).unwrap()).unwrap();
+    fs::write(dir.join("OBS.ron"), ron::to_string(&named("OBS")).unwrap()).unwrap();
+
+    // В приложении Krita удалён, Blender изменён, GIMP переименован
+    let mut blender = named("Blender");
+    blender.buttons[1][0] = 0x05;
+    let saved = Library::save_dir(&dir, &[blender.clone(), named("Inkscape")], &snapshot).unwrap();
+    assert_eq!(saved.files[0].path, dir.join("Blender.ron"));
+
+    let mut loaded = Library::load_dir(&dir).unwrap().profiles();
+    loaded.sort_by(|a, b| a.name.cmp(&b.name));
+    assert_eq!(loaded, [blender, named("Inkscape"), edited, named("OBS")]);
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn rejects_corrupted_file() {
+    assert!(Library::parse(&V1_2_1_3[..V1_2_1_3.len() / 2]).is_err());
+  }
+}
@@ -0,0 +1,211 @@
+/*!
+Связь ячеек кейпада с профилями библиотеки.
+
+При записи профиля библиотеки в ячейку запоминается имя профиля и хэш
+его содержимого. Сравнение хэшей ячейки и профиля библиотеки с сохранённым
+показывает, какая сторона изменилась после записи. Имя ячейки, перекодированное
+или обрезанное устройством, считается совпадающим с именем профиля библиотеки.
+Связь с именем, которое носят несколько профилей библиотеки, не разрешается.
+*/
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  data::{library::Library, name::is_stored_as, profiles::Profile},
+  errors::library::ProfileRefError,
+};
+
+/// Начальное значение хэша FNV-1a
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// Множитель хэша FNV-1a
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/**
+Вычисляет хэш содержимого профиля
+
+Учитываются имя, кнопки, направления стика и мёртвая зона. Хэш
+не зависит от версии компилятора и способа кодирования имени
+и сохраняется между запусками.
+*/
+pub fn content_hash(profile: &Profile) -> u64 {
+  profile
+    .name
+    .trim_end_matches('\0')
+    .as_bytes()
+    .iter()
+    .chain(profile.buttons.as_flattened())
+    .chain(&profile.stick.word)
+    .chain(&[profile.stick.deadzone])
+    .fold(FNV_OFFSET, |hash, &byte| {
+      (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// Профиль библиотеки, записанный в ячейку
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SlotLink {
+  /// Имя профиля библиотеки
+  pub profile: String,
+  /// Хэш содержимого на момент записи
+  pub hash: u64,
+}
+
+impl SlotLink {
+  /// Создает связь с профилем, записываемым в ячейку
+  pub fn new(profile: &Profile) -> Self {
+    Self {
+      profile: profile.name.clone(),
+      hash: content_hash(profile),
+    }
+  }
+}
+
+/// Состояние синхронизации ячейки с профилем библиотеки
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyncStatus {
+  /// Ячейка совпадает с профилем библиотеки
+  InSync(String),
+  /// Ячейка изменена на устройстве после записи
+  DeviceChanged(String),
+  /// Профиль библиотеки изменён после записи
+  LibraryChanged(String),
+  /// Изменены и ячейка, и профиль библиотеки
+  BothChanged(String),
+  /// Профиля с таким именем больше нет в библиотеке
+  Missing(String),
+  /// Имя носят несколько профилей библиотеки: связанный профиль не определить
+  Ambiguous(String),
+}
+
+impl SyncStatus {
+  /**
+  Определяет состояние синхронизации ячейки
+
+  # Аргументы
+  * `link` - Связь ячейки
+  * `slot` - Профиль ячейки
+  * `library` - Профили библиотеки
+
+  # Возвращает
+  Состояние и индекс связанного профиля библиотеки, если он найден
+  */
+  pub fn of(link: &SlotLink, slot: &Profile, library: &[Profile]) -> (Self, Option<usize>) {
+    let name = link.profile.clone();
+    let idx = match Library::find(library, &link.profile) {
+      Ok(idx) => idx,
+      Err(ProfileRefError::Missing(_)) => return (Self::Missing(name), None),
+      // Синхронизация с первым попавшимся профилем могла бы затереть другой
+      Err(ProfileRefError::Ambiguous(_)) => return (Self::Ambiguous(name), None),
+    };
+
+    let library_hash = content_hash(&library[idx]);
+    let slot_hash = match is_stored_as(&library[idx].name, &slot.name) {
+      true => content_hash(&Profile {
+        name: library[idx].name.clone(),
+        ..slot.clone()
+      }),
+      false => content_hash(slot),
+    };
+    let status = match (slot_hash == link.hash, library_hash == link.hash) {
+      // Совпадение с сохранённым хэшем с обеих сторон означает совпадение содержимого
+      (true, true) => Self::InSync(name),
+      _ if slot_hash == library_hash => Self::InSync(name),
+      (false, true) => Self::DeviceChanged(name),
+      (true, false) => Self::LibraryChanged(name),
+      (false, false) => Self::BothChanged(name),
+    };
+    (status, Some(idx))
+  }
+
+  /**
+  Проверяет, расходятся ли ячейка и профиль библиотеки
+
+  Расхождение можно устранить записью профиля в ячейку
+  или обновлением профиля библиотеки из ячейки.
+  */
+  pub fn is_diverged(&self) -> bool {
+    !matches!(
+      self,
+      Self::InSync(_) | Self::Missing(_) | Self::Ambiguous(_)
+    )
+  }
+}
+
+impl fmt::Display for SyncStatus {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::InSync(name) => write!(f, "Совпадает с «{name}»"),
+      Self::DeviceChanged(name) => write!(f, "«{name}» изменён на устройстве"),
+      Self::LibraryChanged(name) => write!(f, "«{name}» изменён в библиотеке после записи"),
+      Self::BothChanged(name) => write!(f, "«{name}» изменён и на устройстве, и в библиотеке"),
+      Self::Missing(name) => write!(f, "«{name}» нет в библиотеке"),
+      Self::Ambiguous(name) => write!(f, "В библиотеке несколько профилей «{name}»"),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn detects_changed_side() {
+    let written = Profile {
+      name: "Blender".to_string(),
+      ..Default::default()
+    };
+    let link = SlotLink::new(&written);
+    let mut library = vec![written.clone()];
+    let mut slot = written.clone();
+
+    assert_eq!(
+      SyncStatus::of(&link, &slot, &library).0,
+      SyncStatus::InSync("Blender".into())
+    );
+
+    slot.buttons[0] = [b'g', 0, 0, 0, 0, 0];
+    let (status, idx) = SyncStatus::of(&link, &slot, &library);
+    assert_eq!(status, SyncStatus::DeviceChanged("Blender".into()));
+    assert_eq!(idx, Some(0));
+
+    library[0].stick.deadzone += 1;
+    assert!(matches!(
+      SyncStatus::of(&link, &slot, &library).0,
+      SyncStatus::BothChanged(_)
+    ));
+
+    slot = written.clone();
+    assert!(matches!(
+      SyncStatus::of(&link, &slot, &library).0,
+      SyncStatus::LibraryChanged(_)
+    ));
+
+    // Имя в ячейке обрезано устройством
+    let long = [Profile {
+      name: "Редактор схем".to_string(),
+      ..Default::default()
+    }];
+    slot.name = "Редакто".to_string();
+    slot.buttons = long[0].buttons;
+    assert_eq!(
+      SyncStatus::of(&SlotLink::new(&long[0]), &slot, &long).0,
+      SyncStatus::InSync("Редактор схем".into())
+    );
+
+    library[0].name = "Other".to_string();
+    assert!(matches!(
+      SyncStatus::of(&link, &slot, &library).0,
+      SyncStatus::Missing(_)
+    ));
+
+    library[0].name = "Blender".to_string();
+    library.push(written.clone());
+    assert_eq!(
+      SyncStatus::of(&link, &slot, &library),
+      (SyncStatus::Ambiguous("Blender".into()), None)
+    );
+  }
+}
@@ -0,0 +1,313 @@
+/*!
+Проверка профиля на ошибки и рискованные назначения.
+
+Проверки выполняются в редакторе (проблемы отображаются на кнопках)
+и перед каждой записью профиля на устройство: профиль с ошибками
+не записывается, предупреждения только показываются.
+*/
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::{
+  code::{KeyCategory, by_code, chord_set, code_to_name},
+  name::{NAME_LEN, NameEncoding},
+  profiles::Profile,
+  readable::parse_combination,
+};
+
+/// Важность проблемы
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+  /// Назначение работает, но может вести себя неожиданно
+  Warning,
+  /// Назначение не будет работать как задумано; запись блокируется
+  Error,
+}
+
+/// Элемент профиля, к которому относится проблема
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+  /// Имя профиля
+  Name,
+  /// Кнопка 1..=16
+  Button(usize),
+  /// Направление стика 1..=4
+  Stick(usize),
+}
+
+/// Найденная проблема
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+  /// Элемент профиля
+  pub target: Target,
+  /// Важность
+  pub severity: Severity,
+  /// Описание проблемы
+  pub text: String,
+}
+
+/// Настройки проверки профилей
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct LintSettings {
+  /// Системные сочетания клавиш, например `Alt+Tab`, которые не стоит назначать кнопкам
+  pub desktop_shortcuts: Vec<String>,
+}
+
+impl Default for LintSettings {
+  fn default() -> Self {
+    Self {
+      desktop_shortcuts: [
+        "Ctrl+Alt+Del",
+        "Ctrl+Shift+Esc",
+        "Ctrl+Alt+T",
+        "Alt+Tab",
+        "Alt+F4",
+        "Win",
+        "Win+D",
+        "Win+E",
+        "Win+L",
+        "Win+R",
+        "Win+Tab",
+        "PrScr",
+      ]
+      .map(String::from)
+      .to_vec(),
+    }
+  }
+}
+
+/**
+Проверяет профиль
+
+# Аргументы
+* `profile` - Проверяемый профиль
+* `settings` - Настройки проверки
+* `encoding` - Способ кодирования имени на устройстве
+
+# Возвращает
+Найденные проблемы в порядке: имя, кнопки, стик
+*/
+pub fn lint(profile: &Profile, settings: &LintSettings, encoding: NameEncoding) -> Vec<Problem> {
+  let mut problems = Vec::new();
+  let mut push = |target, severity, text: String| {
+    problems.push(Problem {
+      target,
+      severity,
+      text,
+    })
+  };
+
+  let name = profile.name.trim_end_matches('\0');
+  let len = encoding.encode_full(name).len();
+  if len > NAME_LEN {
+    push(
+      Target::Name,
+      Severity::Warning,
+      format!("Имя длиннее {NAME_LEN} байт ({len}) и будет обрезано"),
+    );
+  }
+  if !name.is_ascii() && encoding != NameEncoding::Translit {
+    push(
+      Target::Name,
+      Severity::Warning,
+      "Имя содержит символы не из ASCII".to_string(),
+    );
+  }
+
+  let shortcuts = settings
+    .desktop_shortcuts
+    .iter()
+    .filter_map(|text| Some((text, chord_set(&parse_combination(text).ok()?))))
+    .filter(|(_, chord)| !chord.is_empty())
+    .collect::<Vec<_>>();
+
+  for (i, codes) in profile.buttons.iter().enumerate() {
+    let target = Target::Button(i + 1);
+    let keys = codes
+      .iter()
+      .copied()
+      .filter(|&c| c != 0)
+      .collect::<Vec<_>>();
+
+    for &code in &keys {
+      if let Some((severity, text)) = code_problem(code) {
+        push(target, severity, text);
+      }
+    }
+
+    let mut unique = keys.clone();
+    unique.sort_unstable();
+    unique.dedup();
+    if unique.len() != keys.len() {
+      push(
+        target,
+        Severity::Warning,
+        "Клавиша повторяется в комбинации".to_string(),
+      );
+    }
+
+    if !keys.is_empty() && keys.iter().all(|&code| is_modifier(code)) {
+      push(
+        target,
+        Severity::Warning,
+        "Только модификаторы без основной клавиши".to_string(),
+      );
+    }
+
+    let chord = chord_set(codes);
+    if let Some((text, _)) = shortcuts
+      .iter()
+      .find(|(_, s)| !chord.is_empty() && *s == chord)
+    {
+      push(
+        target,
+        Severity::Warning,
+        format!("Совпадает с системным сочетанием {text}"),
+      );
+    }
+  }
+
+  for (i, &code) in profile.stick.word.iter().enumerate() {
+    if code == 0 {
+      continue;
+    }
+    let target = Target::Stick(i + 1);
+
+    if let Some((severity, text)) = code_problem(code) {
+      push(target, severity, text);
+    }
+
+    // Код стика приводится к тому же виду, что и комбинации кнопок:
+    // правый модификатор совпадает с левым
+    let stick_chord = chord_set(&[code]);
+    for (b, codes) in profile.buttons.iter().enumerate() {
+      if chord_set(codes) == stick_chord {
+        let name = code_to_name(code);
+        push(
+          target,
+          Severity::Warning,
+          format!("Совпадает с кнопкой #{}: {name}", b + 1),
+        );
+        push(
+          Target::Button(b + 1),
+          Severity::Warning,
+          format!("Совпадает с направлением стика #{}: {name}", i + 1),
+        );
+      }
+    }
+  }
+
+  problems
+}
+
+/**
+Формирует краткий итог проверки для строки состояния
+
+# Аргументы
+* `name` - Имя профиля
+* `problems` - Найденные проблемы
+
+# Возвращает
+`None`, если проблем нет
+*/
+pub fn summary(name: &str, problems: &[Problem]) -> Option<String> {
+  let first = problems.iter().max_by_key(|p| p.severity)?;
+  let errors = problems
+    .iter()
+    .filter(|p| p.severity == Severity::Error)
+    .count();
+
+  Some(format!(
+    "Профиль «{}»: ошибок {errors}, предупреждений {}. {}",
+    name.trim_end_matches('\0'),
+    problems.len() - errors,
+    first.text
+  ))
+}
+
+/// Проверяет, есть ли среди проблем ошибки
+pub fn has_errors(problems: &[Problem]) -> bool {
+  problems.iter().any(|p| p.severity == Severity::Error)
+}
+
+/// Проверяет отдельный код клавиши
+fn code_problem(code: u8) -> Option<(Severity, String)> {
+  match by_code(code) {
+    Some(key) if key.category == KeyCategory::Legacy => Some((
+      Severity::Warning,
+      format!("Устаревший код 0x{code:02X}, замените на {}", key.title),
+    )),
+    Some(_) => None,
+    None if code.is_ascii_graphic() => None,
+    None => Some((
+      Severity::Error,
+      format!("Код 0x{code:02X} не соответствует клавише"),
+    )),
+  }
+}
+
+/// Проверяет, является ли код модификатором
+fn is_modifier(code: u8) -> bool {
+  by_code(code).is_some_and(|key| key.category == KeyCategory::Modifier)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn targets(profile: &Profile) -> Vec<(Target, Severity)> {
+    lint(profile, &LintSettings::default(), NameEncoding::default())
+      .into_iter()
+      .map(|p| (p.target, p.severity))
+      .collect()
+  }
+
+  #[test]
+  fn clean_profile_has_no_problems() {
+    let mut profile = Profile {
+      name: "Editor".to_string(),
+      ..Default::default()
+    };
+    profile.buttons[0] = [0x80, b'c', 0, 0, 0, 0];
+    assert!(targets(&profile).is_empty());
+  }
+
+  #[test]
+  fn flags_risky_bindings() {
+    let mut profile = Profile {
+      name: "Очень длинное имя".to_string(),
+      ..Default::default()
+    };
+    profile.buttons[0] = [0xFF, 0, 0, 0, 0, 0];
+    profile.buttons[1] = [b'a', b'a', 0, 0, 0, 0];
+    profile.buttons[2] = [0x80, 0x81, 0, 0, 0, 0];
+    profile.buttons[3] = [0x86, 0xB3, 0, 0, 0, 0];
+    profile.buttons[4] = [0xDA, 0, 0, 0, 0, 0];
+    profile.stick.word[0] = 0xDA;
+
+    let found = targets(&profile);
+    assert_eq!(found.iter().filter(|(t, _)| *t == Target::Name).count(), 2);
+    assert!(found.contains(&(Target::Button(1), Severity::Error)));
+    assert!(found.contains(&(Target::Button(2), Severity::Warning)));
+    assert!(found.contains(&(Target::Button(3), Severity::Warning)));
+    assert!(found.contains(&(Target::Button(4), Severity::Warning)));
+    assert!(found.contains(&(Target::Button(5), Severity::Warning)));
+    assert!(found.contains(&(Target::Stick(1), Severity::Warning)));
+    assert!(has_errors(&lint(
+      &profile,
+      &LintSettings::default(),
+      NameEncoding::default()
+    )));
+  }
+
+  #[test]
+  fn matches_right_modifier_on_stick() {
+    let mut profile = Profile::default();
+    profile.buttons[0] = [0x80, 0, 0, 0, 0, 0];
+    profile.stick.word[1] = 0x84;
+
+    let found = targets(&profile);
+    assert!(found.contains(&(Target::Stick(2), Severity::Warning)));
+  }
+}
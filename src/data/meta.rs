@@ -0,0 +1,246 @@
+/*!
+Локальные метаданные профиля.
+
+Описание, автор, время создания и изменения, теги и подписи кнопок
+хранятся только в библиотеке и файлах экспорта. На устройство
+метаданные не отправляются: `Keypad::profile_send` записывает
+только имя, кнопки и стик.
+*/
+
+use std::fmt;
+
+use iced::Color;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::time::unix_now;
+
+/**
+Цвет кнопки в редакторе
+
+Сохраняется строкой с английским названием цвета: библиотека разбирается
+через `ron::Value`, в котором имена вариантов перечислений не сохраняются.
+*/
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(into = "String", try_from = "String")]
+pub enum ButtonColor {
+  Red,
+  Orange,
+  Yellow,
+  Green,
+  Blue,
+  Purple,
+  Gray,
+}
+
+impl ButtonColor {
+  /// Все цвета кнопок
+  pub const ALL: [Self; 7] = [
+    Self::Red,
+    Self::Orange,
+    Self::Yellow,
+    Self::Green,
+    Self::Blue,
+    Self::Purple,
+    Self::Gray,
+  ];
+
+  /// Возвращает цвет фона кнопки
+  pub fn color(&self) -> Color {
+    match self {
+      Self::Red => Color::from_rgb8(0xd6, 0x45, 0x45),
+      Self::Orange => Color::from_rgb8(0xe0, 0x8a, 0x2e),
+      Self::Yellow => Color::from_rgb8(0xc9, 0xa8, 0x1c),
+      Self::Green => Color::from_rgb8(0x3f, 0xa3, 0x5b),
+      Self::Blue => Color::from_rgb8(0x3b, 0x7d, 0xd8),
+      Self::Purple => Color::from_rgb8(0x8e, 0x5c, 0xd9),
+      Self::Gray => Color::from_rgb8(0x80, 0x80, 0x80),
+    }
+  }
+}
+
+impl From<ButtonColor> for String {
+  fn from(color: ButtonColor) -> Self {
+    format!("{color:?}")
+  }
+}
+
+impl TryFrom<String> for ButtonColor {
+  type Error = String;
+
+  fn try_from(name: String) -> Result<Self, Self::Error> {
+    Self::ALL
+      .into_iter()
+      .find(|color| format!("{color:?}") == name)
+      .ok_or_else(|| format!("неизвестный цвет кнопки: {name}"))
+  }
+}
+
+impl fmt::Display for ButtonColor {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let title = match self {
+      Self::Red => "Красный",
+      Self::Orange => "Оранжевый",
+      Self::Yellow => "Жёлтый",
+      Self::Green => "Зелёный",
+      Self::Blue => "Синий",
+      Self::Purple => "Фиолетовый",
+      Self::Gray => "Серый",
+    };
+    write!(f, "{title}")
+  }
+}
+
+/// Метаданные кнопки
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ButtonMeta {
+  /// Номер кнопки 1..=16
+  pub button: usize,
+
+  /// Подпись, отображаемая на кнопке вместо комбинации
+  #[serde(skip_serializing_if = "String::is_empty")]
+  pub caption: String,
+
+  /// Заметка о назначении кнопки
+  #[serde(skip_serializing_if = "String::is_empty")]
+  pub note: String,
+
+  /// Цвет кнопки в редакторе
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub color: Option<ButtonColor>,
+}
+
+impl ButtonMeta {
+  /// Проверяет, что подпись, заметка и цвет не заполнены
+  pub fn is_empty(&self) -> bool {
+    self.caption.is_empty() && self.note.is_empty() && self.color.is_none()
+  }
+}
+
+/// Метаданные профиля
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ProfileMeta {
+  /// Описание профиля
+  #[serde(skip_serializing_if = "String::is_empty")]
+  pub description: String,
+
+  /// Автор профиля
+  #[serde(skip_serializing_if = "String::is_empty")]
+  pub author: String,
+
+  /// Время создания, секунды от начала эпохи Unix
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub created: Option<u64>,
+
+  /// Время последнего изменения, секунды от начала эпохи Unix
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub modified: Option<u64>,
+
+  /// Теги для поиска и группировки
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub tags: Vec<String>,
+
+  /// Метаданные кнопок, только заполненные, по возрастанию номера
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub buttons: Vec<ButtonMeta>,
+}
+
+impl ProfileMeta {
+  /// Проверяет, что метаданные не заполнены
+  pub fn is_empty(&self) -> bool {
+    *self == Self::default()
+  }
+
+  /// Возвращает метаданные кнопки с номером `button` 1..=16
+  pub fn button(&self, button: usize) -> Option<&ButtonMeta> {
+    self.buttons.iter().find(|meta| meta.button == button)
+  }
+
+  /// Возвращает непустую подпись кнопки с номером `button` 1..=16
+  pub fn caption(&self, button: usize) -> Option<&str> {
+    self
+      .button(button)
+      .map(|meta| meta.caption.as_str())
+      .filter(|caption| !caption.is_empty())
+  }
+
+  /**
+  Изменяет метаданные кнопки
+
+  Метаданные, ставшие пустыми, удаляются из списка.
+
+  # Аргументы
+  * `button` - Номер кнопки 1..=16
+  * `f` - Изменение метаданных
+  */
+  pub fn update_button(&mut self, button: usize, f: impl FnOnce(&mut ButtonMeta)) {
+    let i = match self
+      .buttons
+      .binary_search_by_key(&button, |meta| meta.button)
+    {
+      Ok(i) => i,
+      Err(i) => {
+        let meta = ButtonMeta {
+          button,
+          ..Default::default()
+        };
+        self.buttons.insert(i, meta);
+        i
+      }
+    };
+    f(&mut self.buttons[i]);
+    if self.buttons[i].is_empty() {
+      self.buttons.remove(i);
+    }
+  }
+
+  /// Отмечает профиль как только что созданный
+  pub fn mark_created(&mut self) {
+    let now = unix_now();
+    self.created = Some(now);
+    self.modified = Some(now);
+  }
+
+  /// Отмечает время изменения профиля
+  pub fn mark_modified(&mut self) {
+    self.modified = Some(unix_now());
+  }
+
+  /**
+  Добавляет тег, если его ещё нет
+
+  # Возвращает
+  `false`, если тег пустой или уже есть
+  */
+  pub fn add_tag(&mut self, tag: &str) -> bool {
+    let tag = tag.trim();
+    let lower = tag.to_lowercase();
+    if tag.is_empty() || self.tags.iter().any(|t| t.to_lowercase() == lower) {
+      return false;
+    }
+    self.tags.push(tag.to_string());
+    true
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn empty_meta_is_not_serialized() {
+    let mut meta = ProfileMeta::default();
+    assert_eq!(ron::to_string(&meta).unwrap(), "()");
+
+    meta.update_button(3, |meta| meta.caption = "Кисть".to_string());
+    meta.update_button(1, |meta| meta.color = Some(ButtonColor::Blue));
+    assert_eq!(meta.caption(3), Some("Кисть"));
+    meta.update_button(1, |meta| meta.color = None);
+    assert_eq!(meta.buttons.len(), 1);
+    assert!(meta.add_tag("Krita"));
+    assert!(!meta.add_tag(" krita "));
+    let text = ron::to_string(&meta).unwrap();
+    assert_eq!(ron::from_str::<ProfileMeta>(&text).unwrap(), meta);
+  }
+}
@@ -1,8 +1,31 @@
+use std::path::PathBuf;
+
+use crate::assets::APPLICATION_NAME;
+
+pub mod backup;
+pub mod banks;
+pub mod capture;
+pub mod cells;
 pub mod code;
+pub mod compare;
 pub mod device;
 pub mod file_dialog;
+pub mod history;
+pub mod keymaps;
+pub mod layout;
+pub mod library;
+pub mod links;
+pub mod lint;
+pub mod meta;
+pub mod name;
 pub mod profiles;
+pub mod qmk;
+pub mod readable;
+pub mod search;
+pub mod settings;
 pub mod stick;
+pub mod storage;
+pub mod templates;
 pub mod window;
 
 /// Трейт конфигурации для сущностей, которые могут сохранять своё состояние.
@@ -10,3 +33,9 @@ pub trait Config {
   /// Сохраняет текущее состояние конфигурации в постоянное хранилище.
   fn save(&self);
 }
+
+/// Возвращает директорию конфигурации приложения
+pub fn config_dir() -> PathBuf {
+  let file_path = confy::get_configuration_file_path(APPLICATION_NAME, None).unwrap();
+  file_path.parent().unwrap().to_path_buf()
+}
@@ -0,0 +1,260 @@
+/*!
+Кодирование имени профиля для устройства.
+
+Устройство хранит имя в поле фиксированной длины 15 байт, дополненном
+нулями. Имя кодируется выбранным пользователем способом и обрезается
+по границе символа, поэтому в поле не попадают «половинки» символов.
+При чтении нулевые байты отбрасываются, а кодировка определяется
+по содержимому: корректная последовательность UTF-8 читается как UTF-8,
+остальное — как CP1251.
+*/
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Длина поля имени на устройстве в байтах
+pub const NAME_LEN: usize = 15;
+
+/// Символы CP1251 в диапазоне 0x80..=0xBF; 0xC0..=0xFF — буквы А..я подряд
+const CP1251_HIGH: [char; 64] = [
+  'Ђ', 'Ѓ', '‚', 'ѓ', '„', '…', '†', '‡', '€', '‰', 'Љ', '‹', 'Њ', 'Ќ', 'Ћ', 'Џ', //
+  'ђ', '‘', '’', '“', '”', '•', '–', '—', '?', '™', 'љ', '›', 'њ', 'ќ', 'ћ', 'џ', //
+  '\u{A0}', 'Ў', 'ў', 'Ј', '¤', 'Ґ', '¦', '§', 'Ё', '©', 'Є', '«', '¬', '\u{AD}', '®', 'Ї', //
+  '°', '±', 'І', 'і', 'ґ', 'µ', '¶', '·', 'ё', '№', 'є', '»', 'ј', 'Ѕ', 'ѕ', 'ї', //
+];
+
+/// Транслитерация строчных русских букв
+const TRANSLIT: [(char, &str); 33] = [
+  ('а', "a"),
+  ('б', "b"),
+  ('в', "v"),
+  ('г', "g"),
+  ('д', "d"),
+  ('е', "e"),
+  ('ё', "yo"),
+  ('ж', "zh"),
+  ('з', "z"),
+  ('и', "i"),
+  ('й', "y"),
+  ('к', "k"),
+  ('л', "l"),
+  ('м', "m"),
+  ('н', "n"),
+  ('о', "o"),
+  ('п', "p"),
+  ('р', "r"),
+  ('с', "s"),
+  ('т', "t"),
+  ('у', "u"),
+  ('ф', "f"),
+  ('х', "kh"),
+  ('ц', "ts"),
+  ('ч', "ch"),
+  ('ш', "sh"),
+  ('щ', "sch"),
+  ('ъ', ""),
+  ('ы', "y"),
+  ('ь', ""),
+  ('э', "e"),
+  ('ю', "yu"),
+  ('я', "ya"),
+];
+
+/// Способ кодирования имени профиля
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum NameEncoding {
+  /// UTF-8: кириллический символ занимает 2 байта
+  #[default]
+  Utf8,
+  /// Windows-1251: кириллический символ занимает 1 байт
+  Cp1251,
+  /// Транслитерация латиницей, на устройство попадает только ASCII
+  Translit,
+}
+
+impl NameEncoding {
+  /// Все способы кодирования
+  pub const ALL: [Self; 3] = [Self::Utf8, Self::Cp1251, Self::Translit];
+
+  /**
+  Кодирует имя без ограничения длины
+
+  Символы, которые нельзя представить в кодировке, заменяются на `?`.
+
+  # Аргументы
+  * `name` - Имя профиля
+  */
+  pub fn encode_full(&self, name: &str) -> Vec<u8> {
+    self
+      .encode_chars(name.trim_end_matches('\0'))
+      .into_iter()
+      .flatten()
+      .collect()
+  }
+
+  /**
+  Кодирует имя в поле устройства
+
+  Имя обрезается по границе символа так, чтобы уместиться в `NAME_LEN` байт,
+  остаток поля заполняется нулями.
+
+  # Аргументы
+  * `name` - Имя профиля
+  */
+  pub fn encode(&self, name: &str) -> [u8; NAME_LEN] {
+    let mut field = [0; NAME_LEN];
+    let mut len = 0;
+
+    for bytes in self.encode_chars(name.trim_end_matches('\0')) {
+      if len + bytes.len() > NAME_LEN {
+        break;
+      }
+      field[len..len + bytes.len()].copy_from_slice(&bytes);
+      len += bytes.len();
+    }
+
+    field
+  }
+
+  /// Кодирует каждый символ имени отдельно
+  fn encode_chars(&self, name: &str) -> Vec<Vec<u8>> {
+    match self {
+      Self::Utf8 => name.chars().map(|c| c.to_string().into_bytes()).collect(),
+      Self::Cp1251 => name.chars().map(|c| vec![cp1251_byte(c)]).collect(),
+      Self::Translit => name
+        .chars()
+        .flat_map(|c| translit(c).bytes().collect::<Vec<_>>())
+        .map(|b| vec![b])
+        .collect(),
+    }
+  }
+}
+
+impl fmt::Display for NameEncoding {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let title = match self {
+      Self::Utf8 => "UTF-8",
+      Self::Cp1251 => "CP1251",
+      Self::Translit => "Транслитерация",
+    };
+    write!(f, "{title}")
+  }
+}
+
+/**
+Декодирует имя, прочитанное с устройства
+
+Нулевые байты в конце поля отбрасываются. Корректная последовательность
+UTF-8 декодируется как UTF-8, иначе байты читаются как CP1251.
+
+# Аргументы
+* `bytes` - Содержимое поля имени
+*/
+pub fn decode(bytes: &[u8]) -> String {
+  let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+  let bytes = &bytes[..end];
+
+  match std::str::from_utf8(bytes) {
+    Ok(name) => name.to_string(),
+    Err(_) => bytes.iter().map(|&b| cp1251_char(b)).collect(),
+  }
+}
+
+/**
+Проверяет, может ли имя, прочитанное с устройства, быть записанным именем
+
+Имя в поле устройства могло быть перекодировано и обрезано любым
+способом кодирования, поэтому проверяются все способы.
+
+# Аргументы
+* `name` - Записанное имя
+* `stored` - Имя, прочитанное с устройства
+*/
+pub fn is_stored_as(name: &str, stored: &str) -> bool {
+  let stored = stored.trim_end_matches('\0');
+  NameEncoding::ALL
+    .iter()
+    .any(|encoding| decode(&encoding.encode(name)) == stored)
+}
+
+/// Возвращает символ CP1251 для байта
+fn cp1251_char(byte: u8) -> char {
+  match byte {
+    0x00..=0x7F => char::from(byte),
+    0x80..=0xBF => CP1251_HIGH[usize::from(byte - 0x80)],
+    _ => char::from_u32(0x0410 + u32::from(byte - 0xC0)).unwrap_or('?'),
+  }
+}
+
+/// Возвращает байт CP1251 для символа или `?`, если символа нет в кодировке
+fn cp1251_byte(c: char) -> u8 {
+  match c {
+    c if c.is_ascii() => c as u8,
+    'А'..='я' => (u32::from(c) - 0x0410 + 0xC0) as u8,
+    c => CP1251_HIGH
+      .iter()
+      .position(|&high| high == c && c != '?')
+      .map_or(b'?', |i| 0x80 + i as u8),
+  }
+}
+
+/// Транслитерирует символ латиницей
+fn translit(c: char) -> String {
+  if c.is_ascii() {
+    return c.to_string();
+  }
+
+  let lower = c.to_lowercase().next().unwrap_or(c);
+  match TRANSLIT.iter().find(|(ru, _)| *ru == lower) {
+    Some((_, latin)) if lower != c => {
+      let mut chars = latin.chars();
+      chars
+        .next()
+        .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+        .unwrap_or_default()
+    }
+    Some((_, latin)) => latin.to_string(),
+    None => "?".to_string(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn truncates_on_char_boundary() {
+    // 8 кириллических символов — 16 байт UTF-8, в поле помещается 7
+    let field = NameEncoding::Utf8.encode("Редактор");
+    assert_eq!(decode(&field), "Редакто");
+    assert_eq!(field[14], 0);
+  }
+
+  #[test]
+  fn round_trips_cp1251() {
+    let field = NameEncoding::Cp1251.encode("Ёлка №1");
+    assert_eq!(&field[..7], &[0xA8, 0xEB, 0xEA, 0xE0, b' ', 0xB9, b'1']);
+    assert_eq!(decode(&field), "Ёлка №1");
+  }
+
+  #[test]
+  fn transliterates() {
+    assert_eq!(
+      NameEncoding::Translit.encode_full("Щука и Ёж"),
+      b"Schuka i Yozh"
+    );
+  }
+
+  #[test]
+  fn matches_stored_name() {
+    assert!(is_stored_as("Редактор", "Редакто"));
+    assert!(is_stored_as("Щука", "Schuka"));
+    assert!(!is_stored_as("Щука", "Щук"));
+  }
+
+  #[test]
+  fn strips_padding() {
+    assert_eq!(decode(b"GIMP\0\0\0\0\0\0\0\0\0\0\0"), "GIMP");
+  }
+}
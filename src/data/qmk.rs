@@ -0,0 +1,371 @@
+/*!
+Импорт и экспорт раскладок QMK и VIA.
+
+Из `keymap.json` QMK или файла раскладки VIA берутся первые 16 клавиш
+нулевого слоя. Клавиши QMK перечисляются по рядам, а кнопки кейпада
+нумеруются по колонкам (1–4 — первая колонка), поэтому клавиша ряда `r`
+и колонки `c` становится кнопкой `c * 4 + r + 1`; при экспорте порядок
+обратный.
+
+Клавиши записываются названиями QMK (`KC_A`, `LCTL(KC_Z)`) или числовыми
+кодами VIA: код HID в младшем байте и модификаторы в старшем. Названия
+и коды HID клавиш берутся из реестра `code::KEYS`. Слои,
+переключение слоёв, мышь и мультимедиа кейпад не поддерживает — такие
+клавиши не переносятся и перечисляются в отчёте. Стик в раскладку QMK
+не входит.
+*/
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::data::code::{self, KEYS, KeyCategory};
+use crate::data::profiles::{KEYPAD_BUTTONS, Profile};
+
+/// Раскладка QMK, для которой экспортируются профили
+const QMK_LAYOUT: &str = "LAYOUT_ortho_4x4";
+
+/// Сокращённые и составные функции QMK, добавляющие модификаторы к клавише;
+/// функции одного модификатора берутся из названий QMK в `code::KEYS`
+const MODIFIER_FUNCTIONS: &[(&str, &[u8])] = &[
+  ("C", &[0x80]),
+  ("S", &[0x81]),
+  ("A", &[0x82]),
+  ("G", &[0x83]),
+  ("C_S", &[0x80, 0x81]),
+  ("LCA", &[0x80, 0x82]),
+  ("LSA", &[0x81, 0x82]),
+  ("SGUI", &[0x81, 0x83]),
+  ("LCAG", &[0x80, 0x82, 0x83]),
+  ("MEH", &[0x80, 0x81, 0x82]),
+  ("HYPR", &[0x80, 0x81, 0x82, 0x83]),
+];
+
+/// Возвращает название QMK клавиши устройства
+fn code_to_name(code: u8) -> Option<&'static str> {
+  code::by_code(code)?.qmk.first().copied()
+}
+
+/// Возвращает код устройства клавиши с кодом HID
+fn usage_to_code(usage: u8) -> Option<u8> {
+  KEYS
+    .iter()
+    .find(|key| key.usage != 0 && key.usage == usage)
+    .map(|key| key.code)
+}
+
+/// Возвращает код устройства клавиши с названием QMK
+fn name_to_code(name: &str) -> Option<u8> {
+  KEYS
+    .iter()
+    .find(|key| key.qmk.contains(&name))
+    .map(|key| key.code)
+}
+
+/// Возвращает модификаторы, которые добавляет функция QMK, например `LCTL`
+fn function_to_modifiers(function: &str) -> Option<Vec<u8>> {
+  if let Some((_, modifiers)) = MODIFIER_FUNCTIONS
+    .iter()
+    .find(|(name, _)| *name == function)
+  {
+    return Some(modifiers.to_vec());
+  }
+
+  KeyCategory::Modifier
+    .keys()
+    .find(|key| {
+      key
+        .qmk
+        .iter()
+        .any(|name| name.strip_prefix("KC_") == Some(function))
+    })
+    .map(|key| vec![key.code])
+}
+
+/// Переводит клавишу QMK в коды устройства: модификаторы и клавишу
+fn keycode_to_codes(keycode: &Value) -> Result<Vec<u8>, String> {
+  match keycode {
+    Value::String(keycode) => expression_to_codes(keycode.trim()),
+    Value::Number(number) => {
+      let value = number
+        .as_u64()
+        .and_then(|value| u16::try_from(value).ok())
+        .ok_or_else(|| format!("неверный код {number}"))?;
+      numeric_to_codes(value)
+    }
+    _ => Err(format!("неверная клавиша {keycode}")),
+  }
+}
+
+/// Переводит клавишу QMK, записанную названием, например `LCTL(KC_Z)`
+fn expression_to_codes(keycode: &str) -> Result<Vec<u8>, String> {
+  if matches!(
+    keycode,
+    "" | "KC_NO" | "XXXXXXX" | "KC_TRNS" | "KC_TRANSPARENT" | "_______"
+  ) {
+    return Ok(Vec::new());
+  }
+
+  if let Some((function, inner)) = keycode
+    .strip_suffix(')')
+    .and_then(|keycode| keycode.split_once('('))
+  {
+    let Some(mut codes) = function_to_modifiers(function) else {
+      return Err(format!("функция {function}() не поддерживается кейпадом"));
+    };
+    codes.extend(expression_to_codes(inner.trim())?);
+    return Ok(codes);
+  }
+
+  name_to_code(keycode)
+    .map(|code| vec![code])
+    .ok_or_else(|| "клавиша не поддерживается кейпадом".to_string())
+}
+
+/// Переводит числовой код VIA: модификаторы в битах 8–12, код HID в младшем байте
+fn numeric_to_codes(value: u16) -> Result<Vec<u8>, String> {
+  let (modifiers, usage) = ((value >> 8) as u8, value as u8);
+  if value == 0 || value == 1 {
+    // KC_NO и KC_TRNS
+    return Ok(Vec::new());
+  }
+  if modifiers > 0x1F {
+    return Err(format!("код 0x{value:04X} не поддерживается кейпадом"));
+  }
+
+  let right = modifiers & 0x10 != 0;
+  let mut codes = (0..4)
+    .filter(|bit| modifiers & (1 << bit) != 0)
+    .map(|bit| 0x80 + bit + if right { 4 } else { 0 })
+    .collect::<Vec<_>>();
+  codes.push(
+    usage_to_code(usage)
+      .ok_or_else(|| format!("код HID 0x{usage:02X} не поддерживается кейпадом"))?,
+  );
+  Ok(codes)
+}
+
+/// Переводит комбинацию кнопки в клавишу QMK
+fn codes_to_keycode(codes: &[u8; 6]) -> Result<String, String> {
+  let codes = codes.iter().copied().filter(|&code| code != 0);
+  let (modifiers, keys): (Vec<u8>, Vec<u8>) = codes.partition(|code| (0x80..=0x87).contains(code));
+
+  let (modifiers, key) = match (&modifiers[..], &keys[..]) {
+    ([], []) => return Ok("KC_NO".to_string()),
+    (_, [key]) => (&modifiers[..], *key),
+    // Только модификаторы: последний становится клавишей
+    ([rest @ .., last], []) => (rest, *last),
+    _ => return Err("в QMK клавише может быть только одна немодифицирующая клавиша".to_string()),
+  };
+
+  let mut keycode = code_to_name(key)
+    .ok_or_else(|| format!("код 0x{key:02X} не поддерживается QMK"))?
+    .to_string();
+  for &modifier in modifiers.iter().rev() {
+    let function = code_to_name(modifier)
+      .and_then(|name| name.strip_prefix("KC_"))
+      .ok_or_else(|| format!("код 0x{modifier:02X} не поддерживается QMK"))?;
+    keycode = format!("{function}({keycode})");
+  }
+  Ok(keycode)
+}
+
+/// Номер кнопки (1..=16) для клавиши раскладки QMK с индексом `idx` 0..16
+fn button_of(idx: usize) -> usize {
+  (idx % 4) * 4 + idx / 4 + 1
+}
+
+/// Файл раскладки QMK или VIA
+#[derive(Debug, Deserialize)]
+struct QmkFile {
+  /// Имя раскладки QMK
+  #[serde(default)]
+  keymap: Option<String>,
+  /// Имя клавиатуры в файле VIA
+  #[serde(default)]
+  name: Option<String>,
+  /// Слои: клавиши по рядам
+  layers: Vec<Vec<Value>>,
+}
+
+/// Профиль, импортированный из раскладки QMK или VIA
+#[derive(Debug, Clone, PartialEq)]
+pub struct QmkImport {
+  /// Профиль с перенесёнными кнопками
+  pub profile: Profile,
+  /// Непереносимые клавиши: номер кнопки, клавиша и причина
+  pub skipped: Vec<String>,
+  /// Число слоёв раскладки; переносится только нулевой
+  pub layers: usize,
+}
+
+/**
+Импортирует раскладку QMK `keymap.json` или файл раскладки VIA
+
+# Аргументы
+* `text` - Содержимое файла
+* `fallback_name` - Имя профиля, если в файле его нет
+
+# Возвращает
+Профиль из первых 16 клавиш нулевого слоя и список непереносимых клавиш
+*/
+pub fn import(text: &str, fallback_name: &str) -> Result<QmkImport> {
+  let file: QmkFile = serde_json::from_str(text).context("файл не похож на раскладку QMK/VIA")?;
+  let layer = file.layers.first().context("в раскладке нет слоёв")?;
+
+  let name = file
+    .keymap
+    .filter(|keymap| !keymap.is_empty() && keymap != "default")
+    .or(file.name)
+    .unwrap_or_else(|| fallback_name.to_string());
+  let mut profile = Profile {
+    name,
+    buttons: [[0; 6]; 16],
+    ..Default::default()
+  };
+
+  let mut skipped = Vec::new();
+  for (idx, keycode) in layer.iter().take(KEYPAD_BUTTONS as usize).enumerate() {
+    let button = button_of(idx);
+    match keycode_to_codes(keycode) {
+      Ok(codes) if codes.len() <= 6 => {
+        profile.buttons[button - 1][..codes.len()].copy_from_slice(&codes);
+      }
+      Ok(_) => skipped.push(format!("Кнопка {button}: {keycode} — больше 6 клавиш")),
+      Err(reason) => skipped.push(format!("Кнопка {button}: {keycode} — {reason}")),
+    }
+  }
+
+  Ok(QmkImport {
+    profile,
+    skipped,
+    layers: file.layers.len(),
+  })
+}
+
+/// Раскладка QMK `keymap.json` для экспорта
+#[derive(Debug, Serialize)]
+struct QmkKeymap {
+  version: u32,
+  keyboard: String,
+  keymap: String,
+  layout: String,
+  layers: Vec<Vec<String>>,
+  author: String,
+  notes: String,
+}
+
+/**
+Экспортирует кнопки профиля в раскладку QMK `keymap.json`
+
+Имя клавиатуры в файле не заполняется: его нужно указать перед сборкой
+прошивки QMK. Непереносимые кнопки записываются как `KC_NO`.
+
+# Аргументы
+* `profile` - Экспортируемый профиль
+
+# Возвращает
+Текст `keymap.json` и список непереносимых кнопок
+*/
+pub fn export(profile: &Profile) -> Result<(String, Vec<String>)> {
+  let mut skipped = Vec::new();
+  let keys = (0..KEYPAD_BUTTONS as usize)
+    .map(|idx| {
+      let button = button_of(idx);
+      codes_to_keycode(&profile.buttons[button - 1]).unwrap_or_else(|reason| {
+        skipped.push(format!("Кнопка {button}: {reason}"));
+        "KC_NO".to_string()
+      })
+    })
+    .collect();
+
+  // Имя раскладки QMK — имя директории, только латиница, цифры и `_`
+  let keymap = profile
+    .name
+    .chars()
+    .map(|c| match c.is_ascii_alphanumeric() {
+      true => c.to_ascii_lowercase(),
+      false => '_',
+    })
+    .collect::<String>();
+  let keymap = keymap.trim_matches('_');
+
+  let file = QmkKeymap {
+    version: 1,
+    keyboard: String::new(),
+    keymap: match keymap.is_empty() {
+      true => "claws".to_string(),
+      false => keymap.to_string(),
+    },
+    layout: QMK_LAYOUT.to_string(),
+    layers: vec![keys],
+    author: profile.meta.author.clone(),
+    notes: profile.meta.description.clone(),
+  };
+
+  Ok((serde_json::to_string_pretty(&file)?, skipped))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn imports_named_and_numeric_keycodes() {
+    let text = r#"{
+      "keyboard": "example/macropad",
+      "keymap": "default",
+      "layers": [
+        ["KC_A", "LCTL(KC_Z)", "C_S(KC_F13)", "MO(1)",
+         "KC_KP_1", "KC_EXLM", "KC_TRNS", "KC_VOLU",
+         4, 4356, 20484, "KC_NO",
+         "KC_ENTER", "KC_LEFT", "KC_NO", "KC_NO",
+         "KC_B"],
+        ["KC_TRNS"]
+      ]
+    }"#;
+    let import = import(text, "Макропад").unwrap();
+    let buttons = &import.profile.buttons;
+
+    assert_eq!(import.profile.name, "Макропад");
+    assert_eq!(import.layers, 2);
+    // Второй ряд первой колонки — кнопка 2
+    assert_eq!(buttons[0], [b'a', 0, 0, 0, 0, 0]);
+    assert_eq!(buttons[4], [0x80, b'z', 0, 0, 0, 0]);
+    assert_eq!(buttons[8], [0x80, 0x81, 0xF0, 0, 0, 0]);
+    assert_eq!(buttons[1], [0xE1, 0, 0, 0, 0, 0]);
+    assert_eq!(buttons[5], [b'!', 0, 0, 0, 0, 0]);
+    // Числовые коды VIA: 4 — KC_A, 0x1104 — RCTL(KC_A)
+    assert_eq!(buttons[2], [b'a', 0, 0, 0, 0, 0]);
+    assert_eq!(buttons[6], [0x84, b'a', 0, 0, 0, 0]);
+    assert_eq!(buttons[3], [0xB0, 0, 0, 0, 0, 0]);
+    assert_eq!(import.skipped.len(), 3, "{:?}", import.skipped);
+    assert!(import.skipped[0].starts_with("Кнопка 13: \"MO(1)\""));
+  }
+
+  #[test]
+  fn export_round_trips() {
+    let mut profile = Profile {
+      name: "Krita 2".to_string(),
+      buttons: [[0; 6]; 16],
+      ..Default::default()
+    };
+    profile.buttons[0] = [0x80, 0x81, b'z', 0, 0, 0];
+    profile.buttons[4] = [0xD3, 0, 0, 0, 0, 0];
+    profile.buttons[15] = [b'!', 0, 0, 0, 0, 0];
+    profile.buttons[1] = [b'a', b'b', 0, 0, 0, 0];
+
+    let (text, skipped) = export(&profile).unwrap();
+    assert_eq!(
+      skipped,
+      ["Кнопка 2: в QMK клавише может быть только одна немодифицирующая клавиша"]
+    );
+    assert!(text.contains("\"krita_2\""));
+    assert!(text.contains("LCTL(LSFT(KC_Z))"));
+
+    let import = import(&text, "").unwrap();
+    profile.buttons[1] = [0; 6];
+    assert_eq!(import.profile.buttons, profile.buttons);
+    assert!(import.skipped.is_empty());
+  }
+}
@@ -0,0 +1,538 @@
+/*!
+Текстовый формат профилей с названиями клавиш вместо кодов.
+
+Кнопка записывается комбинацией названий клавиш через `+` (`"LCtrl+C"`),
+направления стика и мёртвая зона — именованными полями. Названия берутся
+из реестра клавиш `data::code::KEYS` и разбираются обратно через него же.
+Коды без однозначного названия записываются как `0xNN`, поэтому
+преобразование в двоичный вид и обратно выполняется без потерь.
+
+Формат поддерживается в RON, JSON и TOML. Кроме профилей, файл может
+содержать наборы профилей. При экспорте в раскладке, отличной от US,
+к профилям добавляются подписи кнопок с символами этой раскладки.
+Локальные метаданные профиля (описание, теги, подписи кнопок)
+сохраняются в поле `meta`.
+*/
+
+use std::{fmt, fs, path::Path};
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  data::{
+    banks::Bank,
+    code::{code_to_name, name_to_code},
+    layout::KeyboardLayout,
+    library::Library,
+    meta::ProfileMeta,
+    profiles::{KEYPAD_BUTTONS, Profile},
+  },
+  hardware::serial::stick::Stick,
+};
+
+/// Разделитель клавиш в комбинации
+const KEY_SEPARATOR: char = '+';
+
+/// Формат файла профилей
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ProfileFormat {
+  #[default]
+  Ron,
+  Json,
+  Toml,
+}
+
+impl ProfileFormat {
+  /// Все поддерживаемые форматы
+  pub const ALL: [Self; 3] = [Self::Ron, Self::Json, Self::Toml];
+
+  /// Возвращает расширение файла формата
+  pub fn extension(&self) -> &'static str {
+    match self {
+      Self::Ron => "ron",
+      Self::Json => "json",
+      Self::Toml => "toml",
+    }
+  }
+
+  /**
+  Определяет формат по расширению файла
+
+  # Аргументы
+  * `path` - Путь к файлу
+  */
+  pub fn from_path(path: &Path) -> Option<Self> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    Self::ALL
+      .into_iter()
+      .find(|format| format.extension() == ext)
+  }
+}
+
+impl fmt::Display for ProfileFormat {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", self.extension().to_uppercase())
+  }
+}
+
+/// Профиль в текстовом формате
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReadableProfile {
+  /// Имя профиля
+  pub name: String,
+
+  /// Комбинации кнопок 1..=16, например `"LCtrl+LShift+Z"`; пустая строка — кнопка не назначена
+  pub buttons: Vec<String>,
+
+  /// Настройки стика
+  pub stick: ReadableStick,
+
+  /**
+  Подписи кнопок 1..=16 в раскладке `ReadableFile::layout`, например `"LCtrl+; (ж)"`
+
+  Только для чтения человеком, при импорте не используются.
+  */
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub labels: Vec<String>,
+
+  /// Локальные метаданные профиля
+  #[serde(default, skip_serializing_if = "ProfileMeta::is_empty")]
+  pub meta: ProfileMeta,
+}
+
+/// Настройки стика в текстовом формате
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReadableStick {
+  /// Клавиша направления вверх
+  pub up: String,
+
+  /// Клавиша направления вправо
+  pub right: String,
+
+  /// Клавиша направления вниз
+  pub down: String,
+
+  /// Клавиша направления влево
+  pub left: String,
+
+  /// Мёртвая зона в процентах
+  pub deadzone: u8,
+}
+
+/// Набор профилей в текстовом формате
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReadableBank {
+  /// Имя набора
+  pub name: String,
+
+  /// Имена профилей ячеек 1..=4; пустая строка — ячейка не задана
+  pub profiles: Vec<String>,
+}
+
+/// Файл с профилями и наборами в текстовом формате
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ReadableFile {
+  /// Раскладка подписей кнопок; отсутствует для US-раскладки
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub layout: Option<KeyboardLayout>,
+
+  /// Профили файла
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub profiles: Vec<ReadableProfile>,
+
+  /// Наборы профилей
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub banks: Vec<ReadableBank>,
+}
+
+/**
+Выбор данных для экспорта в файл
+
+Элементы `profiles` и `banks` соответствуют профилям и наборам библиотеки,
+элементы `slots` — профилям ОЗУ кейпада 1..=4.
+*/
+#[derive(Debug, Clone, Default)]
+pub struct ExportSelection {
+  /// Экспортируемые профили библиотеки
+  pub profiles: Vec<bool>,
+
+  /// Экспортируемые наборы
+  pub banks: Vec<bool>,
+
+  /// Экспортируемые профили кейпада
+  pub slots: [bool; 4],
+
+  /// Формат файла
+  pub format: ProfileFormat,
+}
+
+impl ExportSelection {
+  /**
+  Создает пустой выбор для библиотеки заданного размера
+
+  # Аргументы
+  * `profiles` - Количество профилей библиотеки
+  * `banks` - Количество наборов
+  */
+  pub fn new(profiles: usize, banks: usize) -> Self {
+    Self {
+      profiles: vec![false; profiles],
+      banks: vec![false; banks],
+      ..Default::default()
+    }
+  }
+
+  /// Проверяет, выбран ли хотя бы один элемент
+  pub fn is_empty(&self) -> bool {
+    !(self.profiles.iter().any(|&p| p)
+      || self.banks.iter().any(|&b| b)
+      || self.slots.iter().any(|&s| s))
+  }
+}
+
+impl From<&Profile> for ReadableProfile {
+  fn from(profile: &Profile) -> Self {
+    let [up, right, down, left] = profile.stick.word.map(key_to_string);
+
+    Self {
+      name: profile.name.trim_end_matches('\0').to_string(),
+      buttons: profile.buttons.iter().map(combination_to_string).collect(),
+      stick: ReadableStick {
+        up,
+        right,
+        down,
+        left,
+        deadzone: profile.stick.deadzone,
+      },
+      labels: Vec::new(),
+      meta: profile.meta.clone(),
+    }
+  }
+}
+
+impl ReadableProfile {
+  /**
+  Создает профиль в текстовом формате с подписями кнопок в раскладке
+
+  Для US-раскладки подписи не добавляются: они совпадают с `buttons`.
+
+  # Аргументы
+  * `profile` - Исходный профиль
+  * `layout` - Раскладка подписей
+  */
+  pub fn with_labels(profile: &Profile, layout: KeyboardLayout) -> Self {
+    let mut readable = Self::from(profile);
+    if layout != KeyboardLayout::Us {
+      readable.labels = profile
+        .buttons
+        .iter()
+        .map(|codes| {
+          codes
+            .iter()
+            .filter(|&&code| code != 0)
+            .map(|&code| layout.label(code))
+            .collect::<Vec<_>>()
+            .join(&KEY_SEPARATOR.to_string())
+        })
+        .collect();
+    }
+    readable
+  }
+}
+
+impl TryFrom<&ReadableProfile> for Profile {
+  type Error = anyhow::Error;
+
+  fn try_from(readable: &ReadableProfile) -> Result<Self> {
+    if readable.buttons.len() != KEYPAD_BUTTONS as usize {
+      bail!(
+        "профиль «{}»: ожидается {KEYPAD_BUTTONS} кнопок, указано {}",
+        readable.name,
+        readable.buttons.len()
+      );
+    }
+
+    let mut buttons = [[0; 6]; 16];
+    for (i, combination) in readable.buttons.iter().enumerate() {
+      buttons[i] = parse_combination(combination)
+        .with_context(|| format!("профиль «{}», кнопка {}", readable.name, i + 1))?;
+    }
+
+    let stick = &readable.stick;
+    let mut word = [0; 4];
+    for (i, key) in [&stick.up, &stick.right, &stick.down, &stick.left]
+      .into_iter()
+      .enumerate()
+    {
+      word[i] = parse_key(key).with_context(|| format!("профиль «{}», стик", readable.name))?;
+    }
+
+    Ok(Self {
+      name: readable.name.clone(),
+      buttons,
+      stick: Stick {
+        word,
+        deadzone: stick.deadzone,
+      },
+      meta: readable.meta.clone(),
+    })
+  }
+}
+
+/**
+Записывает комбинацию кнопки в текстовом виде
+
+Завершающие пустые позиции опускаются, пустые позиции внутри комбинации
+записываются как `0x00`.
+
+# Аргументы
+* `codes` - Коды клавиш кнопки
+*/
+pub fn combination_to_string(codes: &[u8; 6]) -> String {
+  let len = codes
+    .iter()
+    .rposition(|&code| code != 0)
+    .map_or(0, |i| i + 1);
+
+  codes[..len]
+    .iter()
+    .map(|&code| code_to_name(code))
+    .collect::<Vec<_>>()
+    .join(&KEY_SEPARATOR.to_string())
+}
+
+/**
+Разбирает комбинацию кнопки из текстового вида
+
+# Аргументы
+* `text` - Комбинация, например `"LCtrl+C"`
+
+# Возвращает
+Коды клавиш кнопки или ошибку при неизвестном или пустом названии
+или более чем 6 клавишах
+*/
+pub fn parse_combination(text: &str) -> Result<[u8; 6]> {
+  let mut codes = [0; 6];
+  if text.trim().is_empty() {
+    return Ok(codes);
+  }
+
+  let keys = text.split(KEY_SEPARATOR).collect::<Vec<_>>();
+  if keys.len() > codes.len() {
+    bail!("в комбинации «{text}» больше {} клавиш", codes.len());
+  }
+
+  for (i, key) in keys.into_iter().enumerate() {
+    // Пустая часть («Ctrl+», «+A») означает пропущенную клавишу, а не пустую позицию
+    if key.trim().is_empty() {
+      bail!("в комбинации «{text}» пропущено название клавиши");
+    }
+    codes[i] = parse_key(key)?;
+  }
+
+  Ok(codes)
+}
+
+/// Записывает одну клавишу; пустая строка — клавиша не назначена
+fn key_to_string(code: u8) -> String {
+  match code {
+    0 => String::new(),
+    code => code_to_name(code),
+  }
+}
+
+/// Разбирает одну клавишу; пустая строка — клавиша не назначена
+pub fn parse_key(key: &str) -> Result<u8> {
+  let key = key.trim();
+  match key.is_empty() {
+    true => Ok(0),
+    false => name_to_code(key).with_context(|| format!("неизвестная клавиша «{key}»")),
+  }
+}
+
+/**
+Сериализует профили и наборы в текстовый формат
+
+# Аргументы
+* `profiles` - Профили для записи
+* `banks` - Наборы для записи
+* `format` - Формат файла
+* `layout` - Раскладка для подписей кнопок
+*/
+pub fn to_string(
+  profiles: &[Profile],
+  banks: &[Bank],
+  format: ProfileFormat,
+  layout: KeyboardLayout,
+) -> Result<String> {
+  let readable = |profile| ReadableProfile::with_labels(profile, layout);
+  let file = ReadableFile {
+    layout: (layout != KeyboardLayout::Us).then_some(layout),
+    profiles: profiles.iter().map(readable).collect(),
+    banks: banks
+      .iter()
+      .map(|bank| ReadableBank {
+        name: bank.name.clone(),
+        profiles: bank
+          .profiles
+          .iter()
+          .map(|name| name.clone().unwrap_or_default())
+          .collect(),
+      })
+      .collect(),
+  };
+
+  Ok(match format {
+    ProfileFormat::Ron => ron::ser::to_string_pretty(&file, ron::ser::PrettyConfig::default())?,
+    ProfileFormat::Json => serde_json::to_string_pretty(&file)?,
+    ProfileFormat::Toml => toml::to_string_pretty(&file)?,
+  })
+}
+
+/**
+Разбирает профили и наборы из текстового формата
+
+# Аргументы
+* `text` - Содержимое файла
+* `format` - Формат файла
+
+# Возвращает
+Библиотеку с профилями и наборами файла
+*/
+pub fn from_str(text: &str, format: ProfileFormat) -> Result<Library> {
+  let file: ReadableFile = match format {
+    ProfileFormat::Ron => ron::from_str(text)?,
+    ProfileFormat::Json => serde_json::from_str(text)?,
+    ProfileFormat::Toml => toml::from_str(text)?,
+  };
+
+  let profiles = file
+    .profiles
+    .iter()
+    .map(Profile::try_from)
+    .collect::<Result<Vec<_>>>()?;
+
+  let banks = file
+    .banks
+    .iter()
+    .map(|bank| {
+      let profiles = bank
+        .profiles
+        .iter()
+        .map(|name| (!name.is_empty()).then(|| name.clone()))
+        .collect::<Vec<_>>()
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("набор «{}»: ожидается 4 профиля", bank.name))?;
+
+      Ok(Bank {
+        name: bank.name.clone(),
+        profiles,
+      })
+    })
+    .collect::<Result<Vec<_>>>()?;
+
+  Ok(Library::new(profiles, banks))
+}
+
+/**
+Загружает профили и наборы из файла для импорта
+
+Формат определяется по расширению. Файлы RON могут быть как в текстовом
+формате, так и библиотекой профилей любой поддерживаемой версии.
+
+# Аргументы
+* `path` - Путь к файлу
+*/
+pub fn import_file(path: &Path) -> Result<Library> {
+  let text = fs::read_to_string(path)?;
+  let format = ProfileFormat::from_path(path).unwrap_or_default();
+
+  match (format, Library::parse(&text)) {
+    (ProfileFormat::Ron, Ok(library)) => Ok(library),
+    _ => from_str(&text, format),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn sample() -> Vec<Profile> {
+    let mut profile = Profile {
+      name: "Редактор".to_string(),
+      ..Default::default()
+    };
+    profile.buttons[0] = [0x80, b'c', 0, 0, 0, 0];
+    profile.buttons[1] = [0x80, 0x81, b'z', 0, 0, 0];
+    profile.buttons[2] = [0x80, 0, b'+', 0, 0, 0x84];
+    profile.buttons[3] = [16, 27, b'C', 0xE1, 0xFF, b' '];
+    profile.buttons[4] = [b'\\', b'"', b'\'', 0x01, 0xF0, 0xFB];
+    profile.stick.word = [0xDA, 0xD7, 0, b'w'];
+    profile.stick.deadzone = 35;
+    profile.meta.description = "Правка текста".to_string();
+    profile.meta.created = Some(1_709_210_096);
+    profile
+      .meta
+      .update_button(2, |meta| meta.caption = "Отмена".to_string());
+
+    vec![profile, Profile::default()]
+  }
+
+  #[test]
+  fn buttons_use_key_names() {
+    let readable = ReadableProfile::from(&sample()[0]);
+
+    assert_eq!(readable.buttons[0], "LCtrl+C");
+    assert_eq!(readable.buttons[1], "LCtrl+LShift+Z");
+    assert_eq!(readable.buttons[2], "LCtrl+0x00+Plus+0x00+0x00+RCtrl");
+    assert_eq!(
+      readable.buttons[3],
+      "LegacyDel+LegacyEsc+0x43+Num1+0xFF+Space"
+    );
+    assert_eq!(readable.buttons[4], "\\+\"+'+0x01+F13+F24");
+    assert_eq!(readable.buttons[15], "");
+    assert_eq!(readable.stick.up, "Up");
+    assert_eq!(readable.stick.down, "");
+    assert_eq!(readable.stick.left, "W");
+  }
+
+  #[test]
+  fn every_code_round_trips() {
+    for code in 1..=u8::MAX {
+      let codes = [code, 0, 0, 0, 0, 0];
+      let text = combination_to_string(&codes);
+      assert_eq!(
+        parse_combination(&text).unwrap(),
+        codes,
+        "код {code:#04X}: {text}"
+      );
+    }
+  }
+
+  #[test]
+  fn round_trips_in_every_format() {
+    let profiles = sample();
+
+    let banks = vec![Bank {
+      name: "Набор".to_string(),
+      profiles: [Some(0), Some(1), None, Some(1)].map(|i| i.map(|i| profiles[i].name.clone())),
+    }];
+
+    for format in ProfileFormat::ALL {
+      let text = to_string(&profiles, &banks, format, KeyboardLayout::Russian).unwrap();
+      let library = from_str(&text, format).unwrap();
+      assert_eq!(library.profiles, profiles, "{format}");
+      assert_eq!(library.banks, banks, "{format}");
+    }
+  }
+
+  #[test]
+  fn rejects_unknown_keys() {
+    assert!(parse_combination("LCtrl+Nope").is_err());
+    assert!(parse_combination("A+B+C+D+E+F+G").is_err());
+    for text in ["LCtrl+", "LCtrl++", "+A", "A+ +B"] {
+      assert!(parse_combination(text).is_err(), "{text}");
+    }
+  }
+}
@@ -0,0 +1,273 @@
+/*!
+Поиск, фильтрация и сортировка профилей библиотеки.
+
+Запрос сравнивается с именем, описанием, тегами и подписями кнопок
+профиля. Если запрос разбирается как комбинация клавиш (`Ctrl+S`),
+находятся также профили, в которых она назначена какой-либо кнопке;
+левые и правые модификаторы при этом не различаются.
+
+Избранные профили и время последнего открытия хранятся в настройках
+по имени профиля и не меняют саму библиотеку.
+*/
+
+use std::{collections::BTreeMap, fmt};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  data::{code::chord_set, profiles::Profile, readable::parse_combination},
+  utils::time::unix_now,
+};
+
+/// Порядок профилей в списке библиотеки
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum LibrarySort {
+  /// В порядке добавления в библиотеку
+  #[default]
+  Added,
+  /// По имени без учёта регистра
+  Name,
+  /// Сначала недавно открытые
+  LastUsed,
+}
+
+impl LibrarySort {
+  /// Все варианты сортировки
+  pub const ALL: [Self; 3] = [Self::Added, Self::Name, Self::LastUsed];
+}
+
+impl fmt::Display for LibrarySort {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let title = match self {
+      Self::Added => "По порядку",
+      Self::Name => "По имени",
+      Self::LastUsed => "Недавние",
+    };
+    write!(f, "{title}")
+  }
+}
+
+/// Настройки списка профилей библиотеки
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct LibraryView {
+  /// Порядок профилей
+  #[serde(default)]
+  pub sort: LibrarySort,
+
+  /// Имена избранных профилей, закреплённых вверху списка
+  #[serde(default)]
+  pub favorites: Vec<String>,
+
+  /// Время последнего открытия профиля по имени, секунды от начала эпохи Unix
+  #[serde(default)]
+  pub last_used: BTreeMap<String, u64>,
+}
+
+impl LibraryView {
+  /// Проверяет, находится ли профиль в избранном
+  pub fn is_favorite(&self, name: &str) -> bool {
+    self.favorites.iter().any(|favorite| favorite == name)
+  }
+
+  /// Добавляет профиль в избранное или убирает из него
+  pub fn toggle_favorite(&mut self, name: &str) {
+    match self.favorites.iter().position(|favorite| favorite == name) {
+      Some(i) => {
+        self.favorites.remove(i);
+      }
+      None => self.favorites.push(name.to_string()),
+    }
+  }
+
+  /// Запоминает время открытия профиля
+  pub fn touch(&mut self, name: &str) {
+    self.last_used.insert(name.to_string(), unix_now());
+  }
+
+  /**
+  Переносит избранное и время открытия на новое имя профиля
+
+  # Возвращает
+  `true`, если настройки изменились
+  */
+  pub fn rename(&mut self, old: &str, new: &str) -> bool {
+    let mut changed = false;
+    for favorite in self.favorites.iter_mut().filter(|f| *f == old) {
+      *favorite = new.to_string();
+      changed = true;
+    }
+    if let Some(time) = self.last_used.remove(old) {
+      self.last_used.insert(new.to_string(), time);
+      changed = true;
+    }
+    changed
+  }
+
+  /**
+  Забывает профиль, которого больше нет в библиотеке
+
+  # Возвращает
+  `true`, если настройки изменились
+  */
+  pub fn forget(&mut self, name: &str) -> bool {
+    let len = self.favorites.len();
+    self.favorites.retain(|favorite| favorite != name);
+    self.last_used.remove(name).is_some() || self.favorites.len() != len
+  }
+}
+
+/// Профиль, найденный в библиотеке
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hit {
+  /// Индекс профиля в библиотеке
+  pub idx: usize,
+  /// Кнопки 1..=16, которым назначена искомая комбинация
+  pub buttons: Vec<usize>,
+}
+
+/**
+Разбирает запрос как комбинацию клавиш
+
+Одиночный символ комбинацией не считается, чтобы поиск по букве
+не находил все профили с этой клавишей.
+*/
+fn query_chord(query: &str) -> Option<Vec<u8>> {
+  if query.chars().count() < 2 {
+    return None;
+  }
+  let codes = parse_combination(query).ok()?;
+  let keys = chord_set(&codes);
+  (!keys.is_empty()).then_some(keys)
+}
+
+/**
+Ищет профили библиотеки
+
+# Аргументы
+* `profiles` - Профили библиотеки
+* `view` - Порядок сортировки и избранное
+* `query` - Текст запроса: часть имени, описания, тега, подписи или комбинация
+* `tag` - Тег, которым должен быть отмечен профиль
+
+# Возвращает
+Найденные профили: сначала избранные, затем остальные в выбранном порядке
+*/
+pub fn search(
+  profiles: &[Profile],
+  view: &LibraryView,
+  query: &str,
+  tag: Option<&str>,
+) -> Vec<Hit> {
+  let query = query.trim();
+  let text = query.to_lowercase();
+  let tag = tag.map(str::to_lowercase);
+  let chord = query_chord(query);
+
+  let mut hits = profiles
+    .iter()
+    .enumerate()
+    .filter(|(_, profile)| {
+      tag
+        .as_ref()
+        .is_none_or(|tag| profile.meta.tags.iter().any(|t| t.to_lowercase() == *tag))
+    })
+    .filter_map(|(idx, profile)| {
+      let buttons = chord.as_ref().map_or_else(Vec::new, |chord| {
+        (1..=profile.buttons.len())
+          .filter(|&id| chord_set(&profile.buttons[id - 1]) == *chord)
+          .collect()
+      });
+      let meta = &profile.meta;
+      let found = text.is_empty()
+        || !buttons.is_empty()
+        || [&profile.name, &meta.description, &meta.author]
+          .into_iter()
+          .chain(&meta.tags)
+          .chain(meta.buttons.iter().map(|button| &button.caption))
+          .any(|field| field.to_lowercase().contains(&text));
+      found.then_some(Hit { idx, buttons })
+    })
+    .collect::<Vec<_>>();
+
+  let name = |hit: &Hit| profiles[hit.idx].name.to_lowercase();
+  let used = |hit: &Hit| view.last_used.get(&profiles[hit.idx].name).copied();
+  match view.sort {
+    LibrarySort::Added => {}
+    LibrarySort::Name => hits.sort_by_cached_key(name),
+    LibrarySort::LastUsed => hits.sort_by_key(|hit| std::cmp::Reverse(used(hit))),
+  }
+  hits.sort_by_key(|hit| !view.is_favorite(&profiles[hit.idx].name));
+  hits
+}
+
+/// Возвращает все теги профилей библиотеки по алфавиту без повторов
+pub fn tags(profiles: &[Profile]) -> Vec<String> {
+  let mut tags = profiles
+    .iter()
+    .flat_map(|profile| profile.meta.tags.iter().cloned())
+    .collect::<Vec<_>>();
+  tags.sort_by_key(|tag| tag.to_lowercase());
+  tags.dedup_by(|a, b| a.to_lowercase() == b.to_lowercase());
+  tags
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn library() -> Vec<Profile> {
+    let mut krita = Profile {
+      name: "Krita".to_string(),
+      ..Default::default()
+    };
+    krita.buttons[4] = [0x84, b's', 0, 0, 0, 0];
+    krita.meta.add_tag("рисование");
+
+    let mut blender = Profile {
+      name: "blender".to_string(),
+      ..Default::default()
+    };
+    blender.meta.add_tag("3D");
+
+    vec![krita, blender, Profile::default()]
+  }
+
+  #[test]
+  fn finds_by_name_tag_and_shortcut() {
+    let profiles = library();
+    let view = LibraryView::default();
+    let ids = |hits: Vec<Hit>| hits.into_iter().map(|hit| hit.idx).collect::<Vec<_>>();
+
+    assert_eq!(ids(search(&profiles, &view, "KRI", None)), [0]);
+    assert_eq!(ids(search(&profiles, &view, "3d", None)), [1]);
+    assert_eq!(ids(search(&profiles, &view, "", Some("Рисование"))), [0]);
+
+    let hits = search(&profiles, &view, "Ctrl+S", None);
+    assert_eq!(
+      hits,
+      [Hit {
+        idx: 0,
+        buttons: vec![5]
+      }]
+    );
+  }
+
+  #[test]
+  fn favorites_come_first() {
+    let profiles = library();
+    let mut view = LibraryView {
+      sort: LibrarySort::Name,
+      ..Default::default()
+    };
+    view.toggle_favorite("Default");
+
+    let hits = search(&profiles, &view, "", None);
+    assert_eq!(
+      hits.iter().map(|hit| hit.idx).collect::<Vec<_>>(),
+      [2, 1, 0]
+    );
+
+    assert!(view.rename("Default", "Основной"));
+    assert!(view.is_favorite("Основной"));
+  }
+}
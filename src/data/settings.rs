@@ -0,0 +1,104 @@
+use std::path::PathBuf;
+
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  assets::APPLICATION_NAME,
+  data::{
+    Config,
+    capture::CaptureSettings,
+    layout::KeyboardLayout,
+    links::SlotLink,
+    lint::LintSettings,
+    name::NameEncoding,
+    search::LibraryView,
+    storage::{load_ron_or_default, store_ron},
+  },
+};
+
+/**
+Место хранения локальной библиотеки профилей
+*/
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub enum StorageBackend {
+  /// Вся библиотека в одном файле `profiles.ron` директории конфигурации
+  #[default]
+  Single,
+
+  /**
+  Каждый профиль — отдельный файл в выбранной пользователем директории
+
+  Подходит для общих папок и систем контроля версий: изменения
+  разных профилей не конфликтуют между собой. Наборы профилей
+  по-прежнему хранятся в `profiles.ron`.
+  */
+  Directory(PathBuf),
+}
+
+/**
+Настройки приложения
+Содержит параметры, выбранные пользователем на экране настроек
+*/
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Settings {
+  /// Место хранения библиотеки профилей
+  #[serde(default)]
+  pub storage: StorageBackend,
+
+  /// Захват комбинаций с клавиатуры компьютера
+  #[serde(default)]
+  pub capture: CaptureSettings,
+
+  /// Проверка профилей
+  #[serde(default)]
+  pub lint: LintSettings,
+
+  /// Способ кодирования имён профилей при записи на устройство
+  #[serde(default)]
+  pub name_encoding: NameEncoding,
+
+  /// Раскладка клавиатуры для подписей клавиш
+  #[serde(default)]
+  pub layout: KeyboardLayout,
+
+  /// Профили библиотеки, записанные в ячейки кейпада 1..=4
+  #[serde(default)]
+  pub slot_links: [Option<SlotLink>; 4],
+
+  /// Порядок и избранное списка профилей библиотеки
+  #[serde(default)]
+  pub library_view: LibraryView,
+}
+
+impl Settings {
+  /**
+  Загружает настройки из файла конфигурации
+
+  # Возвращает
+  Загруженные настройки или настройки по умолчанию,
+  если файла нет или он повреждён, и путь повреждённого файла после переноса в карантин
+  */
+  pub fn load() -> (Self, Option<PathBuf>) {
+    match confy::get_configuration_file_path(APPLICATION_NAME, "settings") {
+      Ok(path) => load_ron_or_default(&path),
+      Err(err) => {
+        error!("settings: не удалось определить путь конфигурации: {err}");
+        (Self::default(), None)
+      }
+    }
+  }
+}
+
+impl Config for Settings {
+  /// Атомарно сохраняет настройки в файл конфигурации
+  fn save(&self) {
+    let res = confy::get_configuration_file_path(APPLICATION_NAME, "settings")
+      .map_err(anyhow::Error::from)
+      .and_then(|path| store_ron(&path, self));
+
+    if let Err(err) = res {
+      error!("settings: не удалось записать настройки: {err}");
+    }
+  }
+}
@@ -1,10 +1,12 @@
+use serde::{Deserialize, Serialize};
+
 /**
 Параметры калибровки стика, полученные от устройства
 
 Содержит информацию о центре стика и мертвых зонах,
 необходимую для корректной работы аналогового стика.
 */
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct Stick {
   /// Координата X центра стика в единицах АЦП
   pub center_x: u16,
@@ -0,0 +1,255 @@
+/*!
+Надёжное сохранение файлов конфигурации.
+
+Запись выполняется атомарно: данные пишутся во временный файл рядом с целевым,
+сбрасываются на диск и только затем переименовываются поверх целевого файла,
+поэтому прерванная запись не оставляет полузаписанных файлов.
+
+Нечитаемые файлы не удаляются, а переносятся в карантин (переименовываются
+с суффиксом `.corrupt-<время>`), чтобы их можно было восстановить вручную.
+*/
+
+use std::{
+  fs,
+  io::Write,
+  path::{Path, PathBuf},
+  time::{Duration, SystemTime},
+};
+
+use anyhow::Result;
+use log::{error, info, warn};
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::utils::time::unix_now;
+
+/**
+Атомарно записывает содержимое в файл
+
+# Аргументы
+* `path` - Путь к целевому файлу
+* `contents` - Записываемое содержимое
+*/
+pub fn write_atomic(path: &Path, contents: &str) -> Result<()> {
+  if let Some(dir) = path.parent() {
+    fs::create_dir_all(dir)?;
+  }
+
+  let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+  tmp_name.push(".tmp");
+  let tmp_path = path.with_file_name(tmp_name);
+
+  let mut file = fs::File::create(&tmp_path)?;
+  file.write_all(contents.as_bytes())?;
+  file.sync_all()?;
+  drop(file);
+
+  fs::rename(&tmp_path, path)?;
+  Ok(())
+}
+
+/**
+Атомарно сохраняет значение в файл в формате RON
+
+# Аргументы
+* `path` - Путь к целевому файлу
+* `value` - Сохраняемое значение
+*/
+pub fn store_ron<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+  let text = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())?;
+  write_atomic(path, &text)
+}
+
+/**
+Загружает значение из файла RON с переносом нечитаемого файла в карантин
+
+Если файла нет, возвращается значение по умолчанию. Если файл не удаётся
+разобрать, он переносится в карантин и также возвращается значение по умолчанию.
+
+# Аргументы
+* `path` - Путь к файлу
+
+# Возвращает
+Загруженное значение и новый путь файла, если он перенесён в карантин
+*/
+pub fn load_ron_or_default<T: DeserializeOwned + Default>(path: &Path) -> (T, Option<PathBuf>) {
+  let text = match fs::read_to_string(path) {
+    Ok(text) => text,
+    Err(_) => return (T::default(), None),
+  };
+
+  match ron::from_str(&text) {
+    Ok(value) => (value, None),
+    Err(err) => {
+      warn!("storage: не удалось разобрать {}: {err}", path.display());
+      (T::default(), quarantine(path).ok())
+    }
+  }
+}
+
+/**
+Переносит нечитаемый файл в карантин
+
+# Аргументы
+* `path` - Путь к нечитаемому файлу
+
+# Возвращает
+Новый путь к файлу
+*/
+pub fn quarantine(path: &Path) -> Result<PathBuf> {
+  let mut name = path.file_name().unwrap_or_default().to_os_string();
+  name.push(format!(".corrupt-{}", unix_now()));
+  let new_path = path.with_file_name(name);
+
+  fs::rename(path, &new_path).inspect_err(|err| {
+    error!(
+      "storage: не удалось перенести {} в карантин: {err}",
+      path.display()
+    );
+  })?;
+  warn!("storage: файл перенесён в карантин: {}", new_path.display());
+
+  Ok(new_path)
+}
+
+/**
+Сохраняет предыдущую версию файла в директорию резервных копий
+
+Копия создаётся не чаще `interval`, чтобы частые сохранения (например,
+при вводе имени профиля) не вытесняли более старые версии. В директории
+хранится не более `limit` копий, самые старые удаляются.
+
+# Аргументы
+* `path` - Путь к сохраняемому файлу
+* `dir` - Директория резервных копий
+* `limit` - Количество хранимых копий
+* `interval` - Минимальный интервал между копиями
+*/
+pub fn rotate_backup(path: &Path, dir: &Path, limit: usize, interval: Duration) -> Result<()> {
+  if !path.exists() {
+    return Ok(());
+  }
+
+  let backups = list_backups(dir);
+  let is_recent = backups
+    .first()
+    .and_then(|newest| fs::metadata(newest).ok()?.modified().ok())
+    .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+    .is_some_and(|age| age < interval);
+  if is_recent {
+    return Ok(());
+  }
+
+  fs::create_dir_all(dir)?;
+  let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+  let backup = dir.join(format!("{stem}-{}.ron", unix_now()));
+  fs::copy(path, &backup)?;
+  info!("storage: резервная копия {}", backup.display());
+
+  for old in list_backups(dir).iter().skip(limit) {
+    if let Err(err) = fs::remove_file(old) {
+      warn!("storage: не удалось удалить {}: {err}", old.display());
+    }
+  }
+
+  Ok(())
+}
+
+/**
+Возвращает резервные копии из директории, начиная с самой новой
+
+# Аргументы
+* `dir` - Директория резервных копий
+*/
+pub fn list_backups(dir: &Path) -> Vec<PathBuf> {
+  let Ok(entries) = fs::read_dir(dir) else {
+    return Vec::new();
+  };
+
+  let mut backups = entries
+    .filter_map(|entry| entry.ok().map(|e| e.path()))
+    .filter(|path| path.extension().is_some_and(|ext| ext == "ron"))
+    .filter_map(|path| Some((fs::metadata(&path).ok()?.modified().ok()?, path)))
+    .collect::<Vec<_>>();
+
+  backups.sort_by(|a, b| b.cmp(a));
+  backups.into_iter().map(|(_, path)| path).collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("claws-storage-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+  }
+
+  #[test]
+  fn writes_atomically() {
+    let dir = test_dir("atomic");
+    let path = dir.join("nested").join("settings.ron");
+
+    write_atomic(&path, "(a: 1)").unwrap();
+    write_atomic(&path, "(a: 2)").unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "(a: 2)");
+    assert!(!path.with_file_name("settings.ron.tmp").exists());
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn quarantines_unreadable_file() {
+    let dir = test_dir("quarantine");
+    let path = dir.join("window.ron");
+    write_atomic(&path, "(width: ").unwrap();
+
+    let (value, quarantined): (Vec<u32>, _) = load_ron_or_default(&path);
+    assert!(value.is_empty());
+    assert!(!path.exists());
+    assert!(quarantined.is_some_and(|moved| moved.exists()));
+
+    let moved = fs::read_dir(&dir)
+      .unwrap()
+      .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+      .collect::<Vec<_>>();
+    assert_eq!(moved.len(), 1);
+    assert!(moved[0].starts_with("window.ron.corrupt-"));
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn rotates_backups() {
+    let dir = test_dir("rotate");
+    let backups = dir.join("backups");
+    let path = dir.join("profiles.ron");
+
+    rotate_backup(&path, &backups, 2, Duration::ZERO).unwrap();
+    assert!(list_backups(&backups).is_empty());
+
+    write_atomic(&path, "[]").unwrap();
+    rotate_backup(&path, &backups, 2, Duration::ZERO).unwrap();
+    assert_eq!(list_backups(&backups).len(), 1);
+
+    // Копия не чаще интервала
+    rotate_backup(&path, &backups, 2, Duration::from_secs(3600)).unwrap();
+    assert_eq!(list_backups(&backups).len(), 1);
+
+    // Старые копии сверх лимита удаляются
+    for (i, name) in ["profiles-1.ron", "profiles-2.ron"].iter().enumerate() {
+      let old = backups.join(name);
+      fs::write(&old, "[]").unwrap();
+      let time = SystemTime::now() - Duration::from_secs(3600 * (i as u64 + 1));
+      fs::File::options()
+        .write(true)
+        .open(&old)
+        .unwrap()
+        .set_modified(time)
+        .unwrap();
+    }
+    rotate_backup(&path, &backups, 2, Duration::ZERO).unwrap();
+    let left = list_backups(&backups);
+    assert_eq!(left.len(), 2);
+    assert!(!left.contains(&backups.join("profiles-2.ron")));
+    fs::remove_dir_all(&dir).unwrap();
+  }
+}
@@ -0,0 +1,349 @@
+/*!
+Встроенные и пользовательские шаблоны профилей.
+
+Шаблон — файл RON с назначениями кнопок для популярного приложения
+или с раскладкой стика. Кнопки и клавиши стика записываются названиями
+клавиш, как в текстовом формате профилей (`"Ctrl+Shift+Z"`), а подписи
+кнопок попадают в метаданные профиля.
+
+Встроенные шаблоны лежат в `assets/templates` и встраиваются в программу.
+Свои шаблоны того же формата можно положить в директорию `templates`
+конфигурации приложения. Каждый файл содержит номер версии формата,
+файлы более новых версий пропускаются.
+
+История версий формата:
+1. Поля `name`, `title`, `kind`, `description`, `tags`, `buttons` и `stick`
+*/
+
+use std::{fmt, fs, path::PathBuf};
+
+use anyhow::{Result, bail};
+use log::warn;
+use serde::Deserialize;
+
+use crate::data::{
+  config_dir,
+  profiles::{KEYPAD_BUTTONS, Profile},
+  readable::{parse_combination, parse_key},
+};
+
+/// Текущая версия формата шаблонов
+pub const TEMPLATE_VERSION: u32 = 1;
+
+/// Встроенные шаблоны: имя файла и содержимое
+const BUILTIN: [(&str, &str); 12] = [
+  ("gimp.ron", include_str!("../../assets/templates/gimp.ron")),
+  (
+    "krita.ron",
+    include_str!("../../assets/templates/krita.ron"),
+  ),
+  (
+    "blender.ron",
+    include_str!("../../assets/templates/blender.ron"),
+  ),
+  (
+    "inkscape.ron",
+    include_str!("../../assets/templates/inkscape.ron"),
+  ),
+  ("obs.ron", include_str!("../../assets/templates/obs.ron")),
+  (
+    "kicad.ron",
+    include_str!("../../assets/templates/kicad.ron"),
+  ),
+  (
+    "freecad.ron",
+    include_str!("../../assets/templates/freecad.ron"),
+  ),
+  (
+    "libreoffice.ron",
+    include_str!("../../assets/templates/libreoffice.ron"),
+  ),
+  (
+    "media.ron",
+    include_str!("../../assets/templates/media.ron"),
+  ),
+  (
+    "stick-arrows.ron",
+    include_str!("../../assets/templates/stick-arrows.ron"),
+  ),
+  (
+    "stick-wasd.ron",
+    include_str!("../../assets/templates/stick-wasd.ron"),
+  ),
+  (
+    "stick-pages.ron",
+    include_str!("../../assets/templates/stick-pages.ron"),
+  ),
+];
+
+/// Вид шаблона
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+pub enum TemplateKind {
+  /// Назначения для приложения
+  Application,
+  /// Медиа, прокрутка и навигация
+  Navigation,
+  /// Раскладка стика без кнопок
+  Stick,
+}
+
+impl TemplateKind {
+  /// Все виды шаблонов в порядке отображения
+  pub const ALL: [Self; 3] = [Self::Application, Self::Navigation, Self::Stick];
+}
+
+impl fmt::Display for TemplateKind {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let title = match self {
+      Self::Application => "Приложения",
+      Self::Navigation => "Медиа и навигация",
+      Self::Stick => "Стик",
+    };
+    write!(f, "{title}")
+  }
+}
+
+/// Кнопка шаблона
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct TemplateButton {
+  /// Комбинация клавиш, например `"Ctrl+Z"`; пустая строка — кнопка не назначена
+  pub keys: String,
+  /// Подпись кнопки
+  #[serde(default)]
+  pub caption: String,
+}
+
+/// Раскладка стика шаблона
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct TemplateStick {
+  /// Клавиша направления вверх
+  pub up: String,
+  /// Клавиша направления вправо
+  pub right: String,
+  /// Клавиша направления вниз
+  pub down: String,
+  /// Клавиша направления влево
+  pub left: String,
+  /// Мёртвая зона в процентах; если не указана, не меняется
+  #[serde(default)]
+  pub deadzone: Option<u8>,
+}
+
+/// Шаблон профиля
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct Template {
+  /// Версия формата, в которой записан шаблон
+  pub version: u32,
+
+  /// Имя создаваемого профиля
+  pub name: String,
+
+  /// Название шаблона в галерее
+  pub title: String,
+
+  /// Вид шаблона
+  pub kind: TemplateKind,
+
+  /// Описание шаблона
+  #[serde(default)]
+  pub description: String,
+
+  /// Теги создаваемого профиля
+  #[serde(default)]
+  pub tags: Vec<String>,
+
+  /// Кнопки 1..=16; пустой список — кнопки профиля не меняются
+  #[serde(default)]
+  pub buttons: Vec<TemplateButton>,
+
+  /// Раскладка стика; `None` — стик не меняется
+  #[serde(default)]
+  pub stick: Option<TemplateStick>,
+
+  /// Файл пользовательского шаблона; `None` для встроенных
+  #[serde(skip)]
+  pub path: Option<PathBuf>,
+}
+
+impl Template {
+  /**
+  Разбирает и проверяет шаблон
+
+  # Аргументы
+  * `text` - Содержимое файла шаблона
+
+  # Возвращает
+  Шаблон или ошибку, если файл повреждён, создан более новой версией
+  приложения или содержит неизвестные клавиши
+  */
+  pub fn parse(text: &str) -> Result<Self> {
+    // Необязательные поля записываются без `Some(...)`
+    let template: Self = ron::Options::default()
+      .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+      .from_str(text)?;
+
+    if template.version == 0 || template.version > TEMPLATE_VERSION {
+      bail!("неподдерживаемая версия шаблона: {}", template.version);
+    }
+    if template.buttons.len() > KEYPAD_BUTTONS as usize {
+      bail!(
+        "шаблон «{}»: больше {KEYPAD_BUTTONS} кнопок",
+        template.title
+      );
+    }
+    template.apply(&mut Profile::default())?;
+
+    Ok(template)
+  }
+
+  /**
+  Применяет шаблон к профилю
+
+  Шаблон с кнопками заменяет имя, все кнопки и их подписи, добавляет
+  описание и теги. Шаблон со стиком меняет только направления стика
+  и, если указана, мёртвую зону.
+
+  # Аргументы
+  * `profile` - Изменяемый профиль
+
+  # Возвращает
+  Ошибку, если шаблон содержит неизвестные клавиши; профиль в этом случае не меняется
+  */
+  pub fn apply(&self, profile: &mut Profile) -> Result<()> {
+    let mut result = profile.clone();
+
+    if !self.buttons.is_empty() {
+      result.name = self.name.clone();
+      result.buttons = [[0; 6]; 16];
+      result.meta.buttons.clear();
+      for (i, button) in self.buttons.iter().enumerate() {
+        result.buttons[i] = parse_combination(&button.keys)?;
+        result
+          .meta
+          .update_button(i + 1, |meta| meta.caption = button.caption.clone());
+      }
+      if result.meta.description.is_empty() {
+        result.meta.description = self.description.clone();
+      }
+    }
+
+    if let Some(stick) = &self.stick {
+      for (i, key) in [&stick.up, &stick.right, &stick.down, &stick.left]
+        .into_iter()
+        .enumerate()
+      {
+        result.stick.word[i] = parse_key(key)?;
+      }
+      if let Some(deadzone) = stick.deadzone {
+        result.stick.deadzone = deadzone.clamp(1, 100);
+      }
+    }
+
+    for tag in &self.tags {
+      result.meta.add_tag(tag);
+    }
+
+    *profile = result;
+    Ok(())
+  }
+
+  /// Создает новый профиль по шаблону
+  pub fn new_profile(&self) -> Result<Profile> {
+    let mut profile = Profile {
+      name: self.name.clone(),
+      ..Default::default()
+    };
+    self.apply(&mut profile)?;
+    profile.meta.mark_created();
+    Ok(profile)
+  }
+}
+
+/// Возвращает директорию пользовательских шаблонов
+pub fn user_dir() -> PathBuf {
+  config_dir().join("templates")
+}
+
+/**
+Загружает встроенные и пользовательские шаблоны
+
+Повреждённые файлы и файлы более новых версий пропускаются.
+
+# Возвращает
+Шаблоны и описания ошибок пропущенных файлов
+*/
+pub fn load() -> (Vec<Template>, Vec<String>) {
+  let mut paths = fs::read_dir(user_dir())
+    .map(|dir| {
+      dir
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ron"))
+        .collect::<Vec<_>>()
+    })
+    .unwrap_or_default();
+  paths.sort();
+
+  let builtin = BUILTIN
+    .into_iter()
+    .map(|(file, text)| (file.to_string(), Ok(text.to_string()), None));
+  let user = paths.into_iter().map(|path| {
+    let text = fs::read_to_string(&path).map_err(anyhow::Error::from);
+    (path.display().to_string(), text, Some(path))
+  });
+
+  let mut templates = Vec::new();
+  let mut errors = Vec::new();
+  for (file, text, path) in builtin.chain(user) {
+    match text.and_then(|text| Template::parse(&text)) {
+      Ok(template) => templates.push(Template { path, ..template }),
+      Err(err) => {
+        warn!("templates: шаблон {file} пропущен: {err:#}");
+        errors.push(format!("{file}: {err:#}"));
+      }
+    }
+  }
+
+  (templates, errors)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn builtin_templates_parse() {
+    for (file, text) in BUILTIN {
+      let template = Template::parse(text).unwrap_or_else(|err| panic!("{file}: {err:#}"));
+      assert!(
+        template.kind == TemplateKind::Stick || template.buttons.len() == 16,
+        "{file}"
+      );
+    }
+  }
+
+  #[test]
+  fn stick_template_keeps_buttons() {
+    let (_, wasd) = BUILTIN
+      .into_iter()
+      .find(|(file, _)| *file == "stick-wasd.ron")
+      .unwrap();
+    let template = Template::parse(wasd).unwrap();
+
+    let mut profile = Profile::default();
+    profile.buttons[0] = [0x80, b'c', 0, 0, 0, 0];
+    template.apply(&mut profile).unwrap();
+
+    assert_eq!(profile.name, "Default");
+    assert_eq!(profile.buttons[0], [0x80, b'c', 0, 0, 0, 0]);
+    assert_eq!(profile.stick.word, [b'w', b'd', b's', b'a']);
+    assert_eq!(profile.stick.deadzone, 50);
+  }
+
+  #[test]
+  fn rejects_newer_version() {
+    let text = r#"(version: 99, name: "X", title: "X", kind: Stick)"#;
+
+    assert!(Template::parse(text).is_err());
+  }
+}
@@ -1,8 +1,14 @@
+use std::path::PathBuf;
+
+use log::error;
 use serde::{Deserialize, Serialize};
 
 use crate::{
   assets::APPLICATION_NAME,
-  data::Config,
+  data::{
+    Config,
+    storage::{load_ron_or_default, store_ron},
+  },
   ui::styles::{WINDOW_HEIGH, WINDOW_WIDTH},
 };
 
@@ -46,23 +52,28 @@ impl Window {
   Загружает конфигурацию окна из файла конфигурации
 
   # Возвращает
-  Загруженную конфигурацию окна или паникует при ошибке чтения
-
-  # Паникует
-  Если не удается загрузить конфигурацию из файла
+  Загруженную конфигурацию окна или конфигурацию по умолчанию,
+  если файла нет или он повреждён, и путь повреждённого файла после переноса в карантин
   */
-  pub fn load() -> Self {
-    confy::load(APPLICATION_NAME, "window").expect("Не удалось загрузить конфигурацию окна")
+  pub fn load() -> (Self, Option<PathBuf>) {
+    match confy::get_configuration_file_path(APPLICATION_NAME, "window") {
+      Ok(path) => load_ron_or_default(&path),
+      Err(err) => {
+        error!("window: не удалось определить путь конфигурации: {err}");
+        (Self::default(), None)
+      }
+    }
   }
 }
 impl Config for Window {
-  /**
-  Сохраняет текущую конфигурацию окна в файл конфигурации
-
-  # Паникует
-  Если не удается записать конфигурацию в файл
-  */
+  /// Атомарно сохраняет текущую конфигурацию окна в файл конфигурации
   fn save(&self) {
-    confy::store(APPLICATION_NAME, "window", self).expect("Не удалось записать конфигурацию окна")
+    let res = confy::get_configuration_file_path(APPLICATION_NAME, "window")
+      .map_err(anyhow::Error::from)
+      .and_then(|path| store_ron(&path, self));
+
+    if let Err(err) = res {
+      error!("window: не удалось записать конфигурацию окна: {err}");
+    }
   }
 }
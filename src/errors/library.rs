@@ -0,0 +1,18 @@
+//! Ошибки загрузки локальной библиотеки профилей и поиска профилей в ней.
+
+/// Ошибки чтения библиотеки, при которых файл не переносится в карантин
+#[derive(Debug, thiserror::Error)]
+pub enum LibraryError {
+  #[error("библиотека профилей сохранена более новой версией приложения (версия схемы {0})")]
+  NewerVersion(u32),
+}
+
+/// Ошибки поиска профиля библиотеки по имени
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ProfileRefError {
+  #[error("«{0}» нет в библиотеке")]
+  Missing(String),
+
+  #[error("в библиотеке несколько профилей «{0}»")]
+  Ambiguous(String),
+}
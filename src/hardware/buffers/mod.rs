@@ -3,7 +3,7 @@
 use std::collections::VecDeque;
 use std::sync::{Arc, Mutex, MutexGuard};
 
-use crate::hardware::commands::Value;
+use crate::{data::name::NameEncoding, hardware::commands::Value};
 
 /// Очередь исходящих пакетов к устройству
 #[derive(Debug, Clone, Default)]
@@ -21,9 +21,34 @@ pub struct Receive {
 pub struct Buffers {
   send: Arc<Mutex<Send>>,
   receive: Arc<Mutex<Receive>>,
+  /// Способ кодирования имени профиля при записи на устройство
+  name_encoding: NameEncoding,
 }
 
 impl Buffers {
+  /**
+  Создает пустые буферы
+
+  # Аргументы
+  * `name_encoding` - Способ кодирования имени профиля при записи на устройство
+  */
+  pub fn new(name_encoding: NameEncoding) -> Self {
+    Self {
+      name_encoding,
+      ..Default::default()
+    }
+  }
+
+  /// Возвращает способ кодирования имени профиля при записи на устройство
+  pub fn name_encoding(&self) -> NameEncoding {
+    self.name_encoding
+  }
+
+  /// Задает способ кодирования имени профиля при записи на устройство
+  pub fn set_name_encoding(&mut self, encoding: NameEncoding) {
+    self.name_encoding = encoding;
+  }
+
   /// Возвращает MutexGuard на очередь отправки
   pub fn send(&self) -> MutexGuard<'_, Send> {
     self.send.lock().unwrap()
@@ -46,6 +46,16 @@ pub enum Command {
   */
   SetParameters(u8, u8),
 
+  /**
+  Запрос: 0x73, 0x3, 0x4, (Код параметра), (2 байта значения параметра), 0x65
+
+  Ответ: нет ответа
+
+  Двухбайтовая форма `SetParameters` для параметров калибровки 1-3.
+  Используется только при восстановлении калибровки из резервной копии.
+  */
+  SetCalibrationParameter(u8, u16),
+
   /**
   Запрос: 0x73, 0x3, 0x5, (код положения стика), (ascii код), 0x65
 
@@ -88,6 +98,9 @@ impl Value for Command {
       Self::RequestPositionXY => vec![1],
       Self::RequestPositionASCII => vec![3],
       Self::SetParameters(num, value) => vec![4, *num, *value],
+      Self::SetCalibrationParameter(num, value) => {
+        vec![4, *num, (*value >> 8) as u8, *value as u8]
+      }
       Self::SetPositionASCII(position, ascii_code) => vec![5, *position, *ascii_code],
       Self::Calibration(option) => vec![6, option.get()],
     }
@@ -0,0 +1,100 @@
+use anyhow::Result;
+use log::info;
+
+use crate::{
+  data::{
+    backup::{Backup, RestoreSelection},
+    device::Device,
+    stick::Stick,
+  },
+  hardware::{
+    buffers::Buffers,
+    commands::{device, profile, stick},
+    serial::{Keypad, profile::profile_rom_request},
+  },
+};
+
+/**
+Снимает полную резервную копию кейпада
+
+Запрашивает информацию об устройстве, параметры калибровки стика
+и безопасно читает профили ОЗУ и ПЗУ через `profile_rom_request`.
+
+# Аргументы
+* `buffers` - Буферы для обмена данными с устройством
+
+# Возвращает
+Резервную копию или ошибку при неудачном чтении
+*/
+pub async fn backup_request(buffers: &mut Buffers) -> Result<Backup> {
+  let device = Device::parse(&device::request_info(buffers)?).await;
+  let stick = Stick::parse(&stick::calibration_request(buffers).await?).await;
+  let (ram, rom, active_profile_id) = profile_rom_request(buffers).await?;
+
+  Ok(Backup {
+    device,
+    ram,
+    rom,
+    active_profile_id,
+    stick,
+    ..Backup::new()
+  })
+}
+
+/**
+Восстанавливает выбранные данные из резервной копии на кейпад
+
+Сначала записываются профили ПЗУ, затем профили ОЗУ и калибровка стика.
+После записи активным становится профиль, активный на момент создания копии.
+
+# Аргументы
+* `buffers` - Буферы для обмена данными с устройством
+* `backup` - Резервная копия
+* `selection` - Выбор восстанавливаемых данных
+*/
+pub fn backup_restore(
+  buffers: &mut Buffers,
+  backup: &Backup,
+  selection: &RestoreSelection,
+) -> Result<()> {
+  for (i, profile) in backup.rom.iter().enumerate() {
+    if selection.rom[i] {
+      Keypad::profile_send(buffers, profile.clone())?;
+      buffers
+        .send()
+        .push(&profile::Command::WriteActiveToFlash(i as u8 + 1));
+      info!("backup_restore: профиль ПЗУ {} восстановлен", i + 1);
+    }
+  }
+
+  for (i, profile) in backup.ram.iter().enumerate() {
+    if selection.ram[i] {
+      Keypad::profile_send(buffers, profile.clone())?;
+      buffers
+        .send()
+        .push(&profile::Command::WriteActiveToRam(i as u8 + 1));
+      info!("backup_restore: профиль ОЗУ {} восстановлен", i + 1);
+    }
+  }
+
+  if selection.calibration {
+    [
+      (1, backup.stick.center_x),
+      (2, backup.stick.center_y),
+      (3, backup.stick.external_deadzone),
+    ]
+    .into_iter()
+    .for_each(|(num, value)| {
+      buffers
+        .send()
+        .push(&stick::Command::SetCalibrationParameter(num, value))
+    });
+    info!("backup_restore: калибровка стика восстановлена");
+  }
+
+  buffers.send().push(&profile::Command::LoadRamToActive(
+    backup.active_profile_id.clamp(1, 4),
+  ));
+
+  Ok(())
+}
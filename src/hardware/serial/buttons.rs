@@ -1,3 +1,5 @@
+use crate::data::code::KeyCategory;
+
 /**
 Состояние редактируемой кнопки/стика в пользовательском интерфейсе
 
@@ -19,3 +21,22 @@ pub struct KeypadButton {
   /// Признак, что редактируется стик (true) или обычная кнопка (false)
   pub is_stick: bool,
 }
+
+/**
+Состояние экранной клавиатуры
+
+Позволяет назначить редактируемой кнопке клавиши, которые нельзя нажать
+на клавиатуре компьютера (перехватываемые системой или оконным менеджером),
+выбором из списка или вводом комбинации текстом.
+*/
+#[derive(Debug, Clone, Default)]
+pub struct KeyPicker {
+  /// Категория отображаемых клавиш
+  pub category: KeyCategory,
+
+  /// Комбинация, введённая текстом, например `Ctrl+Shift+F13`
+  pub text: String,
+
+  /// Ошибка разбора введённой комбинации
+  pub error: Option<String>,
+}
@@ -18,8 +18,10 @@ use crate::{
   utils::{BYTE_END, BYTE_START},
 };
 
+pub mod backup;
 pub mod buttons;
 pub mod profile;
+pub mod slots;
 pub mod stick;
 
 /// Тип-обёртка для потокобезопасного доступа к `SerialPort`
@@ -2,7 +2,10 @@ use anyhow::Result;
 use log::{debug, info};
 
 use crate::{
-  data::profiles::{KEYPAD_BUTTONS, Profile},
+  data::{
+    name,
+    profiles::{KEYPAD_BUTTONS, Profile},
+  },
   hardware::{
     buffers::Buffers,
     commands::{
@@ -33,7 +36,7 @@ impl Keypad {
     let mut keypad_profile = Profile::default();
 
     // Читаем имя профиля
-    let profile_name = String::from_utf8_lossy(&profile::request_name(buffers).await?).into_owned();
+    let profile_name = name::decode(&profile::request_name(buffers).await?);
     keypad_profile.name = profile_name;
 
     // Читаем конфигурацию кнопок (1..16)
@@ -56,7 +59,7 @@ impl Keypad {
   Записывает профиль на устройство через последовательный порт
 
   Выполняет последовательность команд для записи всех компонентов профиля:
-  - Имя профиля (обрезается до 15 символов)
+  - Имя профиля (кодируется выбранным способом и обрезается до 15 байт)
   - Конфигурацию всех 16 кнопок
   - Конфигурацию 4 направлений стика
   - Параметры мертвой зоны стика
@@ -69,13 +72,7 @@ impl Keypad {
   `Ok(())` при успешной записи или ошибку при неудаче
   */
   pub fn profile_send(buffers: &mut Buffers, profile: Profile) -> Result<()> {
-    let mut profile_name = [0u8; 15];
-    profile
-      .name
-      .chars()
-      .take(profile_name.len())
-      .enumerate()
-      .for_each(|(i, c)| profile_name[i] = c as u8);
+    let profile_name = buffers.name_encoding().encode(&profile.name);
 
     let switch_s = profile.buttons;
     let stick_s = profile.stick.word;
@@ -115,15 +112,21 @@ impl Keypad {
   }
 }
 
+/**
+Читает все 4 профиля ОЗУ с устройства
+
+После чтения активным снова становится профиль, который был активен до запроса.
+
+# Аргументы
+* `buffers` - Буферы для обмена данными с устройством
+
+# Возвращает
+Кортеж из вектора профилей ОЗУ (1..=4) и номера активного профиля
+*/
 pub async fn profile_all_request(buffers: &mut Buffers) -> Result<(Vec<Profile>, usize)> {
-  let mut res = Vec::with_capacity(4);
   let active_keypad_profile_id: usize = request_active_num(buffers).await?.into();
 
-  for i in 1..=4 {
-    buffers.send().push(&profile::Command::LoadRamToActive(i));
-    let profile = Keypad::profile_receive(buffers).await?;
-    res.push(profile);
-  }
+  let res = profile_slots_request(buffers).await?;
 
   buffers
     .send()
@@ -131,3 +134,96 @@ pub async fn profile_all_request(buffers: &mut Buffers) -> Result<(Vec<Profile>,
 
   Ok((res, active_keypad_profile_id))
 }
+
+/**
+Безопасно читает все 4 профиля ПЗУ, не теряя изменения в ОЗУ
+
+`profile::Command::LoadFlashToRam` перезаписывает все профили ОЗУ и активный профиль,
+поэтому чтение выполняется в три этапа:
+1. Снимок всех 4 профилей ОЗУ через `profile_all_request`
+2. Загрузка ПЗУ в ОЗУ и чтение профилей
+3. Запись снимка обратно в ОЗУ и восстановление активного профиля
+
+Третий этап выполняется даже при ошибке чтения ПЗУ.
+
+# Аргументы
+* `buffers` - Буферы для обмена данными с устройством
+
+# Возвращает
+Кортеж из профилей ОЗУ, профилей ПЗУ и номера активного профиля
+*/
+pub async fn profile_rom_request(
+  buffers: &mut Buffers,
+) -> Result<(Vec<Profile>, Vec<Profile>, usize)> {
+  let (ram, active_keypad_profile_id) = profile_all_request(buffers).await?;
+
+  buffers.send().push(&profile::Command::LoadFlashToRam);
+  info!("profile_rom_request: загружаю ПЗУ в ОЗУ");
+  let rom = profile_slots_request(buffers).await;
+
+  profile_ram_restore(buffers, &ram, active_keypad_profile_id)?;
+  info!("profile_rom_request: ОЗУ восстановлено из снимка");
+
+  Ok((ram, rom?, active_keypad_profile_id))
+}
+
+/**
+Записывает снимок профилей обратно в ОЗУ и делает активным профиль `active_id`
+
+# Аргументы
+* `buffers` - Буферы для обмена данными с устройством
+* `ram` - Профили ОЗУ (1..=4) для записи
+* `active_id` - Номер профиля, который станет активным после записи
+*/
+pub fn profile_ram_restore(buffers: &mut Buffers, ram: &[Profile], active_id: usize) -> Result<()> {
+  for (i, profile) in ram.iter().enumerate() {
+    Keypad::profile_send(buffers, profile.clone())?;
+    buffers
+      .send()
+      .push(&profile::Command::WriteActiveToRam(i as u8 + 1));
+  }
+
+  buffers
+    .send()
+    .push(&profile::Command::LoadRamToActive(active_id));
+
+  Ok(())
+}
+
+/**
+Сохраняет указанные профили ОЗУ в ПЗУ
+
+Каждый профиль делается активным и записывается в ячейку ПЗУ с тем же номером,
+после чего активным снова становится профиль `active_id`.
+
+# Аргументы
+* `buffers` - Буферы для обмена данными с устройством
+* `ids` - Номера профилей ОЗУ (1..=4) для сохранения
+* `active_id` - Номер профиля, который станет активным после записи
+*/
+pub fn profile_ram_commit(buffers: &mut Buffers, ids: &[usize], active_id: usize) {
+  for &id in ids {
+    buffers.send().push(&profile::Command::LoadRamToActive(id));
+    buffers
+      .send()
+      .push(&profile::Command::WriteActiveToFlash(id as u8));
+    info!("profile_ram_commit: профиль ОЗУ {id} записан в ПЗУ");
+  }
+
+  buffers
+    .send()
+    .push(&profile::Command::LoadRamToActive(active_id));
+}
+
+/// Последовательно делает активным каждый профиль ОЗУ (1..=4) и читает его
+async fn profile_slots_request(buffers: &mut Buffers) -> Result<Vec<Profile>> {
+  let mut res = Vec::with_capacity(4);
+
+  for i in 1..=4 {
+    buffers.send().push(&profile::Command::LoadRamToActive(i));
+    let profile = Keypad::profile_receive(buffers).await?;
+    res.push(profile);
+  }
+
+  Ok(res)
+}
@@ -0,0 +1,170 @@
+//! Операции с ячейками профилей кейпада: копирование, обмен и сброс.
+
+use anyhow::Result;
+use log::info;
+
+use crate::{
+  data::profiles::Profile,
+  hardware::{buffers::Buffers, commands::profile, serial::Keypad},
+};
+
+/**
+Копирует профиль `from` в ячейку `to`
+
+# Аргументы
+* `buffers` - Буферы для обмена данными с устройством
+* `from` - Номер исходного профиля (1..=4)
+* `source` - Содержимое исходной ячейки; `None` — профиль ОЗУ `from` копируется средствами устройства
+* `to` - Номер целевого профиля (1..=4)
+* `to_rom` - Сохранить результат в ПЗУ вместо ОЗУ
+* `active_id` - Номер профиля, который станет активным после операции
+*/
+pub fn slot_copy(
+  buffers: &mut Buffers,
+  (from, source): (usize, Option<Profile>),
+  to: usize,
+  to_rom: bool,
+  active_id: usize,
+) -> Result<()> {
+  load_active(buffers, from, source)?;
+  write_active(buffers, to, to_rom);
+  info!("slot_copy: профиль {from} скопирован в {to}");
+
+  buffers
+    .send()
+    .push(&profile::Command::LoadRamToActive(active_id));
+
+  Ok(())
+}
+
+/**
+Меняет местами профили `a` и `b`
+
+Профиль `a` копируется в ячейку `b`, а прежнее содержимое ячейки `b`
+(`profile_b`) записывается в ячейку `a`. В режиме ПЗУ содержимое обеих
+ячеек передаётся явно, чтобы не брать его из ОЗУ.
+
+# Аргументы
+* `buffers` - Буферы для обмена данными с устройством
+* `a`, `b` - Номера профилей (1..=4)
+* `profile_a` - Содержимое ячейки `a`; `None` — профиль ОЗУ `a` копируется средствами устройства
+* `profile_b` - Текущее содержимое ячейки `b`
+* `to_rom` - Сохранить результат в ПЗУ вместо ОЗУ
+* `active_id` - Номер профиля, который станет активным после операции
+*/
+pub fn slot_swap(
+  buffers: &mut Buffers,
+  (a, b): (usize, usize),
+  (profile_a, profile_b): (Option<Profile>, Profile),
+  to_rom: bool,
+  active_id: usize,
+) -> Result<()> {
+  load_active(buffers, a, profile_a)?;
+  write_active(buffers, b, to_rom);
+
+  Keypad::profile_send(buffers, profile_b)?;
+  write_active(buffers, a, to_rom);
+  info!("slot_swap: профили {a} и {b} обменяны");
+
+  buffers
+    .send()
+    .push(&profile::Command::LoadRamToActive(active_id));
+
+  Ok(())
+}
+
+/**
+Записывает профиль `profile` в ячейку `id`
+
+# Аргументы
+* `buffers` - Буферы для обмена данными с устройством
+* `id` - Номер профиля (1..=4)
+* `profile` - Записываемый профиль
+* `to_rom` - Сохранить результат в ПЗУ вместо ОЗУ
+* `active_id` - Номер профиля, который станет активным после операции
+*/
+pub fn slot_write(
+  buffers: &mut Buffers,
+  id: usize,
+  profile: Profile,
+  to_rom: bool,
+  active_id: usize,
+) -> Result<()> {
+  Keypad::profile_send(buffers, profile)?;
+  write_active(buffers, id, to_rom);
+  info!("slot_write: профиль записан в {id}");
+
+  buffers
+    .send()
+    .push(&profile::Command::LoadRamToActive(active_id));
+
+  Ok(())
+}
+
+/**
+Записывает профиль в ячейку и проверяет запись чтением ОЗУ
+
+Чтение при проверке повторяется до 3 раз при отсутствии ответа. При `to_rom`
+профиль сохраняется только в ПЗУ и чтением ОЗУ не проверяется: ПЗУ сверяется
+после перечитывания профилей.
+
+# Аргументы
+* `buffers` - Буферы для обмена данными с устройством
+* `id` - Номер профиля (1..=4)
+* `profile` - Записываемый профиль
+* `to_rom` - Сохранить результат в ПЗУ вместо ОЗУ
+
+# Возвращает
+`true`, если прочитанный профиль совпадает с записанным
+*/
+pub async fn slot_write_verify(
+  buffers: &mut Buffers,
+  id: usize,
+  profile: Profile,
+  to_rom: bool,
+) -> Result<bool> {
+  Keypad::profile_send(buffers, profile.clone())?;
+  write_active(buffers, id, to_rom);
+  if to_rom {
+    info!("slot_write_verify: профиль записан в ПЗУ {id}");
+    return Ok(true);
+  }
+
+  let mut res = Ok(false);
+  for _ in 0..3 {
+    buffers.send().push(&profile::Command::LoadRamToActive(id));
+    res = Keypad::profile_receive(buffers)
+      .await
+      .map(|written| written.device_eq(&profile, buffers.name_encoding()));
+    if res.is_ok() {
+      break;
+    }
+  }
+
+  info!("slot_write_verify: профиль записан в {id}, проверка: {res:?}");
+  res
+}
+
+/// Делает активным профиль `source` или, если он не задан, профиль ОЗУ `id`
+fn load_active(buffers: &mut Buffers, id: usize, source: Option<Profile>) -> Result<()> {
+  match source {
+    Some(profile) => Keypad::profile_send(buffers, profile),
+    None => {
+      buffers.send().push(&profile::Command::LoadRamToActive(id));
+      Ok(())
+    }
+  }
+}
+
+/**
+Сохраняет активный профиль в ячейку `id` ОЗУ или, при `to_rom`, только ПЗУ
+
+Ячейки ОЗУ в режиме ПЗУ не трогаются, чтобы не потерять несохранённые правки.
+*/
+fn write_active(buffers: &mut Buffers, id: usize, to_rom: bool) {
+  let command = match to_rom {
+    true => profile::Command::WriteActiveToFlash(id as u8),
+    false => profile::Command::WriteActiveToRam(id as u8),
+  };
+  buffers.send().push(&command);
+}
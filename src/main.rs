@@ -1,7 +1,7 @@
 //Убираем консоль при старте приложения на windows
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::HashMap};
 
 use iced::{
   Pixels, Point, Size,
@@ -10,10 +10,29 @@ use iced::{
 
 use crate::{
   assets::{APPLICATION_NAME, INTER_FONT, INTER_FONT_BYTES, WINDOW_ICON},
-  data::{device::Device, profiles::Profile, stick::Stick, window::Window},
+  data::{
+    backup::{Backup, RestoreSelection},
+    banks::{Bank, BankWrite},
+    capture::Chord,
+    cells::CellEditor,
+    compare::Comparison,
+    device::Device,
+    history::History,
+    keymaps::Keymap,
+    library::DirSnapshot,
+    profiles::Profile,
+    readable::ExportSelection,
+    settings::Settings,
+    stick::Stick,
+    templates::Template,
+    window::Window,
+  },
   hardware::{
     buffers::Buffers,
-    serial::{Keypad, buttons::KeypadButton},
+    serial::{
+      Keypad,
+      buttons::{KeyPicker, KeypadButton},
+    },
   },
   ui::pages::Pages,
   utils::logger::init_logger,
@@ -35,6 +54,76 @@ pub struct State {
   /// Разрешение на запись комбинации клавиш/стика из UI
   allow_write: bool,
 
+  /// Резервная копия, открытая для восстановления
+  backup_restore: Option<Backup>,
+  /// Выбор восстанавливаемых из копии данных
+  backup_selection: RestoreSelection,
+  /// Автоматическая копия при первом подключении в этом сеансе уже создана
+  backup_session_done: bool,
+  /// Результат последней операции с резервными копиями
+  backup_status: Option<String>,
+
+  /// Аккорд, набираемый в режиме записи комбинации
+  capture: Chord,
+
+  /// Выделение, буфер обмена и перетаскивание ячеек редактора
+  cells: CellEditor,
+
+  /// Сравнение двух профилей
+  compare: Comparison,
+
+  /// Выбор данных для экспорта в файл, если открыт
+  export: Option<ExportSelection>,
+
+  /// История изменений профилей библиотеки за сессию по индексу профиля
+  histories: HashMap<usize, History>,
+
+  /// Экранная клавиатура для редактируемой кнопки, если открыта
+  key_picker: Option<KeyPicker>,
+
+  /// Строка поиска по библиотеке
+  library_query: String,
+  /// Тег, по которому отфильтрован список библиотеки
+  library_tag: Option<String>,
+  /// Переименовываемый из списка профиль библиотеки и вводимое имя
+  library_rename: Option<(usize, String)>,
+  /// Время открытия профилей изменилось и ещё не записано в настройки
+  library_touched: bool,
+
+  /// Панель команд приложения открыта
+  keymap_open: bool,
+  /// Импортированная раскладка приложения
+  keymap: Option<Keymap>,
+  /// Строка поиска по командам раскладки
+  keymap_query: String,
+  /// Результат последнего импорта или назначения команды
+  keymap_status: Option<String>,
+
+  /// Панель описания и подписей профиля открыта
+  meta_open: bool,
+  /// Вводимый тег профиля
+  meta_tag: String,
+
+  /// Загруженные шаблоны профилей
+  templates: Vec<Template>,
+  /// Ошибки загрузки пропущенных файлов шаблонов
+  template_errors: Vec<String>,
+  /// Индекс выбранного шаблона
+  template_id: Option<usize>,
+  /// Ячейка кейпада (1..=4), в которую записывается шаблон
+  template_slot: usize,
+  /// Результат последнего применения шаблона
+  template_status: Option<String>,
+
+  /// Индекс набора профилей, открытого для редактирования
+  bank_id: Option<usize>,
+  /// Текущая запись набора на кейпад
+  bank_progress: Option<BankWrite>,
+  /// Результат последней записи набора
+  bank_status: Option<String>,
+  /// Наборы профилей
+  banks: Vec<Bank>,
+
   /// Двунаправленные буферы обмена с устройством
   buffers: Buffers,
 
@@ -52,6 +141,17 @@ pub struct State {
   /// Дескриптор последовательного порта и его состояние
   pub keypad: Keypad,
 
+  /// Библиотека не загрузилась, предлагается восстановление из резервной копии
+  library_damaged: bool,
+  /// Файл библиотеки не прочитан и остался на месте, сохранение библиотеки запрещено
+  library_locked: bool,
+  /// Снимок директории профилей после последнего чтения/записи
+  library_files: DirSnapshot,
+  /// Результат последней операции с библиотекой профилей
+  library_status: Option<String>,
+  /// Редактируемый список системных сочетаний для проверки профилей
+  lint_shortcuts_text: String,
+
   local_profile_id: Option<usize>,
   /// Текущая страница
   pages: Pages,
@@ -61,8 +161,17 @@ pub struct State {
   profile_write: bool,
   profiles_keypad_vec: Vec<Profile>,
   profiles_local_vec: Vec<Profile>,
+  /// Профили ПЗУ (1..=4), прочитанные без потери изменений в ОЗУ
+  profiles_rom_vec: Vec<Profile>,
   request_active_profile_id: Option<usize>,
 
+  /// Исходная ячейка для операций со слотами (1..=4)
+  slot_from: usize,
+  /// Целевая ячейка для операций со слотами (1..=4)
+  slot_to: usize,
+  /// Номер просматриваемого профиля ПЗУ, если открыт профиль из ПЗУ
+  rom_profile_id: Option<usize>,
+
   stick_callibrate: bool,
   stick_callibrate_time: Option<std::time::Instant>,
   stick_info: Stick,
@@ -71,6 +180,12 @@ pub struct State {
   /// Таймер для автоотмены режима записи
   time_write: Option<std::time::Instant>,
 
+  /// Окно, которое нужно закрыть после сохранения профилей в ПЗУ
+  window_close_id: Option<iced::window::Id>,
+
+  /// Настройки приложения
+  settings: Settings,
+
   /// Параметры окна
   window_settings: Window,
 }
@@ -92,7 +207,7 @@ fn main() -> iced::Result {
   // Инициализация логгера env_logger
   init_logger();
 
-  let window_config = Window::load();
+  let (window_config, window_quarantined) = Window::load();
 
   // Импорт иконки приложения из файла
   let icon = icon::from_file_data(WINDOW_ICON, None);
@@ -120,9 +235,9 @@ fn main() -> iced::Result {
       x: window_config.x,
       y: window_config.y,
     }),
-    resizable: true,             // Включение масштабируемости приложения
-    exit_on_close_request: true, // Включение запроса выхода
-    icon: icon.ok(),             // Установка иконки приложения
+    resizable: true,              // Включение масштабируемости приложения
+    exit_on_close_request: false, // Выход обрабатывается в State::update (проверка несохранённых профилей)
+    icon: icon.ok(),              // Установка иконки приложения
     ..Default::default()
   };
 
@@ -132,5 +247,6 @@ fn main() -> iced::Result {
     .window(window_settings) // Установка настроек окна
     .theme(State::theme)
     .subscription(State::subscription)
-    .run_with(State::new) // Запуск приложения с указанным стартовым состоянием
+    // Запуск приложения с указанным стартовым состоянием
+    .run_with(move || State::new(window_config, window_quarantined))
 }
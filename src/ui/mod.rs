@@ -1,6 +1,8 @@
 //! Пользовательский интерфейс на базе Iced: состояние, сообщения и представления.
 
 use std::{
+  collections::HashMap,
+  path::PathBuf,
   sync::{Arc, Mutex},
   time::Duration,
 };
@@ -10,7 +12,20 @@ use log::error;
 
 use crate::{
   State,
-  data::{device::Device, profiles::Profile, stick::Stick, window::Window},
+  data::{
+    backup::RestoreSelection,
+    capture::Chord,
+    cells::CellEditor,
+    compare::{Comparison, Source, SourceEntry},
+    device::Device,
+    history::History,
+    library::DirSnapshot,
+    links::SyncStatus,
+    profiles::Profile,
+    settings::Settings,
+    stick::Stick,
+    window::Window,
+  },
   hardware::{
     buffers::Buffers,
     serial::{DeviceIO, Keypad, buttons::KeypadButton},
@@ -27,12 +42,18 @@ pub mod view;
 impl State {
   /**
   Создает новый экземпляр приложения с инициализацией
+  # Аргументы
+  * `window_settings` - Загруженная конфигурация окна
+  * `window_quarantined` - Путь файла конфигурации окна, перенесённого в карантин
   # Возвращает
   Кортеж из:
   - Экземпляр приложения
   - Инициализационная задача
   */
-  pub fn new() -> (Self, Task<Message>) {
+  pub fn new(
+    window_settings: Window,
+    window_quarantined: Option<PathBuf>,
+  ) -> (Self, Task<Message>) {
     let port_name = match Keypad::get_port() {
       Ok(s) => s,
       Err(err) => {
@@ -63,7 +84,7 @@ impl State {
     };
 
     let profile =
-      Task::done(Message::ProfileReceiveKeypadVec).chain(Task::done(Message::ProfilesListLoad));
+      Task::done(Message::ProfileReceiveRomVec).chain(Task::done(Message::ProfilesListLoad));
 
     let device_info_task = match keypad.is_open {
       true => Task::done(Message::GetDeviceInfo),
@@ -81,16 +102,56 @@ impl State {
       false => Pages::default(),
     };
 
+    let (settings, settings_quarantined) = Settings::load();
+    let quarantined = [window_quarantined, settings_quarantined]
+      .into_iter()
+      .flatten()
+      .collect::<Vec<_>>();
+
     (
       Self {
         active_profile_id: None,
         allow_write: false,
-        buffers: Buffers::default(),
+        backup_restore: None,
+        backup_selection: RestoreSelection::default(),
+        backup_session_done: false,
+        backup_status: None,
+        bank_id: None,
+        bank_progress: None,
+        bank_status: None,
+        banks: Vec::new(),
+        buffers: Buffers::new(settings.name_encoding),
         button: KeypadButton::default(),
         device_info: Device::default(),
+        capture: Chord::default(),
+        cells: CellEditor::default(),
+        compare: Comparison::default(),
+        export: None,
+        histories: HashMap::new(),
         is_first_start: true,
+        key_picker: None,
+        library_query: String::new(),
+        library_tag: None,
+        library_rename: None,
+        library_touched: false,
+        keymap_open: false,
+        keymap: None,
+        keymap_query: String::new(),
+        keymap_status: None,
+        meta_open: false,
+        meta_tag: String::new(),
+        templates: Vec::new(),
+        template_errors: Vec::new(),
+        template_id: None,
+        template_slot: 1,
+        template_status: None,
         is_rom: false,
         keypad,
+        library_damaged: false,
+        library_locked: false,
+        library_files: DirSnapshot::default(),
+        library_status: quarantined_status(&quarantined),
+        lint_shortcuts_text: settings.lint.desktop_shortcuts.join(", "),
         local_profile_id: None,
         pages,
         profile: Profile::default(),
@@ -98,15 +159,161 @@ impl State {
         profile_write: false,
         profiles_keypad_vec: Vec::with_capacity(4),
         profiles_local_vec: Vec::new(),
+        profiles_rom_vec: Vec::with_capacity(4),
         request_active_profile_id: None,
+        rom_profile_id: None,
+        slot_from: 1,
+        slot_to: 2,
         stick_callibrate: false,
         stick_callibrate_time: None,
         stick_info: Stick::default(),
         stick_show_calibrate_parameters: false,
         time_write: None,
-        window_settings: Window::load(),
+        settings,
+        window_close_id: None,
+        window_settings,
       },
       Task::batch(vec![profile, device_info_task]),
     )
   }
+
+  /**
+  Проверяет, отличается ли профиль ОЗУ от сохранённого в ПЗУ
+
+  Пока профили ПЗУ не прочитаны, профиль считается сохранённым.
+
+  # Аргументы
+  * `id` - Номер профиля (1..=4)
+  */
+  pub fn is_profile_unsaved(&self, id: usize) -> bool {
+    match (
+      self.profiles_keypad_vec.get(id - 1),
+      self.profiles_rom_vec.get(id - 1),
+    ) {
+      (Some(ram), Some(rom)) => ram != rom,
+      _ => false,
+    }
+  }
+
+  /// Возвращает номер активного профиля кейпада (по умолчанию 1)
+  pub fn keypad_active_id(&self) -> usize {
+    self
+      .request_active_profile_id
+      .or(self.active_profile_id)
+      .unwrap_or(1)
+  }
+
+  /// Возвращает индекс редактируемого профиля библиотеки, если открыт профиль библиотеки
+  pub fn history_id(&self) -> Option<usize> {
+    (!self.profile_on_keypad).then_some(self.local_profile_id?)
+  }
+
+  /// Возвращает историю редактируемого профиля библиотеки
+  pub fn history(&self) -> Option<&History> {
+    self.histories.get(&self.history_id()?)
+  }
+
+  /**
+  Начинает или сверяет историю открытого профиля библиотеки
+
+  Первое открытие профиля в сессии задаёт исходное состояние,
+  при повторном изменения вне редактора записываются отдельной записью.
+  */
+  pub fn history_open(&mut self) {
+    let Some(id) = self.history_id() else {
+      return;
+    };
+    let profile = &self.profile;
+    self
+      .histories
+      .entry(id)
+      .and_modify(|history| history.sync(profile))
+      .or_insert_with(|| History::new(profile.clone()));
+  }
+
+  /**
+  Возвращает текущее состояние профиля из источника сравнения
+
+  # Возвращает
+  `None`, если профиль в источнике отсутствует
+  */
+  pub fn compare_profile(&self, source: Source) -> Option<Profile> {
+    match source {
+      Source::Library(idx) => self.profiles_local_vec.get(idx).cloned(),
+      Source::Ram(id) => self.profiles_keypad_vec.get(id - 1).cloned(),
+      Source::Rom(id) => self.profiles_rom_vec.get(id - 1).cloned(),
+      Source::File(idx) => self.compare.files.get(idx).map(|file| file.profile.clone()),
+    }
+  }
+
+  /// Возвращает все доступные для сравнения профили
+  pub fn compare_sources(&self) -> Vec<SourceEntry> {
+    let slots = |profiles: &[Profile], title: &str, source: fn(usize) -> Source| {
+      profiles
+        .iter()
+        .enumerate()
+        .map(|(i, profile)| SourceEntry {
+          source: source(i + 1),
+          title: format!("{title} {}: {}", i + 1, profile.name.trim_end_matches('\0')),
+        })
+        .collect::<Vec<_>>()
+    };
+
+    let library = self
+      .profiles_local_vec
+      .iter()
+      .enumerate()
+      .map(|(idx, profile)| SourceEntry {
+        source: Source::Library(idx),
+        title: format!("Библиотека: {}", profile.name),
+      });
+    let files = self
+      .compare
+      .files
+      .iter()
+      .enumerate()
+      .map(|(idx, file)| SourceEntry {
+        source: Source::File(idx),
+        title: format!("{}: {}", file.file, file.profile.name),
+      });
+
+    library
+      .chain(slots(&self.profiles_keypad_vec, "ОЗУ", Source::Ram))
+      .chain(slots(&self.profiles_rom_vec, "ПЗУ", Source::Rom))
+      .chain(files)
+      .collect()
+  }
+
+  /**
+  Возвращает состояние синхронизации ячейки с профилем библиотеки
+
+  # Аргументы
+  * `id` - Номер ячейки (1..=4)
+
+  # Возвращает
+  Состояние и индекс связанного профиля или `None`, если ячейка не связана
+  или её профиль ещё не прочитан
+  */
+  pub fn slot_sync(&self, id: usize) -> Option<(SyncStatus, Option<usize>)> {
+    let link = self.settings.slot_links.get(id - 1)?.as_ref()?;
+    let slot = self.profiles_keypad_vec.get(id - 1)?;
+    Some(SyncStatus::of(link, slot, &self.profiles_local_vec))
+  }
+
+  /// Возвращает номера профилей ОЗУ, не сохранённых в ПЗУ
+  pub fn unsaved_profiles(&self) -> Vec<usize> {
+    (1..=4).filter(|&id| self.is_profile_unsaved(id)).collect()
+  }
+}
+
+/// Формирует сообщение о файлах, перенесённых в карантин при запуске
+fn quarantined_status(quarantined: &[PathBuf]) -> Option<String> {
+  (!quarantined.is_empty()).then(|| {
+    let paths = quarantined
+      .iter()
+      .map(|path| path.display().to_string())
+      .collect::<Vec<_>>()
+      .join(", ");
+    format!("Повреждённые файлы настроек перемещены в карантин: {paths}")
+  })
 }
@@ -0,0 +1,235 @@
+use std::fmt::{self, Display};
+
+use iced::{
+  Alignment, Element, Length, Theme,
+  widget::{
+    Scrollable, button, column, container, horizontal_rule, pick_list, progress_bar, row,
+    scrollable::{Direction, Scrollbar},
+    svg, text, text_input, vertical_rule,
+  },
+};
+
+use crate::{
+  State,
+  data::{banks::Bank, library::Library},
+  mk_button,
+  ui::{
+    pages::Pages,
+    styles::{self, BUTTON_HEIGH, PADDING, RULE_WIDTH, SPACING},
+    update::Message,
+  },
+};
+
+/// Элемент списка выбора профиля библиотеки для ячейки набора
+#[derive(Debug, Clone, PartialEq)]
+struct LibraryEntry {
+  /// Индекс профиля в локальной библиотеке
+  idx: usize,
+
+  /// Имя профиля
+  name: String,
+}
+
+impl Display for LibraryEntry {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", self.name)
+  }
+}
+
+impl Pages {
+  /**
+  Создает интерфейс экрана наборов профилей
+
+  Разделен на две панели:
+  - Левая панель: список наборов и их создание
+  - Правая панель: редактирование и запись открытого набора
+
+  # Аргументы
+  * `state` - Состояние приложения
+  * `screen_name` - Заголовок экрана
+
+  # Возвращает
+  Элемент интерфейса экрана наборов
+  */
+  pub fn banks_screen<'a>(
+    state: &'a State,
+    screen_name: Element<'a, Message>,
+  ) -> Element<'a, Message> {
+    let banks_panel = Self::build_banks_panel(state, screen_name);
+
+    let bank_panel = match state.bank_id.and_then(|id| state.banks.get(id)) {
+      Some(bank) => Self::build_bank_editor(state, state.bank_id.unwrap(), bank),
+      None => container(text("Выберите или создайте набор"))
+        .center(Length::Fill)
+        .into(),
+    };
+
+    row![banks_panel, vertical_rule(RULE_WIDTH), bank_panel].into()
+  }
+
+  /**
+  Строит левую панель со списком наборов
+
+  # Аргументы
+  * `state` - Состояние приложения
+  * `screen_name` - Заголовок экрана
+
+  # Возвращает
+  Вертикальную колонку с кнопками создания наборов и их списком
+  */
+  fn build_banks_panel<'a>(
+    state: &'a State,
+    screen_name: Element<'a, Message>,
+  ) -> Element<'a, Message> {
+    let bank_management = column![
+      mk_button!(
+        container("Создать набор").center_x(Length::Fill),
+        Message::BankNew
+      ),
+      button(container("Набор из кейпада").center_x(Length::Fill))
+        .height(BUTTON_HEIGH)
+        .on_press_maybe((state.profiles_keypad_vec.len() == 4).then_some(Message::BankCapture))
+        .style(styles::button::rounding),
+    ]
+    .spacing(SPACING);
+
+    let bank_buttons = column(state.banks.iter().enumerate().map(|(idx, bank)| {
+      row![
+        mk_button!(
+          container(text(&bank.name)).center_x(Length::Fill),
+          Message::BankSelect(idx)
+        )
+        .width(Length::Fill)
+        .style(move |theme: &Theme, status| styles::button::active_bank(theme, status, state, idx)),
+        mk_button!(
+          container(svg(svg::Handle::from_memory(include_bytes!(
+            "../../../assets/icons/trash.svg"
+          ))))
+          .center(Length::Fill),
+          Message::BankRemove(idx)
+        )
+        .width(BUTTON_HEIGH)
+        .height(BUTTON_HEIGH)
+      ]
+      .spacing(SPACING)
+      .into()
+    }))
+    .spacing(SPACING);
+
+    column![
+      screen_name,
+      bank_management,
+      horizontal_rule(RULE_WIDTH),
+      Scrollable::new(bank_buttons)
+        .direction(Direction::Vertical(Scrollbar::new()))
+        .spacing(SPACING),
+    ]
+    .align_x(Alignment::Center)
+    .spacing(SPACING)
+    .padding(PADDING)
+    .width(260)
+    .into()
+  }
+
+  /**
+  Строит панель редактирования набора
+
+  Содержит:
+  - Поле ввода имени набора
+  - Выбор профиля библиотеки для каждой из 4 ячеек
+  - Кнопки записи набора в ОЗУ и ПЗУ
+  - Индикатор хода записи и результат проверки
+
+  # Аргументы
+  * `state` - Состояние приложения
+  * `idx` - Индекс набора
+  * `bank` - Редактируемый набор
+
+  # Возвращает
+  Вертикальную колонку с элементами редактирования набора
+  */
+  fn build_bank_editor<'a>(state: &'a State, idx: usize, bank: &'a Bank) -> Element<'a, Message> {
+    let library = state
+      .profiles_local_vec
+      .iter()
+      .enumerate()
+      .map(|(idx, profile)| LibraryEntry {
+        idx,
+        name: profile.name.clone(),
+      })
+      .collect::<Vec<_>>();
+
+    let name_input = text_input("Имя набора", &bank.name)
+      .align_x(Alignment::Center)
+      .size(25)
+      .width(300)
+      .on_input(Message::BankRename)
+      .style(styles::text_input::rounding);
+
+    let slots = column(bank.profiles.iter().enumerate().map(|(i, name)| {
+      // Ненайденный или неоднозначный профиль показывается вместо выбора
+      let (selected, placeholder) = match name {
+        None => (None, "Пустой профиль".to_string()),
+        Some(name) => match Library::find(&state.profiles_local_vec, name) {
+          Ok(idx) => (library.get(idx).cloned(), name.clone()),
+          Err(err) => (None, err.to_string()),
+        },
+      };
+
+      row![
+        text!("Ячейка {}", i + 1).width(100),
+        pick_list(library.clone(), selected, move |entry| {
+          Message::BankSetSlot(i + 1, entry.idx)
+        })
+        .placeholder(placeholder)
+        .width(Length::Fill),
+      ]
+      .align_y(Alignment::Center)
+      .spacing(SPACING)
+      .into()
+    }))
+    .spacing(SPACING)
+    .width(400);
+
+    let can_write = state.keypad.is_open && state.bank_progress.is_none();
+    let write_button = |label, to_rom| {
+      button(container(label).center_x(Length::Fill))
+        .height(BUTTON_HEIGH)
+        .width(Length::Fill)
+        .on_press_maybe(can_write.then_some(Message::BankWrite(idx, to_rom)))
+        .style(styles::button::rounding)
+    };
+
+    let write_buttons = row![
+      write_button("Записать в ОЗУ", false),
+      write_button("Записать в ПЗУ", true),
+    ]
+    .spacing(SPACING)
+    .width(400);
+
+    let progress = state
+      .bank_progress
+      .as_ref()
+      .filter(|progress| progress.name == bank.name)
+      .map(|progress| {
+        column![
+          progress_bar(0.0..=4.0, progress.written as f32).height(10),
+          text(match progress.verifying_rom {
+            true => "Проверка ПЗУ...".to_string(),
+            false => format!("Записано ячеек: {} из 4", progress.written),
+          }),
+        ]
+        .spacing(SPACING)
+        .width(400)
+      });
+
+    column![name_input, slots, write_buttons]
+      .push_maybe(progress)
+      .push_maybe(state.bank_status.as_deref().map(text))
+      .align_x(Alignment::Center)
+      .spacing(SPACING)
+      .padding(PADDING)
+      .width(Length::Fill)
+      .into()
+  }
+}
@@ -0,0 +1,208 @@
+use iced::{
+  Alignment, Element, Length,
+  widget::{
+    Scrollable, button, checkbox, column, container, horizontal_rule, pick_list, row,
+    scrollable::{Direction, Scrollbar},
+    text,
+  },
+};
+
+use crate::{
+  State,
+  data::{
+    cells::Cell,
+    compare::{Field, Side},
+    profiles::Profile,
+  },
+  mk_button,
+  ui::{
+    pages::Pages,
+    styles::{self, BUTTON_HEIGH, PADDING, RULE_WIDTH, SPACING},
+    update::Message,
+  },
+};
+
+impl Pages {
+  /**
+  Создает интерфейс экрана сравнения профилей
+
+  Содержит:
+  - Выбор левого и правого профиля (библиотека, ОЗУ, ПЗУ или файл)
+  - Таблицу полей профилей с выделением отличий
+  - Кнопки переноса отдельных полей и всех отличий
+
+  # Аргументы
+  * `state` - Состояние приложения
+  * `screen_name` - Заголовок экрана
+
+  # Возвращает
+  Элемент интерфейса экрана сравнения
+  */
+  pub fn compare_screen<'a>(
+    state: &'a State,
+    screen_name: Element<'a, Message>,
+  ) -> Element<'a, Message> {
+    let compare = &state.compare;
+    let sides = row![
+      Self::build_compare_side(state, false),
+      Self::build_compare_side(state, true),
+    ]
+    .spacing(SPACING);
+
+    let table: Element<'_, Message> = match (&compare.left, &compare.right) {
+      (Some(left), Some(right)) => Self::build_compare_table(state, left, right),
+      _ => container(text("Выберите два профиля для сравнения"))
+        .center(Length::Fill)
+        .into(),
+    };
+
+    column![screen_name, sides, horizontal_rule(RULE_WIDTH), table]
+      .spacing(SPACING)
+      .padding(PADDING)
+      .into()
+  }
+
+  /**
+  Строит выбор профиля для одной стороны сравнения
+
+  # Аргументы
+  * `state` - Состояние приложения
+  * `right` - Правая (`true`) или левая сторона
+
+  # Возвращает
+  Колонку со списком профилей и кнопками открытия файла и сохранения в библиотеку
+  */
+  fn build_compare_side(state: &State, right: bool) -> Element<'_, Message> {
+    let sources = state.compare_sources();
+    let side = state.compare.side(right);
+    let selected = side.and_then(|side| {
+      sources
+        .iter()
+        .find(|entry| entry.source == side.source)
+        .cloned()
+    });
+
+    let changed = side.is_some_and(|side| {
+      state
+        .compare_profile(side.source)
+        .is_some_and(|profile| profile != side.profile)
+    });
+
+    column![
+      pick_list(sources, selected, move |entry| Message::CompareSelect(
+        right, entry
+      ))
+      .placeholder("Профиль")
+      .width(Length::Fill),
+      row![
+        mk_button!("Открыть файл", Message::CompareOpenFile(right)),
+        button("В библиотеку как новый")
+          .height(BUTTON_HEIGH)
+          .on_press_maybe(side.is_some().then_some(Message::CompareSaveAsNew(right)))
+          .style(styles::button::rounding),
+      ]
+      .spacing(SPACING),
+    ]
+    .push_maybe(changed.then(|| {
+      text("Изменён в сравнении, источник не обновлён")
+        .size(12)
+        .style(text::secondary)
+    }))
+    .spacing(SPACING)
+    .width(Length::Fill)
+    .into()
+  }
+
+  /**
+  Строит таблицу полей двух профилей
+
+  Отличающиеся поля выделяются, для них доступны кнопки переноса
+  значения влево и вправо.
+
+  # Аргументы
+  * `state` - Состояние приложения
+  * `left` - Левый профиль
+  * `right` - Правый профиль
+
+  # Возвращает
+  Сводку отличий и прокручиваемую таблицу полей
+  */
+  fn build_compare_table<'a>(state: &'a State, left: &Side, right: &Side) -> Element<'a, Message> {
+    let differences = state.compare.diff();
+
+    let summary = row![
+      text(match differences.len() {
+        0 => "Профили совпадают".to_string(),
+        len => format!("Отличий: {len}"),
+      })
+      .width(Length::Fill),
+      checkbox("Только отличия", state.compare.only_differences)
+        .on_toggle(Message::CompareOnlyDifferences),
+      button("Все →")
+        .on_press_maybe((!differences.is_empty()).then_some(Message::ComparePickAll(true)))
+        .style(styles::button::rounding),
+      button("← Все")
+        .on_press_maybe((!differences.is_empty()).then_some(Message::ComparePickAll(false)))
+        .style(styles::button::rounding),
+    ]
+    .align_y(Alignment::Center)
+    .spacing(SPACING);
+
+    let rows = column(
+      Field::all()
+        .filter(|field| !state.compare.only_differences || differences.contains(field))
+        .map(|field| {
+          let differs = differences.contains(&field);
+          let value = |profile: &Profile| {
+            let value = field_value(state, profile, field);
+            let value = match value.is_empty() {
+              true => "—".to_string(),
+              false => value,
+            };
+            text(value).width(Length::Fill).style(match differs {
+              true => text::primary,
+              false => text::secondary,
+            })
+          };
+          let pick = |label, to_right| {
+            button(text(label).size(14))
+              .on_press_maybe(differs.then_some(Message::ComparePick(field, to_right)))
+              .style(styles::button::rounding)
+          };
+
+          row![
+            text(field.title()).width(140),
+            value(&left.profile),
+            pick("→", true),
+            pick("←", false),
+            value(&right.profile),
+          ]
+          .align_y(Alignment::Center)
+          .spacing(SPACING)
+          .into()
+        }),
+    )
+    .spacing(5);
+
+    column![
+      summary,
+      Scrollable::new(rows)
+        .direction(Direction::Vertical(Scrollbar::new()))
+        .spacing(SPACING)
+        .height(Length::Fill),
+    ]
+    .spacing(SPACING)
+    .into()
+  }
+}
+
+/// Возвращает значение поля профиля для таблицы сравнения
+fn field_value(state: &State, profile: &Profile, field: Field) -> String {
+  let layout = state.settings.layout;
+  match field {
+    Field::Name => profile.name.trim_end_matches('\0').to_string(),
+    Field::Deadzone => format!("{}%", profile.stick.deadzone),
+    Field::Cell(Cell::Button(id)) => profile.get_button_label(id - 1, layout),
+    Field::Cell(Cell::Stick(id)) => profile.get_stick_label(id - 1, layout),
+  }
+}
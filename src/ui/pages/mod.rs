@@ -14,9 +14,12 @@ use crate::{
   ui::{Message, styles::HEADING_SIZE},
 };
 
+pub mod banks;
+pub mod compare;
 pub mod connected_device_not_found;
 pub mod profiles;
 pub mod settings;
+pub mod templates;
 pub mod updater;
 
 /**
@@ -58,6 +61,31 @@ pub enum Pages {
   #[default]
   Profiles,
 
+  /**
+  Экран наборов профилей
+
+  Позволяет собирать наборы из 4 профилей библиотеки и записывать
+  их в ячейки кейпада одной операцией.
+  */
+  Banks,
+
+  /**
+  Экран сравнения профилей
+
+  Показывает два профиля рядом, выделяет отличия и позволяет
+  переносить отдельные назначения из одного профиля в другой.
+  */
+  Compare,
+
+  /**
+  Экран шаблонов профилей
+
+  Показывает встроенные и пользовательские шаблоны для популярных
+  приложений и раскладки стика; шаблон можно применить к новому
+  профилю библиотеки или записать в ячейку кейпада.
+  */
+  Templates,
+
   /**
   Экран настроек
 
@@ -97,6 +125,9 @@ impl Pages {
   fn name(&self) -> &str {
     match self {
       Self::Profiles => "Профили",
+      Self::Banks => "Наборы",
+      Self::Compare => "Сравнение",
+      Self::Templates => "Шаблоны",
       Self::Settings => "Настройки",
       Self::Updater => "Обновление",
       Self::ConnectedDeviceNotFound => "Устройство не найдено",
@@ -121,6 +152,9 @@ impl Pages {
 
     match state.pages {
       Self::Profiles => Self::profiles_screen(state, profile, screen_name),
+      Self::Banks => Self::banks_screen(state, screen_name),
+      Self::Compare => Self::compare_screen(state, screen_name),
+      Self::Templates => Self::templates_screen(state, screen_name),
       Self::Settings => Self::settings_screen(state, screen_name),
       Self::Updater => Self::updater_screen(state, screen_name),
       Self::ConnectedDeviceNotFound => Self::device_not_found_screen(screen_name),
@@ -141,6 +175,7 @@ impl Pages {
       .size(HEADING_SIZE)
       .width(match state.pages {
         Pages::Profiles => Length::Shrink,
+        Pages::Banks => Length::Shrink,
         Pages::ConnectedDeviceNotFound => Length::Shrink,
         _ => Length::Fill,
       })
@@ -157,6 +192,15 @@ pub enum Icon {
   /// Иконка для раздела профилей
   Profiles,
 
+  /// Иконка для раздела наборов профилей
+  Banks,
+
+  /// Иконка для раздела сравнения профилей
+  Compare,
+
+  /// Иконка для раздела шаблонов профилей
+  Templates,
+
   /// Иконка для раздела настроек
   Settings,
 
@@ -182,6 +226,9 @@ impl Icon {
   pub fn icon(&self) -> &'static [u8] {
     match self {
       Self::Profiles => include_bytes!("../../../assets/icons/profiles.svg"),
+      Self::Banks => include_bytes!("../../../assets/icons/banks.svg"),
+      Self::Compare => include_bytes!("../../../assets/icons/compare.svg"),
+      Self::Templates => include_bytes!("../../../assets/icons/templates.svg"),
       Self::Settings => include_bytes!("../../../assets/icons/settings.svg"),
       Self::Update => include_bytes!("../../../assets/icons/updater.svg"),
       Self::Download => include_bytes!("../../../assets/icons/download.svg"),
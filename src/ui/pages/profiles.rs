@@ -1,15 +1,26 @@
 use iced::{
   Alignment, Element, Length, Theme,
   widget::{
-    Scrollable, button, column, container, horizontal_rule, mouse_area, row,
+    Scrollable, button, column, container, horizontal_rule, horizontal_space, mouse_area,
+    pick_list, progress_bar, row,
     scrollable::{Direction, Scrollbar},
-    slider, svg, text, text_input, toggler, vertical_rule,
+    slider, svg, text, text_input, toggler, tooltip, vertical_rule,
   },
 };
 
 use crate::{
   State,
-  data::profiles::Profile,
+  data::{
+    capture::CaptureMode,
+    cells::Cell,
+    code::KeyCategory,
+    lint::{Problem, Severity, Target, lint},
+    meta::ButtonColor,
+    name::NAME_LEN,
+    profiles::Profile,
+    search::{LibrarySort, search, tags},
+  },
+  hardware::serial::buttons::KeyPicker,
   mk_button,
   ui::{
     pages::{Icon, Pages},
@@ -19,6 +30,7 @@ use crate::{
     },
     update::Message,
   },
+  utils::time::format_timestamp,
 };
 
 impl Pages {
@@ -62,6 +74,7 @@ impl Pages {
   Содержит:
   - Переключатель режимов ОЗУ/ПЗУ
   - Кнопки быстрого доступа к профилям 1-4
+  - Операции со слотами (копирование, обмен, сброс)
   - Кнопки импорта/экспорта профилей
   - Прокручиваемый список всех доступных профилей
 
@@ -83,6 +96,16 @@ impl Pages {
     let ram_rom_buttons =
       column((1..=4).map(|id| mk_button_profile_row(state, id))).spacing(SPACING);
 
+    let commit_button = button(container("Сохранить всё в ПЗУ").center_x(Length::Fill))
+      .height(BUTTON_HEIGH)
+      .width(Length::Fill)
+      .on_press_maybe(
+        (!state.unsaved_profiles().is_empty()).then_some(Message::ProfilesCommitToRom),
+      )
+      .style(styles::button::rounding);
+
+    let slot_operations = Self::build_slot_operations(state);
+
     let profile_management = column![mk_button!(
       container("Создать профиль").center_x(Length::Fill),
       Message::ProfileNew
@@ -96,10 +119,24 @@ impl Pages {
       text("Профили").size(HEADING_SIZE),
       mode_toggle,
       ram_rom_buttons,
+      commit_button,
+      slot_operations,
       horizontal_rule(RULE_WIDTH),
       profile_management,
-      container(profile_list),
     ]
+    .push_maybe(
+      state
+        .library_status
+        .as_deref()
+        .map(|status| text(status).size(14)),
+    )
+    .push_maybe(state.library_damaged.then(|| {
+      mk_button!(
+        container("Восстановить библиотеку").center_x(Length::Fill),
+        Message::LibraryRestore
+      )
+    }))
+    .push(container(profile_list))
     .align_x(Alignment::Center)
     .spacing(SPACING)
     .padding(PADDING)
@@ -108,45 +145,198 @@ impl Pages {
   }
 
   /**
-  Создает прокручиваемый список всех доступных профилей
+  Строит элементы управления операциями со слотами кейпада
+
+  Содержит выбор исходной и целевой ячеек и кнопки:
+  - Копировать: исходная ячейка → целевая
+  - Обменять: исходная ячейка ↔ целевая
+  - Очистить: сбросить исходную ячейку
+  - В библиотеку: добавить копию исходной ячейки в локальную библиотеку
+
+  В режиме ПЗУ результат операций также сохраняется в ПЗУ.
+
+  # Аргументы
+  * `state` - Состояние приложения с выбранными ячейками
+
+  # Возвращает
+  Вертикальную колонку с элементами управления слотами
+  */
+  fn build_slot_operations(state: &State) -> Element<'_, Message> {
+    const SLOTS: [usize; 4] = [1, 2, 3, 4];
+
+    let (from, to) = (state.slot_from, state.slot_to);
+
+    let slot_select = row![
+      text("Слот"),
+      pick_list(SLOTS, Some(from), Message::SlotSelectFrom),
+      text("→"),
+      pick_list(SLOTS, Some(to), Message::SlotSelectTo),
+    ]
+    .align_y(Alignment::Center)
+    .spacing(SPACING);
+
+    let slot_button = |label, message| {
+      button(container(text(label).size(14)).center_x(Length::Fill))
+        .width(Length::Fill)
+        .on_press(message)
+        .style(styles::button::rounding)
+    };
+
+    column![
+      slot_select,
+      row![
+        slot_button("Копировать", Message::SlotCopy(from, to)),
+        slot_button("Обменять", Message::SlotSwap(from, to)),
+      ]
+      .spacing(SPACING),
+      row![
+        slot_button("Очистить", Message::SlotClear(from)),
+        slot_button("В библиотеку", Message::SlotDuplicate(from)),
+      ]
+      .spacing(SPACING),
+    ]
+    .spacing(SPACING)
+    .into()
+  }
+
+  /**
+  Создает список профилей библиотеки с поиском и фильтрами
+
+  Содержит:
+  - Строку поиска по имени, тегу, описанию, подписям кнопок и комбинации
+  - Выбор тега и порядка сортировки
+  - Прокручиваемый список, в котором избранные профили закреплены вверху;
+    у каждого профиля есть кнопки избранного и удаления, у открытого —
+    также переименования и копирования
 
   # Аргументы
   * `state` - Состояние приложения, содержащее вектор профилей
 
   # Возвращает
-  Прокручиваемый контейнер с кнопками выбора профилей
+  Колонку с фильтрами и прокручиваемым списком профилей
   */
   fn build_profile_list(state: &State) -> Element<'_, Message> {
-    let profile_buttons = column(state.profiles_local_vec.iter().enumerate().map(
-      |(idx, profile)| {
-        row![
-          mk_button!(
-            container(text(&profile.name)).center_x(Length::Fill),
-            Message::ProfileLoadLocal(idx)
-          )
-          .style(move |theme: &Theme, status| {
-            styles::button::active_profile_id(theme, status, state, idx)
-          }),
-          mk_button!(
-            container(svg(svg::Handle::from_memory(include_bytes!(
-              "../../../assets/icons/trash.svg"
-            ))))
-            .center(Length::Fill),
-            Message::ProfileRemove(idx)
-          )
-          .width(BUTTON_HEIGH)
-          .height(BUTTON_HEIGH)
-        ]
-        .spacing(SPACING)
-        .into()
-      },
-    ))
-    .spacing(SPACING);
+    let view = &state.settings.library_view;
+    let hits = search(
+      &state.profiles_local_vec,
+      view,
+      &state.library_query,
+      state.library_tag.as_deref(),
+    );
 
-    Scrollable::new(profile_buttons)
-      .direction(Direction::Vertical(Scrollbar::new()))
-      .spacing(SPACING)
+    let search_input = text_input("Имя, тег или Ctrl+S", &state.library_query)
+      .on_input(Message::LibrarySearch)
+      .size(14)
+      .style(styles::text_input::rounding);
+
+    let tags = tags(&state.profiles_local_vec);
+    let filters = row![
+      pick_list(tags, state.library_tag.clone(), |tag| Message::LibraryTag(
+        Some(tag)
+      ))
+      .placeholder("Все теги")
+      .text_size(14)
+      .width(Length::Fill),
+    ]
+    .push_maybe(state.library_tag.is_some().then(|| {
+      button(text("×").size(14))
+        .on_press(Message::LibraryTag(None))
+        .style(styles::button::rounding)
+    }))
+    .push(pick_list(LibrarySort::ALL, Some(view.sort), Message::LibrarySort).text_size(14))
+    .align_y(Alignment::Center)
+    .spacing(5);
+
+    let small_button = |label: &'static str, message: Message| {
+      button(container(text(label).size(14)).center(Length::Fill))
+        .width(BUTTON_HEIGH)
+        .height(BUTTON_HEIGH)
+        .on_press(message)
+        .style(styles::button::rounding)
+    };
+
+    let profile_buttons = column(hits.into_iter().map(|hit| {
+      let idx = hit.idx;
+      let profile = &state.profiles_local_vec[idx];
+
+      let name: Element<'_, Message> = match &state.library_rename {
+        Some((i, input)) if *i == idx => text_input(&profile.name, input)
+          .on_input(Message::LibraryRenameInput)
+          .on_submit(Message::LibraryRenameSubmit)
+          .size(14)
+          .width(Length::Fill)
+          .style(styles::text_input::rounding)
+          .into(),
+        _ => mk_button!(
+          container(text(&profile.name)).center_x(Length::Fill),
+          Message::ProfileLoadLocal(idx)
+        )
+        .style(move |theme: &Theme, status| {
+          styles::button::active_profile_id(theme, status, state, idx)
+        })
+        .into(),
+      };
+      let favorite = match view.is_favorite(&profile.name) {
+        true => "★",
+        false => "☆",
+      };
+
+      let actions = match &state.library_rename {
+        Some((i, _)) if *i == idx => row![
+          small_button("✓", Message::LibraryRenameSubmit),
+          small_button("×", Message::LibraryRenameCancel),
+        ],
+        // Переименование и копирование доступны для открытого профиля
+        _ if !state.profile_on_keypad && state.local_profile_id == Some(idx) => row![
+          small_button("✎", Message::LibraryRenameStart(idx)),
+          small_button("⧉", Message::LibraryDuplicate(idx)),
+        ],
+        _ => row![],
+      };
+      let actions = actions.push(
+        mk_button!(
+          container(svg(svg::Handle::from_memory(include_bytes!(
+            "../../../assets/icons/trash.svg"
+          ))))
+          .center(Length::Fill),
+          Message::ProfileRemove(idx)
+        )
+        .width(BUTTON_HEIGH)
+        .height(BUTTON_HEIGH),
+      );
+
+      // Кнопки, которым назначена искомая комбинация
+      let found = (!hit.buttons.is_empty()).then(|| {
+        let buttons = hit
+          .buttons
+          .iter()
+          .map(|id| format!("#{id}"))
+          .collect::<Vec<_>>()
+          .join(", ");
+        text!("Кнопки {buttons}").size(12).style(text::secondary)
+      });
+
+      column![
+        row![small_button(favorite, Message::LibraryFavorite(idx)), name]
+          .push(actions.spacing(5))
+          .align_y(Alignment::Center)
+          .spacing(5)
+      ]
+      .push_maybe(found)
+      .spacing(2)
       .into()
+    }))
+    .spacing(SPACING);
+
+    column![
+      search_input,
+      filters,
+      Scrollable::new(profile_buttons)
+        .direction(Direction::Vertical(Scrollbar::new()))
+        .spacing(SPACING),
+    ]
+    .spacing(SPACING)
+    .into()
   }
 
   /**
@@ -188,12 +378,41 @@ impl Pages {
       .center_x(Length::Fill),
     };
 
-    let keypad_grid = Self::build_keypad_grid(state, profile);
-    let stick_controls = Self::build_stick_controls(state, profile);
+    let problems = lint(profile, &state.settings.lint, state.settings.name_encoding);
+    let name_len = state
+      .settings
+      .name_encoding
+      .encode_full(&profile.name)
+      .len();
+    let name_counter =
+      text!("{name_len}/{NAME_LEN} байт")
+        .size(12)
+        .style(match name_len > NAME_LEN {
+          true => text::danger,
+          false => text::secondary,
+        });
+    let profile_name_input = column![profile_name_input, name_counter]
+      .push_maybe(name_problems(&problems))
+      .align_x(Alignment::Center)
+      .spacing(SPACING);
+
+    let keypad_grid = Self::build_keypad_grid(state, profile, &problems);
+    let stick_controls = Self::build_stick_controls(state, profile, &problems);
 
     let controls_layout = row![keypad_grid, column![stick_controls].spacing(SPACING)]
       .spacing(SPACING)
       .align_y(Alignment::End);
+    let controls_layout = column![controls_layout]
+      .push_maybe(Self::build_cell_menu(state))
+      .push_maybe(
+        state
+          .cells
+          .status
+          .as_deref()
+          .map(|status| text(status).size(14)),
+      )
+      .align_x(Alignment::Center)
+      .spacing(SPACING);
 
     match state.profile_on_keypad {
       true => column![
@@ -203,17 +422,505 @@ impl Pages {
       .padding(PADDING)
       .spacing(SPACING)
       .into(),
-      false => mouse_area(
+      false => {
+        let picker = match &state.key_picker {
+          Some(picker) => Self::build_key_picker(state, picker),
+          None => container(mk_button!("Экранная клавиатура", Message::KeyPickerOpen))
+            .center_x(Length::Fill)
+            .into(),
+        };
+
         column![
-          profile_name_input,
-          container(controls_layout).center(Length::Fill)
+          mouse_area(
+            column![
+              profile_name_input,
+              container(controls_layout).center(Length::Fill)
+            ]
+            .spacing(SPACING),
+          )
+          .on_press(Message::DisallowWriteButtonCombination),
         ]
+        .push_maybe(Self::build_capture_countdown(state))
+        .push(picker)
+        .push(Self::build_keymap(state))
+        .push(Self::build_metadata(state, profile))
+        .push_maybe(Self::build_history(state))
         .padding(PADDING)
+        .spacing(SPACING)
+        .into()
+      }
+    }
+  }
+
+  /**
+  Строит индикатор оставшегося времени записи комбинации
+
+  # Аргументы
+  * `state` - Состояние приложения
+
+  # Возвращает
+  Подсказку и шкалу времени или `None`, если запись не идёт
+  */
+  fn build_capture_countdown(state: &State) -> Option<Element<'_, Message>> {
+    let start = state.time_write.filter(|_| state.allow_write)?;
+    let mode = state.settings.capture.mode;
+    let timeout = mode.timeout().as_secs_f32();
+    let left = (timeout - start.elapsed().as_secs_f32()).max(0.);
+
+    let hint = match (mode, state.capture.is_held()) {
+      (CaptureMode::Chord, true) => "Отпустите клавиши, чтобы записать комбинацию".to_string(),
+      (CaptureMode::Chord, false) => format!("Нажмите комбинацию: {left:.1} с"),
+      (CaptureMode::Sequential, _) => format!("Нажимайте клавиши по очереди: {left:.1} с"),
+    };
+
+    Some(
+      row![
+        text(hint).size(14),
+        progress_bar(0.0..=timeout, left).height(8).width(200),
+      ]
+      .align_y(Alignment::Center)
+      .spacing(SPACING)
+      .into(),
+    )
+  }
+
+  /**
+  Строит контекстное меню ячейки
+
+  Операции применяются ко всем выделенным ячейкам. Для профилей кейпада
+  доступно только копирование: назначения можно вставить в профиль библиотеки.
+
+  # Аргументы
+  * `state` - Состояние приложения
+
+  # Возвращает
+  Строку с операциями или `None`, если меню не открыто
+  */
+  fn build_cell_menu(state: &State) -> Option<Element<'_, Message>> {
+    let cell = state.cells.menu?;
+    let editable = !state.profile_on_keypad;
+    let selected = &state.cells.selected;
+
+    let title = match selected.len() {
+      1 => cell.title(),
+      len => format!("Выделено ячеек: {len}"),
+    };
+    let item = |label: &'static str, message: Option<Message>| {
+      button(text(label).size(14))
+        .on_press_maybe(message)
+        .style(styles::button::rounding)
+    };
+
+    Some(
+      row![
+        text(title).size(14),
+        item("Копировать", Some(Message::CellCopy)),
+        item(
+          "Вставить",
+          (editable && !state.cells.clipboard.is_empty()).then_some(Message::CellPaste),
+        ),
+        item(
+          "Поменять местами",
+          (editable && selected.len() == 2).then_some(Message::CellSwap),
+        ),
+        item("Очистить", editable.then_some(Message::CellClear)),
+        item(
+          "Во все профили библиотеки",
+          editable.then_some(Message::CellApplyToLibrary),
+        ),
+        item("Закрыть", Some(Message::CellMenuClose)),
+      ]
+      .align_y(Alignment::Center)
+      .spacing(SPACING)
+      .wrap()
+      .into(),
+    )
+  }
+
+  /**
+  Строит панель команд импортированной раскладки приложения
+
+  Команды ищутся по названию, идентификатору или сочетанию; нажатие
+  на команду записывает её сочетание в выбранную кнопку и делает
+  название команды подписью кнопки.
+
+  # Аргументы
+  * `state` - Состояние приложения
+
+  # Возвращает
+  Кнопку открытия панели и, если панель открыта, список команд
+  */
+  fn build_keymap(state: &State) -> Element<'_, Message> {
+    /// Наибольшее число команд в списке; остальные находятся поиском
+    const MAX_COMMANDS: usize = 50;
+
+    let toggle = button(
+      text(match state.keymap_open {
+        true => "▾ Команды приложения",
+        false => "▸ Команды приложения",
+      })
+      .size(14),
+    )
+    .on_press(Message::KeymapToggle)
+    .style(styles::button::transparent);
+
+    if !state.keymap_open {
+      return toggle.into();
+    }
+
+    let status = state
+      .keymap_status
+      .as_deref()
+      .map(|status| text(status).size(12).style(text::secondary));
+
+    let Some(keymap) = &state.keymap else {
+      return column![
+        toggle,
+        row![
+          mk_button!(text("Открыть файл сочетаний").size(14), Message::KeymapOpen),
+          text(
+            "VS Code keybindings.json, раскладка Blender, GIMP shortcutsrc/menurc, Krita .shortcuts"
+          )
+          .size(12)
+          .style(text::secondary),
+        ]
+        .align_y(Alignment::Center)
         .spacing(SPACING),
+      ]
+      .push_maybe(status)
+      .spacing(5)
+      .into();
+    };
+
+    let header = row![
+      text!("{}: {}", keymap.format, keymap.file).size(14),
+      horizontal_space(),
+      button(text("Другой файл").size(14))
+        .on_press(Message::KeymapOpen)
+        .style(styles::button::rounding),
+      button(text("Закрыть").size(14))
+        .on_press(Message::KeymapClose)
+        .style(styles::button::rounding),
+    ]
+    .align_y(Alignment::Center)
+    .spacing(SPACING);
+
+    let target = match (state.button.id, state.button.is_stick) {
+      (0, _) => "Выберите кнопку, затем команду".to_string(),
+      (id, true) => format!("Назначить направлению стика #{id}"),
+      (id, false) => format!("Назначить кнопке #{id}"),
+    };
+
+    let found = keymap.search(&state.keymap_query);
+    let hidden = found.len().saturating_sub(MAX_COMMANDS);
+    let commands = column(found.into_iter().take(MAX_COMMANDS).map(|idx| {
+      let command = &keymap.commands[idx];
+      let title = match command.context.is_empty() {
+        true => command.title.clone(),
+        false => format!("{} · {}", command.title, command.context),
+      };
+      tooltip(
+        button(
+          row![
+            text(title).size(14).width(Length::Fill),
+            text(&command.keys).size(14).style(text::secondary),
+          ]
+          .spacing(SPACING),
+        )
+        .width(Length::Fill)
+        .on_press_maybe((state.button.id != 0).then_some(Message::KeymapAssign(idx)))
+        .style(styles::button::rounding),
+        container(text(&command.id).size(12))
+          .padding(5)
+          .style(container::rounded_box),
+        tooltip::Position::Top,
       )
-      .on_press(Message::DisallowWriteButtonCombination)
-      .into(),
+      .into()
+    }))
+    .spacing(5);
+
+    column![
+      toggle,
+      header,
+      text_input("Название команды или сочетание", &state.keymap_query)
+        .on_input(Message::KeymapSearch)
+        .size(14)
+        .style(styles::text_input::rounding),
+      text(target).size(12),
+      Scrollable::new(commands)
+        .direction(Direction::Vertical(Scrollbar::new()))
+        .height(Length::Fixed(200.)),
+    ]
+    .push_maybe((hidden > 0).then(|| {
+      text!("Ещё команд: {hidden}, уточните поиск")
+        .size(12)
+        .style(text::secondary)
+    }))
+    .push_maybe(status)
+    .spacing(5)
+    .into()
+  }
+
+  /**
+  Строит панель описания и подписей редактируемого профиля
+
+  Содержит описание, автора, теги, время создания и изменения, а для
+  выбранной кнопки — подпись, заметку и цвет. Метаданные хранятся только
+  в библиотеке и на устройство не записываются.
+
+  # Аргументы
+  * `state` - Состояние приложения
+  * `profile` - Редактируемый профиль библиотеки
+
+  # Возвращает
+  Кнопку открытия панели и, если панель открыта, поля метаданных
+  */
+  fn build_metadata<'a>(state: &'a State, profile: &'a Profile) -> Element<'a, Message> {
+    let meta = &profile.meta;
+    let toggle = button(
+      text(match state.meta_open {
+        true => "▾ Описание и подписи",
+        false => "▸ Описание и подписи",
+      })
+      .size(14),
+    )
+    .on_press(Message::MetaToggle)
+    .style(styles::button::transparent);
+
+    if !state.meta_open {
+      return toggle.into();
     }
+
+    let field = |label: &'static str, input: text_input::TextInput<'a, Message>| {
+      row![text(label).size(14).width(100), input.size(14)]
+        .align_y(Alignment::Center)
+        .spacing(SPACING)
+    };
+
+    let tags = row(meta.tags.iter().enumerate().map(|(i, tag)| {
+      button(text!("{tag} ×").size(12))
+        .on_press(Message::MetaTagRemove(i))
+        .style(styles::button::rounding)
+        .into()
+    }))
+    .push(
+      text_input("Новый тег", &state.meta_tag)
+        .on_input(Message::MetaTagInput)
+        .on_submit(Message::MetaTagAdd)
+        .size(12)
+        .width(150)
+        .style(styles::text_input::rounding),
+    )
+    .align_y(Alignment::Center)
+    .spacing(5)
+    .wrap();
+
+    let times = [("Создан", meta.created), ("Изменён", meta.modified)]
+      .into_iter()
+      .filter_map(|(label, time)| time.map(|time| format!("{label}: {}", format_timestamp(time))))
+      .collect::<Vec<_>>()
+      .join("   ");
+
+    let button_meta: Element<'_, Message> = match (state.button.id, state.button.is_stick) {
+      (0, _) | (_, true) => text("Выберите кнопку, чтобы задать подпись, заметку и цвет")
+        .size(12)
+        .style(text::secondary)
+        .into(),
+      (id, false) => {
+        let current = meta.button(id);
+        let caption = current.map_or("", |meta| meta.caption.as_str());
+        let note = current.map_or("", |meta| meta.note.as_str());
+        let color = current.and_then(|meta| meta.color);
+
+        column![
+          text!("Кнопка #{id}").size(14),
+          field(
+            "Подпись",
+            text_input(
+              &profile.get_button_label(id - 1, state.settings.layout),
+              caption
+            )
+            .on_input(Message::MetaCaption)
+            .style(styles::text_input::rounding),
+          ),
+          field(
+            "Заметка",
+            text_input("", note)
+              .on_input(Message::MetaNote)
+              .style(styles::text_input::rounding),
+          ),
+          row![
+            text("Цвет").size(14).width(100),
+            pick_list(ButtonColor::ALL, color, |color| Message::MetaColor(Some(
+              color
+            )))
+            .placeholder("Без цвета")
+            .text_size(14),
+            button(text("Без цвета").size(14))
+              .on_press_maybe(color.is_some().then_some(Message::MetaColor(None)))
+              .style(styles::button::rounding),
+          ]
+          .align_y(Alignment::Center)
+          .spacing(SPACING),
+        ]
+        .spacing(5)
+        .into()
+      }
+    };
+
+    column![
+      toggle,
+      field(
+        "Описание",
+        text_input("", &meta.description)
+          .on_input(Message::MetaDescription)
+          .style(styles::text_input::rounding),
+      ),
+      field(
+        "Автор",
+        text_input("", &meta.author)
+          .on_input(Message::MetaAuthor)
+          .style(styles::text_input::rounding),
+      ),
+      row![text("Теги").size(14).width(100), tags].spacing(SPACING),
+    ]
+    .push_maybe((!times.is_empty()).then(|| text(times).size(12).style(text::secondary)))
+    .push(button_meta)
+    .spacing(5)
+    .into()
+  }
+
+  /**
+  Строит панель истории изменений редактируемого профиля
+
+  Содержит кнопки отмены и повтора и список записей за сессию;
+  нажатие на запись возвращает профиль к этому состоянию.
+
+  # Аргументы
+  * `state` - Состояние приложения
+
+  # Возвращает
+  Панель истории или `None`, если профиль библиотеки не открыт
+  */
+  fn build_history(state: &State) -> Option<Element<'_, Message>> {
+    let history = state.history()?;
+
+    let controls = row![
+      button(text("Отменить").size(14))
+        .on_press_maybe(history.can_undo().then_some(Message::HistoryUndo))
+        .style(styles::button::rounding),
+      button(text("Повторить").size(14))
+        .on_press_maybe(history.can_redo().then_some(Message::HistoryRedo))
+        .style(styles::button::rounding),
+      text("Ctrl+Z / Ctrl+Shift+Z")
+        .size(12)
+        .style(text::secondary),
+    ]
+    .align_y(Alignment::Center)
+    .spacing(SPACING);
+
+    let entries = column(
+      history
+        .entries()
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(i, entry)| {
+          let title = text(entry.title()).size(14);
+          let title = match i.cmp(&history.position()) {
+            std::cmp::Ordering::Equal => title.style(text::primary),
+            std::cmp::Ordering::Greater => title.style(text::secondary),
+            std::cmp::Ordering::Less => title,
+          };
+
+          button(title)
+            .width(Length::Fill)
+            .on_press_maybe((i != history.position()).then_some(Message::HistoryJump(i)))
+            .style(styles::button::transparent)
+            .into()
+        }),
+    );
+
+    Some(
+      column![
+        controls,
+        Scrollable::new(entries).height(120).width(Length::Fill)
+      ]
+      .spacing(SPACING)
+      .into(),
+    )
+  }
+
+  /**
+  Строит экранную клавиатуру для редактируемой кнопки
+
+  Содержит:
+  - Выбор категории клавиш
+  - Клавиши выбранной категории; нажатие добавляет клавишу к комбинации
+  - Поле ввода комбинации текстом, например `Ctrl+Shift+F13`
+
+  # Аргументы
+  * `state` - Состояние приложения с редактируемой кнопкой
+  * `picker` - Состояние экранной клавиатуры
+
+  # Возвращает
+  Панель экранной клавиатуры
+  */
+  fn build_key_picker<'a>(state: &'a State, picker: &'a KeyPicker) -> Element<'a, Message> {
+    const KEYS_IN_ROW: usize = 12;
+
+    let target = match (state.button.id, state.button.is_stick) {
+      (0, _) => "Выберите кнопку".to_string(),
+      (id, true) => format!("Стик, направление #{id}"),
+      (id, false) => format!("Кнопка #{id}"),
+    };
+
+    let categories = row(KeyCategory::PICKER.into_iter().map(|category| {
+      button(text(category.to_string()).size(14))
+        .on_press_maybe(
+          (category != picker.category).then_some(Message::KeyPickerCategory(category)),
+        )
+        .style(styles::button::rounding)
+        .into()
+    }))
+    .spacing(SPACING)
+    .wrap();
+
+    let keys = picker.category.keys().collect::<Vec<_>>();
+    let keys = column(keys.chunks(KEYS_IN_ROW).map(|chunk| {
+      row(chunk.iter().map(|key| {
+        tooltip(
+          button(container(text(key.title).size(14)).center_x(Length::Fill))
+            .width(70)
+            .on_press_maybe((state.button.id != 0).then_some(Message::KeyPickerKey(key.code)))
+            .style(styles::button::rounding),
+          container(text!("{} · 0x{:02X}", key.name, key.code).size(12))
+            .padding(5)
+            .style(container::rounded_box),
+          tooltip::Position::Top,
+        )
+        .into()
+      }))
+      .spacing(SPACING)
+      .into()
+    }))
+    .spacing(SPACING);
+
+    let input = row![
+      text_input("Ctrl+Shift+F13", &picker.text)
+        .on_input(Message::KeyPickerInput)
+        .on_submit(Message::KeyPickerSubmit)
+        .style(styles::text_input::rounding),
+      mk_button!("Назначить", Message::KeyPickerSubmit),
+      mk_button!("Закрыть", Message::KeyPickerClose),
+    ]
+    .align_y(Alignment::Center)
+    .spacing(SPACING);
+
+    column![text(target), categories, keys, input]
+      .push_maybe(picker.error.as_deref().map(|error| text(error).size(14)))
+      .spacing(SPACING)
+      .into()
   }
 
   /**
@@ -232,14 +939,22 @@ impl Pages {
   # Возвращает
   Горизонтальную строку с 4 колонками кнопок
   */
-  fn build_keypad_grid<'a>(state: &'a State, profile: &'a Profile) -> Element<'a, Message> {
-    let col_1 = column((1..=4).map(|id| mk_keypad_button(state, id, profile))).spacing(SPACING);
+  fn build_keypad_grid<'a>(
+    state: &'a State,
+    profile: &'a Profile,
+    problems: &[Problem],
+  ) -> Element<'a, Message> {
+    let col_1 =
+      column((1..=4).map(|id| mk_keypad_button(state, id, profile, problems))).spacing(SPACING);
 
-    let col_2 = column((5..=8).map(|id| mk_keypad_button(state, id, profile))).spacing(SPACING);
+    let col_2 =
+      column((5..=8).map(|id| mk_keypad_button(state, id, profile, problems))).spacing(SPACING);
 
-    let col_3 = column((9..=12).map(|id| mk_keypad_button(state, id, profile))).spacing(SPACING);
+    let col_3 =
+      column((9..=12).map(|id| mk_keypad_button(state, id, profile, problems))).spacing(SPACING);
 
-    let col_4 = column((13..=16).map(|id| mk_keypad_button(state, id, profile))).spacing(SPACING);
+    let col_4 =
+      column((13..=16).map(|id| mk_keypad_button(state, id, profile, problems))).spacing(SPACING);
 
     row![col_1, col_2, col_3, col_4].spacing(SPACING).into()
   }
@@ -259,7 +974,11 @@ impl Pages {
   # Возвращает
   Вертикальную колонку с элементами управления стиком
   */
-  fn build_stick_controls<'a>(state: &'a State, profile: &'a Profile) -> Element<'a, Message> {
+  fn build_stick_controls<'a>(
+    state: &'a State,
+    profile: &'a Profile,
+    problems: &[Problem],
+  ) -> Element<'a, Message> {
     let deadzone_controls = match state.profile_on_keypad {
       true => column![
         text!("Мёртвая зона: {}%", state.profile.stick.deadzone).size(25),
@@ -280,14 +999,14 @@ impl Pages {
 
     let stick_buttons = column![
       deadzone_controls,
-      mk_button_stick(state, 1, profile), // Вверх
+      mk_button_stick(state, 1, profile, problems), // Вверх
       row![
-        mk_button_stick(state, 4, profile), // Влево
+        mk_button_stick(state, 4, profile, problems), // Влево
         Self::create_center_stick_button(state),
-        mk_button_stick(state, 2, profile), // Вправо
+        mk_button_stick(state, 2, profile, problems), // Вправо
       ]
       .spacing(SPACING),
-      mk_button_stick(state, 3, profile), // Вниз
+      mk_button_stick(state, 3, profile, problems), // Вниз
     ]
     .align_x(Alignment::Center)
     .spacing(SPACING);
@@ -318,73 +1037,83 @@ impl Pages {
 }
 
 /**
-Создает кнопку для клавиатурной панели с поддержкой правого клика
+Создает кнопку для клавиатурной панели с контекстным меню
 
 Каждая кнопка отображает:
-- Назначенную клавишу (метку) в центре
+- Подпись из метаданных профиля или назначенную клавишу (метку) в центре
 - Номер кнопки в правом нижнем углу
+- Отметку `!` в левом нижнем углу, если проверка нашла проблемы
+- Комбинацию и заметку во всплывающей подсказке, если задана подпись или заметка
 
 Поддерживает:
-- Левый клик: открытие настроек кнопки
-- Правый клик: очистка комбинации кнопки
+- Левый клик: запись комбинации кнопки, с Ctrl — выделение нескольких ячеек
+- Правый клик: контекстное меню (копирование, вставка, обмен, очистка)
+- Перетаскивание на другую ячейку: обмен назначениями, с Ctrl — копирование
 
 # Аргументы
 * `state` - Состояние приложения для определения стилей
 * `id` - Идентификатор кнопки (1-16)
 * `profile` - Профиль для получения метки кнопки
+* `problems` - Проблемы профиля, найденные проверкой
 
 # Возвращает
 Интерактивную кнопку клавиатуры
 */
-fn mk_keypad_button<'a>(state: &'a State, id: usize, profile: &Profile) -> Element<'a, Message> {
+fn mk_keypad_button<'a>(
+  state: &'a State,
+  id: usize,
+  profile: &Profile,
+  problems: &[Problem],
+) -> Element<'a, Message> {
   let button_index = id - 1;
-  let label = profile.get_button_label(button_index);
+  let keys = profile.get_button_label(button_index, state.settings.layout);
+  let meta = profile.meta.button(id);
+  let label = match profile.meta.caption(id) {
+    Some(caption) => caption.to_string(),
+    None => keys.clone(),
+  };
+  let content = mk_cell_grip(
+    mk_cell_content(label, id, problems, Target::Button(id)),
+    Cell::Button(id),
+  );
 
-  match state.profile_on_keypad {
-    true => button(
-      column![
-        container(text(label).size(15)).center(Length::Fill),
-        text!("#{}", id)
-          .size(10)
-          .align_x(Alignment::End)
-          .align_y(Alignment::End),
-      ]
-      .width(Length::Fill)
-      .height(Length::Fill),
-    )
+  let cell = button(content)
     .on_press(Message::GetButtonSettings(id, false))
     .height(BUTTON_HEIGH_PROFILE)
     .width(BUTTON_WIDTH_PROFILE)
     .style(move |theme: &Theme, status| {
       styles::button::active_write(theme, status, state, id, state.button.is_stick)
-    })
-    .into(),
-    false => mouse_area(
-      button(
-        column![
-          container(text(label).size(15)).center(Length::Fill),
-          text!("#{}", id)
-            .size(10)
-            .align_x(Alignment::End)
-            .align_y(Alignment::End),
-        ]
-        .width(Length::Fill)
-        .height(Length::Fill),
-      )
-      .on_press(Message::GetButtonSettings(id, false))
-      .height(BUTTON_HEIGH_PROFILE)
-      .width(BUTTON_WIDTH_PROFILE)
-      .style(move |theme: &Theme, status| {
-        styles::button::active_write(theme, status, state, id, state.button.is_stick)
-      }),
+    });
+
+  // Под подписью скрыта комбинация: она и заметка показываются в подсказке
+  let tip = meta
+    .map(
+      |meta| match (meta.caption.is_empty(), meta.note.is_empty()) {
+        (true, true) => String::new(),
+        (false, true) => keys.clone(),
+        (true, false) => meta.note.clone(),
+        (false, false) => format!("{keys}\n{}", meta.note),
+      },
+    )
+    .filter(|tip| !tip.is_empty());
+
+  let cell: Element<'_, Message> = match tip {
+    Some(tip) => tooltip(
+      cell,
+      container(text(tip).size(12))
+        .padding(5)
+        .style(container::rounded_box),
+      tooltip::Position::Top,
     )
-    .on_right_press(Message::ClearButtonCombination(id, false))
     .into(),
-  }
+    None => cell.into(),
+  };
+
+  mk_cell_area(cell, Cell::Button(id))
 }
 
 /**
-Создает кнопку направления стика с поддержкой правого клика
+Создает кнопку направления стика с контекстным меню
 
 Аналогична кнопкам клавиатуры, но предназначена для направлений стика.
 
@@ -392,56 +1121,182 @@ fn mk_keypad_button<'a>(state: &'a State, id: usize, profile: &Profile) -> Eleme
 * `state` - Состояние приложения для определения стилей
 * `id` - Идентификатор направления (1-4)
 * `profile` - Профиль для получения метки направления
+* `problems` - Проблемы профиля, найденные проверкой
 
 # Возвращает
 Интерактивную кнопку направления стика
 */
-fn mk_button_stick<'a>(state: &'a State, id: usize, profile: &Profile) -> Element<'a, Message> {
+fn mk_button_stick<'a>(
+  state: &'a State,
+  id: usize,
+  profile: &Profile,
+  problems: &[Problem],
+) -> Element<'a, Message> {
   let button_index = id - 1;
-  let label = profile.get_stick_label(button_index);
+  let label = profile.get_stick_label(button_index, state.settings.layout);
 
-  mouse_area(
-    button(
-      column![
-        container(text(label).size(15)).center(Length::Fill),
-        text!("#{}", id)
-          .size(10)
-          .align_x(Alignment::End)
-          .align_y(Alignment::End),
-      ]
-      .width(Length::Fill)
-      .height(Length::Fill),
-    )
+  mk_cell_area(
+    button(mk_cell_grip(
+      mk_cell_content(label, id, problems, Target::Stick(id)),
+      Cell::Stick(id),
+    ))
     .on_press(Message::GetButtonSettings(id, true))
     .height(BUTTON_HEIGH_PROFILE)
     .width(BUTTON_WIDTH_PROFILE)
     .style(move |theme: &Theme, status| {
       styles::button::stick::active_write(theme, status, state, id, state.button.is_stick)
-    }),
+    })
+    .into(),
+    Cell::Stick(id),
   )
-  .on_right_press(Message::ClearButtonCombination(id, true))
+}
+
+/**
+Оборачивает кнопку ячейки областью, отслеживающей курсор
+
+Наведение нужно для перетаскивания назначений между ячейками,
+правый клик открывает контекстное меню ячейки.
+
+# Аргументы
+* `content` - Кнопка ячейки
+* `cell` - Ячейка профиля
+
+# Возвращает
+Кнопку с обработкой наведения и правого клика
+*/
+fn mk_cell_area(content: Element<'_, Message>, cell: Cell) -> Element<'_, Message> {
+  mouse_area(content)
+    .on_enter(Message::CellHover(cell))
+    .on_exit(Message::CellLeave(cell))
+    .on_right_press(Message::CellMenu(cell))
+    .into()
+}
+
+/**
+Оборачивает содержимое кнопки ячейки областью перетаскивания
+
+Область перехватывает нажатие и отпускание левой кнопки мыши: перетаскивание
+начинается только с самой ячейки, а отпускание без сдвига курсора
+обрабатывается как клик по кнопке.
+
+# Аргументы
+* `content` - Содержимое кнопки ячейки
+* `cell` - Ячейка профиля
+*/
+fn mk_cell_grip(content: Element<'_, Message>, cell: Cell) -> Element<'_, Message> {
+  mouse_area(content)
+    .on_press(Message::CellDragStart(cell))
+    .on_release(Message::CellDragEnd(cell))
+    .into()
+}
+
+/**
+Создает содержимое кнопки кейпада или стика
+
+Если проверка нашла проблемы назначения, в левом нижнем углу выводится
+отметка `!` (красная для ошибок) с описанием проблем во всплывающей подсказке.
+
+# Аргументы
+* `label` - Подпись назначенной комбинации
+* `id` - Номер кнопки или направления
+* `problems` - Проблемы профиля, найденные проверкой
+* `target` - Элемент профиля, проблемы которого отображаются
+
+# Возвращает
+Колонку с подписью, отметкой проблем и номером
+*/
+fn mk_cell_content<'a>(
+  label: String,
+  id: usize,
+  problems: &[Problem],
+  target: Target,
+) -> Element<'a, Message> {
+  let problems = problems
+    .iter()
+    .filter(|problem| problem.target == target)
+    .collect::<Vec<_>>();
+
+  let marker = problems
+    .iter()
+    .map(|problem| problem.severity)
+    .max()
+    .map(|severity| {
+      let tip = problems
+        .iter()
+        .map(|problem| problem.text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+      let style = match severity {
+        Severity::Error => text::danger,
+        Severity::Warning => text::primary,
+      };
+
+      tooltip(
+        text("!").size(12).style(style),
+        container(text(tip).size(12))
+          .padding(5)
+          .style(container::rounded_box),
+        tooltip::Position::Top,
+      )
+    });
+
+  column![
+    container(text(label).size(15)).center(Length::Fill),
+    row![]
+      .push_maybe(marker)
+      .push(horizontal_space())
+      .push(text!("#{}", id).size(10))
+      .align_y(Alignment::End),
+  ]
+  .width(Length::Fill)
+  .height(Length::Fill)
   .into()
 }
 
+/**
+Создает список проблем имени профиля под полем ввода
+
+# Аргументы
+* `problems` - Проблемы профиля, найденные проверкой
+
+# Возвращает
+Текст с проблемами имени или `None`, если их нет
+*/
+fn name_problems<'a>(problems: &[Problem]) -> Option<Element<'a, Message>> {
+  let text_problems = problems
+    .iter()
+    .filter(|problem| problem.target == Target::Name)
+    .map(|problem| problem.text.as_str())
+    .collect::<Vec<_>>();
+
+  (!text_problems.is_empty()).then(|| {
+    text(text_problems.join(". "))
+      .size(14)
+      .style(text::primary)
+      .into()
+  })
+}
+
 /**
 Создает строку с кнопками управления профилем для быстрого доступа
 
 Каждая строка содержит:
-- Основную кнопку профиля с номером и типом (ОЗУ/ПЗУ)
-- Кнопку загрузки/выгрузки профиля
+- Кнопку профиля ОЗУ с номером (со звёздочкой, если профиль не сохранён в ПЗУ)
+- Кнопку профиля ПЗУ с номером (доступна после чтения ПЗУ)
+- Кнопку записи профиля в ОЗУ/ПЗУ (в зависимости от режима)
 
 # Аргументы
 * `state` - Состояние приложения для определения режима (ОЗУ/ПЗУ)
 * `num` - Номер профиля (1-4)
 
 # Возвращает
-Горизонтальную строку с двумя кнопками управления профилем
+Горизонтальную строку с кнопками управления профилем
 */
 fn mk_button_profile_row<'a>(state: &'a State, id: usize) -> Element<'a, Message> {
-  let (profile_type, write_message) = if state.is_rom {
-    ("ПЗУ", Message::ProfileActiveWriteToRom(id as u8))
+  let write_message = if state.is_rom {
+    Message::ProfileActiveWriteToRom(id as u8)
   } else {
-    ("ОЗУ", Message::ProfileActiveWriteToRam(id as u8))
+    Message::ProfileActiveWriteToRam(id as u8)
   };
 
   let block = if let Some(pr_num) = state.request_active_profile_id
@@ -452,15 +1307,35 @@ fn mk_button_profile_row<'a>(state: &'a State, id: usize) -> Element<'a, Message
     column![button("").width(10).style(styles::button::transparent)]
   };
 
-  row![
+  let rom_button = button(text!("ПЗУ {}", id).center())
+    .on_press_maybe((state.profiles_rom_vec.len() >= id).then_some(Message::ProfileLoadRom(id)))
+    .width(70)
+    .height(35)
+    .style(move |theme: &Theme, status| styles::button::rom_profile(theme, status, state, id));
+
+  let unsaved = state.is_profile_unsaved(id);
+  let ram_button = button(text!("ОЗУ {}{}", id, if unsaved { "*" } else { "" }).center())
+    .on_press(Message::ProfileLoadKeypad(id))
+    .width(70)
+    .height(35)
+    .style(move |theme: &Theme, status| styles::button::active_profile(theme, status, state, id));
+
+  let ram_button: Element<'a, Message> = match unsaved {
+    true => tooltip(
+      ram_button,
+      container("Изменения не сохранены в ПЗУ")
+        .padding(PADDING)
+        .style(container::rounded_box),
+      tooltip::Position::Bottom,
+    )
+    .into(),
+    false => ram_button.into(),
+  };
+
+  let slot_row = row![
     block,
-    button(text!("{} {}", profile_type, id).center())
-      .on_press(Message::ProfileLoadKeypad(id))
-      .width(80)
-      .height(35)
-      .style(move |theme: &Theme, status| {
-        styles::button::active_profile(theme, status, state, id)
-      }),
+    ram_button,
+    rom_button,
     button(
       svg(svg::Handle::from_memory(Icon::Download.icon()))
         .height(Length::Fill)
@@ -471,6 +1346,67 @@ fn mk_button_profile_row<'a>(state: &'a State, id: usize) -> Element<'a, Message
     .on_press(write_message)
     .style(styles::button::rounding)
   ]
-  .spacing(SPACING)
-  .into()
+  .spacing(SPACING);
+
+  column![slot_row]
+    .push_maybe(mk_slot_sync(state, id))
+    .spacing(5)
+    .into()
+}
+
+/**
+Создает строку состояния связи ячейки с профилем библиотеки
+
+При расхождении доступны кнопки записи профиля библиотеки в ячейку
+и обновления профиля библиотеки из ячейки.
+
+# Аргументы
+* `state` - Состояние приложения
+* `id` - Номер ячейки (1-4)
+
+# Возвращает
+Строку состояния или `None`, если ячейка не связана с профилем библиотеки
+*/
+fn mk_slot_sync(state: &State, id: usize) -> Option<Element<'_, Message>> {
+  let (status, _) = state.slot_sync(id)?;
+  let diverged = status.is_diverged();
+
+  let action = |label: &'static str, tip: &'static str, message: Message| {
+    tooltip(
+      button(text(label).size(12))
+        .padding([2, 6])
+        .on_press_maybe((diverged && state.keypad.is_open).then_some(message))
+        .style(styles::button::rounding),
+      container(text(tip).size(12))
+        .padding(5)
+        .style(container::rounded_box),
+      tooltip::Position::Bottom,
+    )
+  };
+
+  let row = row![
+    text(status.to_string())
+      .size(12)
+      .width(Length::Fill)
+      .style(match diverged {
+        true => text::primary,
+        false => text::secondary,
+      }),
+  ];
+  let row = match diverged {
+    true => row
+      .push(action(
+        "→",
+        "Записать профиль библиотеки в ячейку",
+        Message::SlotPush(id),
+      ))
+      .push(action(
+        "←",
+        "Обновить профиль библиотеки из ячейки",
+        Message::SlotPull(id),
+      )),
+    false => row,
+  };
+
+  Some(row.align_y(Alignment::Center).spacing(5).into())
 }
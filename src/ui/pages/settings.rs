@@ -1,15 +1,28 @@
 use iced::{
   Alignment, Element, Length,
-  widget::{button, center, column, container, text},
+  widget::{
+    button, center, checkbox, column, container, horizontal_space, pick_list, row, scrollable,
+    text, text_input,
+  },
 };
 
 use crate::{
-  State, mk_button,
+  State,
+  data::{
+    backup::Backup,
+    capture::CaptureMode,
+    layout::KeyboardLayout,
+    name::NameEncoding,
+    readable::{ExportSelection, ProfileFormat},
+    settings::StorageBackend,
+  },
+  mk_button,
   ui::{
     pages::Pages,
     styles::{self, BUTTON_HEIGH, HEADING_SIZE, PADDING, SPACING},
     update::Message,
   },
+  utils::time::format_timestamp,
 };
 
 impl Pages {
@@ -30,9 +43,11 @@ impl Pages {
     state: &'a State,
     screen_name: Element<'a, Message>,
   ) -> Element<'a, Message> {
-    let settings_content = match state.stick_callibrate {
-      true => Self::build_stick_calibration_ui(state),
-      false => Self::build_regular_settings_ui(),
+    let settings_content = match (state.stick_callibrate, &state.backup_restore, &state.export) {
+      (true, _, _) => Self::build_stick_calibration_ui(state),
+      (false, Some(backup), _) => Self::build_backup_restore_ui(state, backup),
+      (false, None, Some(export)) => Self::build_export_ui(state, export),
+      (false, None, None) => Self::build_regular_settings_ui(state),
     };
 
     column![screen_name, center(settings_content)]
@@ -47,11 +62,16 @@ impl Pages {
   Содержит кнопки для:
   - Перезагрузки в bootloader
   - Запуска калибровки стика
+  - Импорта/экспорта профилей, в том числе раскладок QMK/VIA
+  - Создания резервной копии кейпада и восстановления из неё
+
+  # Аргументы
+  * `state` - Состояние приложения с результатом операций резервного копирования
 
   # Возвращает
   Вертикальную колонку с кнопками системных настроек
   */
-  fn build_regular_settings_ui<'a>() -> Element<'a, Message> {
+  fn build_regular_settings_ui(state: &State) -> Element<'_, Message> {
     let reboot_button = mk_button!(
       container("Перезагрузить в bootloader").center_x(Length::Fill),
       Message::RebootToBootloader
@@ -72,16 +92,134 @@ impl Pages {
 
     let profile_export = mk_button!(
       container("Экспорт профилей").center_x(Length::Fill),
-      Message::ProfilesExport
+      Message::ExportOpen
+    )
+    .width(Length::Fill);
+
+    let qmk = row![
+      mk_button!(
+        container("Импорт QMK/VIA").center_x(Length::Fill),
+        Message::QmkImport
+      )
+      .width(Length::Fill),
+      mk_button!(
+        container("Экспорт в QMK").center_x(Length::Fill),
+        Message::QmkExport
+      )
+      .width(Length::Fill),
+    ]
+    .spacing(SPACING);
+
+    let backup_create = mk_button!(
+      container("Создать резервную копию").center_x(Length::Fill),
+      Message::BackupCreate(false)
+    )
+    .width(Length::Fill);
+
+    let backup_restore = mk_button!(
+      container("Восстановить из копии").center_x(Length::Fill),
+      Message::BackupRestoreOpen
+    )
+    .width(Length::Fill);
+
+    let library_restore = mk_button!(
+      container("Восстановить библиотеку").center_x(Length::Fill),
+      Message::LibraryRestore
     )
     .width(Length::Fill);
 
+    let storage = column![
+      text(match &state.settings.storage {
+        StorageBackend::Single => "Профили: единый файл библиотеки".to_string(),
+        StorageBackend::Directory(dir) => format!("Профили: {}", dir.display()),
+      })
+      .size(14),
+      row![
+        mk_button!(
+          container("Папка профилей").center_x(Length::Fill),
+          Message::StorageSelectDirectory
+        )
+        .width(Length::Fill),
+        button(container("Единый файл").center_x(Length::Fill))
+          .height(BUTTON_HEIGH)
+          .width(Length::Fill)
+          .on_press_maybe(
+            (state.settings.storage != StorageBackend::Single)
+              .then_some(Message::StorageChanged(StorageBackend::Single))
+          )
+          .style(styles::button::rounding),
+      ]
+      .spacing(SPACING),
+    ]
+    .spacing(SPACING);
+
+    let capture = column![
+      text("Запись комбинаций").size(14),
+      pick_list(
+        CaptureMode::ALL,
+        Some(state.settings.capture.mode),
+        Message::CaptureModeSelect
+      )
+      .width(Length::Fill),
+      checkbox(
+        "Модификаторы первыми",
+        state.settings.capture.normalize_modifiers
+      )
+      .on_toggle(Message::CaptureNormalizeToggle),
+    ]
+    .spacing(SPACING);
+
+    let lint = column![
+      text("Системные сочетания через запятую").size(14),
+      text_input("Alt+Tab, Win+L", &state.lint_shortcuts_text)
+        .on_input(Message::LintShortcutsInput)
+        .style(styles::text_input::rounding),
+    ]
+    .spacing(SPACING);
+
+    let name_encoding = column![
+      text("Кодировка имён профилей").size(14),
+      pick_list(
+        NameEncoding::ALL,
+        Some(state.settings.name_encoding),
+        Message::NameEncodingSelect
+      )
+      .width(Length::Fill),
+    ]
+    .spacing(SPACING);
+
+    let layout = column![
+      text("Раскладка подписей клавиш").size(14),
+      pick_list(
+        KeyboardLayout::ALL,
+        Some(state.settings.layout),
+        Message::LayoutSelect
+      )
+      .width(Length::Fill),
+    ]
+    .spacing(SPACING);
+
+    let backup_status = state
+      .backup_status
+      .as_deref()
+      .map(|status| text(status).size(14));
+
     column![
       reboot_button,
       calibration_button,
       profile_import,
-      profile_export
+      profile_export,
+      qmk,
+      backup_create,
+      backup_restore,
+      library_restore,
+      storage,
+      capture,
+      lint,
+      name_encoding,
+      layout,
     ]
+    .push_maybe(backup_status)
     .width(270)
     .align_x(Alignment::Center)
     .spacing(SPACING)
@@ -191,6 +329,156 @@ impl Pages {
     .into()
   }
 
+  /**
+  Создает диалог выбора данных для восстановления из резервной копии
+
+  Для каждого из 4 профилей можно отдельно выбрать восстановление ОЗУ и ПЗУ,
+  а также отметить восстановление калибровки стика.
+
+  # Аргументы
+  * `state` - Состояние приложения с текущим выбором
+  * `backup` - Открытая резервная копия
+
+  # Возвращает
+  Интерфейс диалога восстановления
+  */
+  fn build_backup_restore_ui<'a>(state: &'a State, backup: &'a Backup) -> Element<'a, Message> {
+    let selection = &state.backup_selection;
+
+    let info = text!(
+      "Кейпад №{}, прошивка {}, копия от {}",
+      backup.device.serial_num,
+      backup.device.firmware_version,
+      format_timestamp(backup.created)
+    );
+
+    let slots = column((1..=4).map(|id| {
+      let ram_name = backup.ram[id - 1].name.trim_end_matches('\0');
+      let rom_name = backup.rom[id - 1].name.trim_end_matches('\0');
+
+      row![
+        text!("Профиль {}", id).width(100),
+        checkbox(format!("ОЗУ: {ram_name}"), selection.ram[id - 1])
+          .on_toggle(move |_| Message::BackupRestoreToggleRam(id))
+          .width(Length::FillPortion(1)),
+        checkbox(format!("ПЗУ: {rom_name}"), selection.rom[id - 1])
+          .on_toggle(move |_| Message::BackupRestoreToggleRom(id))
+          .width(Length::FillPortion(1)),
+      ]
+      .spacing(SPACING)
+      .into()
+    }))
+    .spacing(SPACING);
+
+    let calibration = checkbox("Калибровка стика", selection.calibration)
+      .on_toggle(|_| Message::BackupRestoreToggleCalibration);
+
+    let buttons = row![
+      horizontal_space(),
+      mk_button!("Отмена", Message::BackupRestoreCancel),
+      mk_button!("Восстановить", Message::BackupRestoreApply),
+    ]
+    .spacing(SPACING);
+
+    column![
+      Self::create_calibration_header("Восстановление из резервной копии"),
+      Self::create_calibration_box(column![info, slots, calibration, buttons].spacing(SPACING)),
+    ]
+    .width(600)
+    .into()
+  }
+
+  /**
+  Создает диалог выбора данных для экспорта в файл
+
+  Можно выбрать профили библиотеки, наборы и профили ОЗУ кейпада,
+  а также формат файла.
+
+  # Аргументы
+  * `state` - Состояние приложения
+  * `export` - Текущий выбор экспорта
+
+  # Возвращает
+  Интерфейс диалога экспорта
+  */
+  fn build_export_ui<'a>(state: &'a State, export: &'a ExportSelection) -> Element<'a, Message> {
+    let profiles = column(
+      state
+        .profiles_local_vec
+        .iter()
+        .enumerate()
+        .map(|(idx, profile)| {
+          checkbox(
+            &profile.name,
+            export.profiles.get(idx).copied().unwrap_or(false),
+          )
+          .on_toggle(move |_| Message::ExportToggleProfile(idx))
+          .into()
+        }),
+    )
+    .spacing(SPACING);
+
+    let banks = column(state.banks.iter().enumerate().map(|(idx, bank)| {
+      checkbox(&bank.name, export.banks.get(idx).copied().unwrap_or(false))
+        .on_toggle(move |_| Message::ExportToggleBank(idx))
+        .into()
+    }))
+    .spacing(SPACING);
+
+    let slots = column(
+      state
+        .profiles_keypad_vec
+        .iter()
+        .enumerate()
+        .map(|(i, profile)| {
+          checkbox(
+            format!("Ячейка {}: {}", i + 1, profile.name.trim_end_matches('\0')),
+            export.slots[i],
+          )
+          .on_toggle(move |_| Message::ExportToggleSlot(i + 1))
+          .into()
+        }),
+    )
+    .spacing(SPACING);
+
+    let lists = row![
+      column![text("Профили"), scrollable(profiles).height(200)]
+        .spacing(SPACING)
+        .width(Length::FillPortion(1)),
+      column![text("Наборы"), scrollable(banks).height(200)]
+        .spacing(SPACING)
+        .width(Length::FillPortion(1)),
+      column![text("Кейпад"), slots]
+        .spacing(SPACING)
+        .width(Length::FillPortion(1)),
+    ]
+    .spacing(SPACING);
+
+    let buttons = row![
+      text("Формат"),
+      pick_list(
+        ProfileFormat::ALL,
+        Some(export.format),
+        Message::ExportSelectFormat
+      ),
+      horizontal_space(),
+      mk_button!("Отмена", Message::ExportCancel),
+      button("Экспортировать")
+        .height(BUTTON_HEIGH)
+        .on_press_maybe((!export.is_empty()).then_some(Message::ExportApply))
+        .style(styles::button::rounding),
+    ]
+    .align_y(Alignment::Center)
+    .spacing(SPACING);
+
+    column![
+      Self::create_calibration_header("Экспорт в файл"),
+      Self::create_calibration_box(column![lists, buttons].spacing(SPACING)),
+    ]
+    .width(700)
+    .into()
+  }
+
   /**
   Создает стандартизированный заголовок для экранов калибровки
 
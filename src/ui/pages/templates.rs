@@ -0,0 +1,242 @@
+use iced::{
+  Alignment, Element, Length, Theme,
+  widget::{
+    Scrollable, button, column, container, horizontal_rule, pick_list, row,
+    scrollable::{Direction, Scrollbar},
+    text, vertical_rule,
+  },
+};
+
+use crate::{
+  State,
+  data::templates::{self, Template, TemplateKind},
+  mk_button,
+  ui::{
+    pages::Pages,
+    styles::{self, BUTTON_HEIGH, PADDING, RULE_WIDTH, SPACING},
+    update::Message,
+  },
+};
+
+impl Pages {
+  /**
+  Создает интерфейс экрана шаблонов профилей
+
+  Разделен на две панели:
+  - Левая панель: шаблоны, сгруппированные по виду
+  - Правая панель: просмотр и применение выбранного шаблона
+
+  # Аргументы
+  * `state` - Состояние приложения
+  * `screen_name` - Заголовок экрана
+
+  # Возвращает
+  Элемент интерфейса экрана шаблонов
+  */
+  pub fn templates_screen<'a>(
+    state: &'a State,
+    screen_name: Element<'a, Message>,
+  ) -> Element<'a, Message> {
+    let list = Self::build_templates_list(state, screen_name);
+
+    let preview = match state.template_id.and_then(|id| state.templates.get(id)) {
+      Some(template) => Self::build_template_preview(state, template),
+      None => container(text("Выберите шаблон"))
+        .center(Length::Fill)
+        .into(),
+    };
+
+    row![list, vertical_rule(RULE_WIDTH), preview].into()
+  }
+
+  /**
+  Строит левую панель со списком шаблонов
+
+  # Аргументы
+  * `state` - Состояние приложения
+  * `screen_name` - Заголовок экрана
+
+  # Возвращает
+  Колонку с шаблонами по видам, ошибками загрузки и путём к своим шаблонам
+  */
+  fn build_templates_list<'a>(
+    state: &'a State,
+    screen_name: Element<'a, Message>,
+  ) -> Element<'a, Message> {
+    let groups = column(TemplateKind::ALL.into_iter().filter_map(|kind| {
+      let buttons = state
+        .templates
+        .iter()
+        .enumerate()
+        .filter(|(_, template)| template.kind == kind)
+        .map(|(idx, template)| {
+          let title = match template.path {
+            Some(_) => format!("{} (свой)", template.title),
+            None => template.title.clone(),
+          };
+          let is_selected = state.template_id == Some(idx);
+          mk_button!(
+            container(text(title)).center_x(Length::Fill),
+            Message::TemplateSelect(idx)
+          )
+          .width(Length::Fill)
+          .style(move |theme: &Theme, status| {
+            styles::button::selected(theme, styles::button::rounding(theme, status), is_selected)
+          })
+          .into()
+        })
+        .collect::<Vec<Element<'_, Message>>>();
+
+      (!buttons.is_empty()).then(|| {
+        column![
+          text(kind.to_string()).size(14),
+          column(buttons).spacing(SPACING)
+        ]
+        .spacing(SPACING)
+        .into()
+      })
+    }))
+    .spacing(SPACING * 2);
+
+    let errors = (!state.template_errors.is_empty()).then(|| {
+      column(
+        state
+          .template_errors
+          .iter()
+          .map(|error| text(error).size(12).into()),
+      )
+      .spacing(SPACING)
+    });
+
+    let user_dir = text!("Свои шаблоны: {}", templates::user_dir().display()).size(12);
+
+    column![
+      screen_name,
+      Scrollable::new(groups)
+        .direction(Direction::Vertical(Scrollbar::new()))
+        .spacing(SPACING)
+        .height(Length::Fill),
+      horizontal_rule(RULE_WIDTH),
+    ]
+    .push_maybe(errors)
+    .push(user_dir)
+    .align_x(Alignment::Center)
+    .spacing(SPACING)
+    .padding(PADDING)
+    .width(260)
+    .into()
+  }
+
+  /**
+  Строит панель просмотра шаблона
+
+  Содержит:
+  - Название, описание и теги шаблона
+  - Сетку 4×4 с подписями и комбинациями кнопок
+  - Направления стика
+  - Кнопки создания профиля и записи в ячейку кейпада
+
+  # Аргументы
+  * `state` - Состояние приложения
+  * `template` - Выбранный шаблон
+
+  # Возвращает
+  Вертикальную колонку с содержимым шаблона и действиями
+  */
+  fn build_template_preview<'a>(state: &'a State, template: &'a Template) -> Element<'a, Message> {
+    let header = column![text(&template.title).size(25)]
+      .push_maybe((!template.description.is_empty()).then(|| text(&template.description)))
+      .push_maybe(
+        (!template.tags.is_empty()).then(|| text!("Теги: {}", template.tags.join(", ")).size(12)),
+      )
+      .align_x(Alignment::Center)
+      .spacing(SPACING);
+
+    let grid = (!template.buttons.is_empty()).then(|| {
+      column(template.buttons.chunks(4).map(|line| {
+        row(line.iter().map(|button| {
+          container(
+            column![
+              text(&button.caption),
+              text(&button.keys).size(12).style(text::secondary),
+            ]
+            .align_x(Alignment::Center),
+          )
+          .center_x(Length::Fill)
+          .height(60)
+          .padding(PADDING)
+          .style(styles::container::round_bordered_box)
+          .into()
+        }))
+        .spacing(SPACING)
+        .into()
+      }))
+      .spacing(SPACING)
+      .width(600)
+    });
+
+    let stick = template.stick.as_ref().map(|stick| {
+      let mut line = format!(
+        "Стик: вверх {}, вправо {}, вниз {}, влево {}",
+        stick.up, stick.right, stick.down, stick.left
+      );
+      if let Some(deadzone) = stick.deadzone {
+        line.push_str(&format!(", мёртвая зона {deadzone}%"));
+      }
+      text(line)
+    });
+
+    let slot = row![
+      text("Ячейка"),
+      pick_list(
+        [1, 2, 3, 4],
+        Some(state.template_slot),
+        Message::TemplateSlotSelect
+      )
+      .width(80),
+      button(
+        container(match state.is_rom {
+          true => "Записать в ячейку ПЗУ",
+          false => "Записать в ячейку ОЗУ",
+        })
+        .center_x(Length::Fill)
+      )
+      .height(BUTTON_HEIGH)
+      .width(Length::Fill)
+      .on_press_maybe(state.keypad.is_open.then_some(Message::TemplateToSlot))
+      .style(styles::button::rounding),
+    ]
+    .align_y(Alignment::Center)
+    .spacing(SPACING);
+
+    let actions = column![
+      mk_button!(
+        container(match template.kind {
+          TemplateKind::Stick => "Создать профиль со стиком",
+          _ => "Создать профиль",
+        })
+        .center_x(Length::Fill),
+        Message::TemplateNewProfile
+      )
+      .width(Length::Fill),
+      slot,
+    ]
+    .spacing(SPACING)
+    .width(400);
+
+    let content = column![header]
+      .push_maybe(grid)
+      .push_maybe(stick)
+      .push(horizontal_rule(RULE_WIDTH))
+      .push(actions)
+      .push_maybe(state.template_status.as_deref().map(text))
+      .align_x(Alignment::Center)
+      .spacing(SPACING)
+      .padding(PADDING)
+      .width(Length::Fill);
+
+    Scrollable::new(content)
+      .direction(Direction::Vertical(Scrollbar::new()))
+      .into()
+  }
+}
@@ -1,6 +1,6 @@
 use iced::{Border, Color, Shadow, Theme, color, widget::button};
 
-use crate::{State, ui::styles::BORDER_RADIUS};
+use crate::{State, data::cells::Cell, ui::styles::BORDER_RADIUS};
 
 /**
 Создает стиль кнопки с закругленными углами
@@ -61,7 +61,7 @@ pub fn active_profile(
   number: usize,
 ) -> button::Style {
   match state.active_profile_id {
-    Some(i) => match i == number && state.profile_on_keypad {
+    Some(i) => match i == number && state.profile_on_keypad && state.rom_profile_id.is_none() {
       true => button::Style {
         background: Some(iced::Background::Color(color!(0x778fe6))),
         border: Border {
@@ -82,6 +82,45 @@ pub fn active_profile(
   }
 }
 
+/**
+Создает стиль кнопки профиля ПЗУ с подсветкой
+
+Выделяет просматриваемый профиль ПЗУ синим цветом.
+
+# Аргументы
+* `theme` - Текущая тема приложения
+* `status` - Состояние кнопки (нажата, наведена и т.д.)
+* `state` - Состояние приложения для определения просматриваемого профиля ПЗУ
+* `number` - Номер профиля (1-4)
+
+# Возвращает
+Стиль кнопки с подсветкой для просматриваемого профиля ПЗУ
+*/
+pub fn rom_profile(
+  theme: &Theme,
+  status: button::Status,
+  state: &State,
+  number: usize,
+) -> button::Style {
+  match state.rom_profile_id == Some(number) {
+    true => button::Style {
+      background: Some(iced::Background::Color(color!(0x778fe6))),
+      border: Border {
+        radius: BORDER_RADIUS.into(),
+        ..Default::default()
+      },
+      ..button::primary(theme, status)
+    },
+    false => button::Style {
+      border: Border {
+        radius: BORDER_RADIUS.into(),
+        ..Default::default()
+      },
+      ..button::primary(theme, status)
+    },
+  }
+}
+
 /**
 Создает стиль кнопки активного профиля с подсветкой
 
@@ -131,11 +170,45 @@ pub fn active_profile_id(
   }
 }
 
+/**
+Создает стиль кнопки набора профилей
+
+Выделяет синим цветом набор, открытый для редактирования.
+
+# Аргументы
+* `theme` - Текущая тема приложения
+* `status` - Состояние кнопки (нажата, наведена и т.д.)
+* `state` - Состояние приложения для определения открытого набора
+* `idx` - Индекс набора
+
+# Возвращает
+Стиль кнопки с подсветкой для открытого набора
+*/
+pub fn active_bank(
+  theme: &Theme,
+  status: button::Status,
+  state: &State,
+  idx: usize,
+) -> button::Style {
+  match state.bank_id == Some(idx) {
+    true => button::Style {
+      background: Some(iced::Background::Color(color!(0x778fe6))),
+      border: Border {
+        radius: BORDER_RADIUS.into(),
+        ..Default::default()
+      },
+      ..button::primary(theme, status)
+    },
+    false => rounding(theme, status),
+  }
+}
+
 /**
 Создает стиль кнопки в режиме записи комбинации
 
 Выделяет кнопку синим цветом с увеличенными закруглениями,
-когда она находится в режиме записи комбинации клавиш.
+когда она находится в режиме записи комбинации клавиш. В остальное
+время фон кнопки окрашивается цветом из метаданных профиля, если он задан.
 
 # Аргументы
 * `theme` - Текущая тема приложения
@@ -154,7 +227,7 @@ pub fn active_write(
   id: usize,
   is_stick: bool,
 ) -> button::Style {
-  match (state.button.id == id, state.allow_write, is_stick) {
+  let style = match (state.button.id == id, state.allow_write, is_stick) {
     (true, true, false) => button::Style {
       background: Some(iced::Background::Color(color!(0x778fe6))),
       border: Border {
@@ -163,19 +236,52 @@ pub fn active_write(
       },
       ..button::primary(theme, status)
     },
-    _ => button::Style {
+    _ => {
+      let base = button::primary(theme, status);
+      let color = state.profile.meta.button(id).and_then(|meta| meta.color);
+      button::Style {
+        background: color
+          .map(|color| iced::Background::Color(color.color()))
+          .or(base.background),
+        border: Border {
+          radius: (BORDER_RADIUS * 2.).into(),
+          ..Default::default()
+        },
+        ..base
+      }
+    }
+  };
+  selected(theme, style, state.cells.is_selected(Cell::Button(id)))
+}
+
+/**
+Добавляет рамку выделенной ячейке
+
+# Аргументы
+* `theme` - Текущая тема приложения
+* `style` - Стиль кнопки ячейки
+* `is_selected` - Признак, что ячейка выделена
+
+# Возвращает
+Стиль с рамкой для выделенной ячейки или исходный стиль
+*/
+pub fn selected(theme: &Theme, style: button::Style, is_selected: bool) -> button::Style {
+  match is_selected {
+    true => button::Style {
       border: Border {
-        radius: (BORDER_RADIUS * 2.).into(),
-        ..Default::default()
+        color: theme.extended_palette().background.base.text,
+        width: 2.,
+        ..style.border
       },
-      ..button::primary(theme, status)
+      ..style
     },
+    false => style,
   }
 }
 
 /// Модуль стилей для кнопок стика
 pub mod stick {
-  use crate::{State, ui::styles::BORDER_RADIUS};
+  use crate::{State, data::cells::Cell, ui::styles::BORDER_RADIUS};
   use iced::{Border, Theme, color, widget::button};
 
   /**
@@ -201,7 +307,7 @@ pub mod stick {
     id: usize,
     is_stick: bool,
   ) -> button::Style {
-    match (state.button.id == id, state.allow_write, is_stick) {
+    let style = match (state.button.id == id, state.allow_write, is_stick) {
       (true, true, true) => button::Style {
         background: Some(iced::Background::Color(color!(0x778fe6))),
         border: Border {
@@ -217,6 +323,7 @@ pub mod stick {
         },
         ..button::primary(theme, status)
       },
-    }
+    };
+    super::selected(theme, style, state.cells.is_selected(Cell::Stick(id)))
   }
 }
@@ -1,11 +1,15 @@
 use std::time::Duration;
 
-use iced::{Event, Subscription, event, window};
+use iced::{
+  Event, Subscription, event,
+  keyboard::{Key, key::Named},
+  mouse, window,
+};
 use log::{debug, info, trace};
 
 use crate::{
   State,
-  data::code::CodeAscii,
+  data::{code::by_physical, settings::StorageBackend},
   hardware::buffers::BuffersIO,
   ui::{Message, pages::Pages},
 };
@@ -28,7 +32,7 @@ impl State {
 
     // Подписка на события окна: перемещение, изменение размера,
     // а также сохранение параметров при фокусе/расфокусе/закрытии
-    let window = event::listen_with(|event, _status, _id| match event {
+    let window = event::listen_with(|event, _status, id| match event {
       Event::Window(event) => match event {
         #[cfg(windows)]
         window::Event::Moved(point) => {
@@ -39,44 +43,94 @@ impl State {
           trace!("subscription: window: resized: {size:#?}");
           Some(Message::WindowResized(size.width, size.height))
         }
-        window::Event::Focused | window::Event::Unfocused | window::Event::CloseRequested => {
+        window::Event::Focused | window::Event::Unfocused => {
           info!("subscription: window: сохранение положения окна");
           Some(Message::WindowSettingsSave)
         }
+        window::Event::CloseRequested => {
+          info!("subscription: window: запрос закрытия окна");
+          Some(Message::WindowCloseRequested(id))
+        }
         _ => None,
       },
       _ => None,
     });
 
     // Захват нажатий клавиатуры для набора комбинации
-    // Активен только когда разрешён режим записи комбинации
-    let keyboard = match self.allow_write {
-      true => event::listen_with(|event, _status, _id| match event {
-        Event::Keyboard(event) => {
-          if let iced::keyboard::Event::KeyPressed {
-            key, physical_key, ..
-          } = event
-          {
-            match (key, physical_key) {
-              (iced::keyboard::Key::Named(named), iced::keyboard::key::Physical::Code(code)) => {
-                debug!("named: {:?}, code {:?}", named, code.to_ascii());
-                Some(Message::WriteButtonCombination(code.to_ascii()))
-              }
-              (iced::keyboard::Key::Character(c), iced::keyboard::key::Physical::Code(code)) => {
-                debug!("named: {:?}, code {:?}", c, code.to_ascii());
-                Some(Message::WriteButtonCombination(code.to_ascii()))
-              }
-              _ => None,
-            }
-          } else {
-            None
-          }
+    // Активен только когда разрешён режим записи комбинации и не открыта экранная клавиатура.
+    // Нажатия в полях ввода (например, в подписи кнопки) в комбинацию не попадают
+    let keyboard = match self.allow_write && self.key_picker.is_none() {
+      true => event::listen_with(|event, status, _id| match event {
+        Event::Keyboard(iced::keyboard::Event::KeyPressed {
+          key,
+          physical_key: iced::keyboard::key::Physical::Code(code),
+          location,
+          ..
+        }) if status == event::Status::Ignored => {
+          debug!(
+            "pressed: {:?}, code {:?}",
+            key,
+            by_physical(code).map(|key| key.code)
+          );
+          Some(Message::CapturePressed(code, key, location))
+        }
+        Event::Keyboard(iced::keyboard::Event::KeyReleased { key, location, .. })
+          if status == event::Status::Ignored =>
+        {
+          debug!("released: {:?}", key);
+          Some(Message::CaptureReleased(key, location))
         }
         _ => None,
       }),
       false => Subscription::none(),
     };
 
+    // Горячие клавиши редактора: отмена и повтор (Ctrl+Z, Ctrl+Shift+Z или Ctrl+Y),
+    // копирование и вставка ячеек (Ctrl+C, Ctrl+V), очистка (Delete).
+    // Нажатия, обработанные виджетами (например, полем ввода имени), не перехватываются
+    let editor_keys = match (&self.pages, self.allow_write || self.key_picker.is_some()) {
+      (Pages::Profiles, false) => event::listen_with(|event, status, _id| match (event, status) {
+        (
+          Event::Keyboard(iced::keyboard::Event::KeyPressed { key, modifiers, .. }),
+          event::Status::Ignored,
+        ) => match key.as_ref() {
+          Key::Character("z" | "я") if modifiers.command() && modifiers.shift() => {
+            Some(Message::HistoryRedo)
+          }
+          Key::Character("z" | "я") if modifiers.command() => Some(Message::HistoryUndo),
+          Key::Character("y" | "н") if modifiers.command() => Some(Message::HistoryRedo),
+          Key::Character("c" | "с") if modifiers.command() => Some(Message::CellCopy),
+          Key::Character("v" | "м") if modifiers.command() => Some(Message::CellPaste),
+          Key::Named(Named::Delete) => Some(Message::CellClear),
+          Key::Named(Named::Escape) => Some(Message::CellMenuClose),
+          _ => None,
+        },
+        _ => None,
+      }),
+      _ => Subscription::none(),
+    };
+
+    // Модификаторы и положение курсора для выделения и перетаскивания ячеек.
+    // Нажатие и отпускание на ячейке обрабатывает сама ячейка, отпускание вне ячеек
+    // (не перехваченное виджетами) отменяет перетаскивание
+    let cell_pointer = match &self.pages {
+      Pages::Profiles => event::listen_with(|event, status, _id| match event {
+        Event::Keyboard(iced::keyboard::Event::ModifiersChanged(modifiers)) => {
+          Some(Message::CellModifiers(modifiers))
+        }
+        Event::Mouse(mouse::Event::CursorMoved { position }) => {
+          Some(Message::CellPointer(position))
+        }
+        Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left))
+          if status == event::Status::Ignored =>
+        {
+          Some(Message::CellDragCancel)
+        }
+        _ => None,
+      }),
+      _ => Subscription::none(),
+    };
+
     // Периодический запрос номера активного профиля при открытой странице "Профили"
     let profile_active = match (&self.pages, &self.keypad.is_open, &self.profile_write) {
       (Pages::Profiles, true, false) => {
@@ -96,13 +150,24 @@ impl State {
       None => Subscription::none(),
     };
 
+    // Отслеживание внешних изменений в директории профилей
+    let library_watch = match self.settings.storage {
+      StorageBackend::Directory(_) => {
+        iced::time::every(Duration::from_secs(2)).map(|_| Message::LibraryWatch)
+      }
+      StorageBackend::Single => Subscription::none(),
+    };
+
     Subscription::batch(vec![
       port_sub,
       window,
       keyboard,
+      editor_keys,
+      cell_pointer,
       profile_active,
       write_timer_check,
       stick_calibrate_timer,
+      library_watch,
     ])
   }
 }
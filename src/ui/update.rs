@@ -1,21 +1,61 @@
 use std::{
+  path::PathBuf,
   sync::{Arc, Mutex},
   time::Duration,
 };
 
-use iced::{Point, Task};
-use log::{debug, info, trace};
+use iced::{
+  Point, Task,
+  keyboard::{Key, Location, Modifiers, key::Code},
+  window,
+};
+use log::{debug, info, trace, warn};
 
 use crate::{
   State,
-  data::{Config, device::Device, profiles::Profile, stick::Stick},
+  data::{
+    Config,
+    backup::{Backup, RestoreSelection},
+    banks::{Bank, BankWrite},
+    capture::{CaptureMode, Chord, normalize_modifiers},
+    cells::{self, Cell, Content},
+    code::{KeyCategory, by_physical, code_to_title},
+    compare::{Field, FileProfile, Side, Source, SourceEntry},
+    device::Device,
+    history::{Change, History},
+    keymaps::Keymap,
+    layout::KeyboardLayout,
+    library::{DirSnapshot, Library},
+    links::SlotLink,
+    lint::{has_errors, lint, summary},
+    meta::{ButtonColor, ButtonMeta, ProfileMeta},
+    name::{NAME_LEN, NameEncoding},
+    profiles::Profile,
+    qmk::QmkImport,
+    readable::{ExportSelection, ProfileFormat, parse_combination},
+    search::LibrarySort,
+    settings::{Settings, StorageBackend},
+    stick::Stick,
+    templates::{self, Template},
+  },
+  errors::library::LibraryError,
   hardware::{
+    buffers::Buffers,
     commands::{device, profile, stick},
-    serial::{DeviceIO, Keypad, profile::profile_all_request},
+    serial::{
+      DeviceIO, Keypad,
+      backup::{backup_request, backup_restore},
+      buttons::KeyPicker,
+      profile::{profile_all_request, profile_ram_commit, profile_rom_request},
+      slots::{slot_copy, slot_swap, slot_write, slot_write_verify},
+    },
   },
   ui::pages::Pages,
 };
 
+/// Наибольшее время ожидания сохранения профилей в ПЗУ перед закрытием окна
+const WINDOW_CLOSE_TIMEOUT: Duration = Duration::from_secs(10);
+
 /**
 Сообщения приложения, обрабатываемые в системе событий
 Определяют все возможные действия и взаимодействия в приложении
@@ -52,8 +92,14 @@ pub enum Message {
   WindowResized(f32, f32),
   /// Сообщение о перемещении окна
   WindowMoved(Point),
-  /// Сохранить текущие параметры окна
+  /// Сохранить текущие параметры окна и время открытия профилей
   WindowSettingsSave,
+  /// Запрос закрытия окна (проверка несохранённых профилей)
+  WindowCloseRequested(window::Id),
+  /// Ответ пользователя на предупреждение о несохранённых профилях
+  WindowCloseConfirmed(window::Id, rfd::MessageDialogResult),
+  /// Истекло время ожидания сохранения профилей перед закрытием окна
+  WindowCloseTimeout(window::Id),
 
   // --- Служебные операции устройства ---
   /// Перезагрузка устройства в загрузчик прошивки
@@ -68,31 +114,130 @@ pub enum Message {
   ProfileReceiveKeypadVec,
   ProfileReceivedKeypadVec((Vec<Profile>, usize)),
 
+  /// Безопасно прочитать профили ПЗУ (с восстановлением ОЗУ)
+  ProfileReceiveRomVec,
+  /// Сохранить профили ОЗУ, ПЗУ и номер активного профиля
+  ProfileReceivedRomVec((Vec<Profile>, Vec<Profile>, usize)),
+
   ProfileNew,
   ProfileRemove(usize),
   ProfileSave((usize, Profile)),
 
   ProfileLoadKeypad(usize),
   ProfileLoadLocal(usize),
+  /// Изменение строки поиска по библиотеке
+  LibrarySearch(String),
+  /// Показать только профили с тегом (`None` — все профили)
+  LibraryTag(Option<String>),
+  /// Выбрать порядок профилей библиотеки
+  LibrarySort(LibrarySort),
+  /// Добавить профиль библиотеки в избранное или убрать из него
+  LibraryFavorite(usize),
+  /// Начать переименование профиля библиотеки из списка
+  LibraryRenameStart(usize),
+  /// Изменение нового имени профиля
+  LibraryRenameInput(String),
+  /// Применить новое имя профиля
+  LibraryRenameSubmit,
+  /// Отменить переименование
+  LibraryRenameCancel,
+  /// Добавить в библиотеку копию профиля
+  LibraryDuplicate(usize),
+  /// Отменить последнее изменение редактируемого профиля
+  HistoryUndo,
+  /// Повторить отменённое изменение
+  HistoryRedo,
+  /// Вернуть профиль к записи истории по индексу
+  HistoryJump(usize),
+  /// Открыть профиль ПЗУ (1..=4) для просмотра
+  ProfileLoadRom(usize),
 
   /// Импорт профиля из файла
   ProfileImport,
-  ProfileImported(Vec<Profile>),
-
-  /// Экспорт вектора профилей в файл
-  ProfilesExport,
+  /// Профили и наборы прочитаны из файла, добавить их в библиотеку без дубликатов
+  ProfileImported(Library),
+  /// Импорт профиля из раскладки QMK или VIA
+  QmkImport,
+  /// Раскладка QMK прочитана, добавить профиль в библиотеку
+  QmkImported(QmkImport),
+  /// Экспорт редактируемого профиля в раскладку QMK
+  QmkExport,
+
+  // --- Экспорт в файл ---
+  /// Открыть выбор профилей, наборов и ячеек кейпада для экспорта
+  ExportOpen,
+  /// Переключить экспорт профиля библиотеки по индексу
+  ExportToggleProfile(usize),
+  /// Переключить экспорт набора по индексу
+  ExportToggleBank(usize),
+  /// Переключить экспорт профиля кейпада (1..=4)
+  ExportToggleSlot(usize),
+  /// Выбрать формат файла экспорта
+  ExportSelectFormat(ProfileFormat),
+  /// Выбрать файл и экспортировать выбранное с подписями в текущей раскладке
+  ExportApply,
+  /// Закрыть выбор экспорта
+  ExportCancel,
+
+  /// Сохранение библиотеки профилей и наборов в файл
+  LibrarySave,
 
   /// Завершение операций сохранения профиля (в файл или Flash)
   ProfileSaved,
 
-  /// Показать список сохранённых профилей
+  /// Загрузить библиотеку профилей и наборов
   ProfilesListLoad,
-  ProfilesListSave(Vec<Profile>),
+  /// Библиотека загружена (с миграцией из старых версий) или произошла ошибка
+  LibraryLoaded(Result<(Library, DirSnapshot), String>),
+  /// Библиотека сохранена более новой версией приложения и не должна перезаписываться
+  LibraryLocked(String),
+  /// Показать результат операции с библиотекой
+  LibraryStatus(String),
+  /// Библиотека сохранена (снимок директории профилей, директория менялась извне)
+  LibrarySaved(DirSnapshot, bool),
+  /// Проверить директорию профилей на внешние изменения
+  LibraryWatch,
+  /// Получен отпечаток директории профилей
+  LibraryWatched(u64),
+  /// Выбрать директорию для хранения профилей отдельными файлами
+  StorageSelectDirectory,
+  /// Сменить место хранения библиотеки профилей
+  StorageChanged(StorageBackend),
+  /// Изменение списка системных сочетаний для проверки профилей
+  LintShortcutsInput(String),
+  /// Выбрать способ кодирования имён профилей
+  NameEncodingSelect(NameEncoding),
+  /// Выбрать раскладку клавиатуры для подписей клавиш
+  LayoutSelect(KeyboardLayout),
+  /// Восстановить библиотеку из самой новой читаемой резервной копии
+  LibraryRestore,
+  /// Библиотека восстановлена (путь к копии) или произошла ошибка
+  LibraryRestored(Result<(Library, PathBuf), String>),
 
   /// Сделать профиль активным в RAM (1..=4)
   ProfileActiveWriteToRam(u8),
   /// Сделать профиль активным в ROM/Flash (1..=4)
   ProfileActiveWriteToRom(u8),
+  /// Записать все изменённые профили ОЗУ в ПЗУ
+  ProfilesCommitToRom,
+
+  // --- Операции со слотами кейпада ---
+  /// Выбрать исходную ячейку (1..=4)
+  SlotSelectFrom(usize),
+  /// Выбрать целевую ячейку (1..=4)
+  SlotSelectTo(usize),
+  /// Скопировать профиль из ячейки в ячейку
+  SlotCopy(usize, usize),
+  /// Поменять профили в двух ячейках местами
+  SlotSwap(usize, usize),
+  /// Сбросить ячейку к пустому профилю
+  SlotClear(usize),
+  /// Добавить копию профиля из ячейки в локальную библиотеку
+  SlotDuplicate(usize),
+  /// Записать в ячейку связанный с ней профиль библиотеки
+  SlotPush(usize),
+  /// Обновить связанный профиль библиотеки из ячейки
+  SlotPull(usize),
 
   /// Запрос номера активного профиля
   ProfileRequestActiveNum,
@@ -109,13 +254,114 @@ pub enum Message {
   AllowWriteButtonCombination,
   /// Запретить ввод комбинации и сбросить таймер
   DisallowWriteButtonCombination,
-  /// Очистить комбинацию у кнопки/стика
-  ClearButtonCombination(usize, bool),
+  // --- Сравнение профилей ---
+  /// Выбрать профиль для стороны сравнения (true — правая)
+  CompareSelect(bool, SourceEntry),
+  /// Открыть файл профилей для стороны сравнения
+  CompareOpenFile(bool),
+  /// Профили файла загружены (сторона, имя файла, профили)
+  CompareFileLoaded(bool, String, Vec<Profile>),
+  /// Показывать только отличающиеся поля
+  CompareOnlyDifferences(bool),
+  /// Перенести поле (true — из левого профиля в правый)
+  ComparePick(Field, bool),
+  /// Перенести все отличающиеся поля (true — из левого профиля в правый)
+  ComparePickAll(bool),
+  /// Сохранить профиль стороны сравнения в библиотеку как новый
+  CompareSaveAsNew(bool),
+
+  // --- Операции с ячейками ---
+  /// Изменились нажатые модификаторы (Ctrl — выделение нескольких ячеек и копирование при перетаскивании)
+  CellModifiers(Modifiers),
+  /// Курсор над ячейкой
+  CellHover(Cell),
+  /// Курсор покинул ячейку
+  CellLeave(Cell),
+  /// Перемещение курсора на странице профилей
+  CellPointer(Point),
+  /// Нажата левая кнопка мыши на ячейке: возможное начало перетаскивания
+  CellDragStart(Cell),
+  /// Отпущена левая кнопка мыши на ячейке: перенос назначения или клик по ячейке
+  CellDragEnd(Cell),
+  /// Левая кнопка мыши отпущена вне ячеек: перетаскивание отменяется
+  CellDragCancel,
+  /// Открыть контекстное меню ячейки
+  CellMenu(Cell),
+  /// Закрыть контекстное меню
+  CellMenuClose,
+  /// Скопировать назначения выделенных ячеек
+  CellCopy,
+  /// Вставить скопированные назначения в выделенные ячейки
+  CellPaste,
+  /// Поменять местами назначения двух выделенных ячеек
+  CellSwap,
+  /// Очистить выделенные ячейки
+  CellClear,
+  /// Назначить выделенным ячейкам во всех профилях библиотеки текущие назначения
+  CellApplyToLibrary,
+
+  // --- Команды приложений ---
+  /// Показать или скрыть панель команд приложения
+  KeymapToggle,
+  /// Открыть файл сочетаний клавиш приложения
+  KeymapOpen,
+  /// Раскладка приложения импортирована
+  KeymapLoaded(Keymap),
+  /// Показать результат импорта или назначения команды
+  KeymapStatus(String),
+  /// Изменение строки поиска по командам
+  KeymapSearch(String),
+  /// Назначить выбранной кнопке команду раскладки по индексу
+  KeymapAssign(usize),
+  /// Закрыть импортированную раскладку
+  KeymapClose,
+
+  // --- Метаданные профиля ---
+  /// Показать или скрыть панель описания и подписей
+  MetaToggle,
+  /// Изменение описания профиля
+  MetaDescription(String),
+  /// Изменение автора профиля
+  MetaAuthor(String),
+  /// Изменение текста нового тега
+  MetaTagInput(String),
+  /// Добавить введённый тег
+  MetaTagAdd,
+  /// Удалить тег по индексу
+  MetaTagRemove(usize),
+  /// Изменение подписи выбранной кнопки
+  MetaCaption(String),
+  /// Изменение заметки выбранной кнопки
+  MetaNote(String),
+  /// Выбрать цвет выбранной кнопки (`None` — без цвета)
+  MetaColor(Option<ButtonColor>),
+  /// Нажата физическая клавиша в режиме записи комбинации (с логической клавишей и расположением)
+  CapturePressed(Code, Key, Location),
+  /// Отпущена клавиша в режиме записи комбинации (логическая клавиша и расположение)
+  CaptureReleased(Key, Location),
+  /// Выбрать режим захвата комбинаций
+  CaptureModeSelect(CaptureMode),
+  /// Переключить упорядочивание модификаторов при захвате
+  CaptureNormalizeToggle(bool),
   /// Добавить код в текущую комбинацию (если разрешено)
   WriteButtonCombination(Option<u8>),
   /// Сохранить набранную комбинацию в профиль
   SaveButtonCombination(usize),
 
+  // --- Экранная клавиатура ---
+  /// Открыть экранную клавиатуру для редактируемой кнопки
+  KeyPickerOpen,
+  /// Показать клавиши категории
+  KeyPickerCategory(KeyCategory),
+  /// Добавить клавишу к комбинации редактируемой кнопки
+  KeyPickerKey(u8),
+  /// Изменение текста комбинации
+  KeyPickerInput(String),
+  /// Разобрать текст и заменить им комбинацию редактируемой кнопки
+  KeyPickerSubmit,
+  /// Закрыть экранную клавиатуру
+  KeyPickerClose,
+
   /// Установить мёртвую зону стика
   WriteDeadZone(u8),
 
@@ -127,6 +373,64 @@ pub enum Message {
   StickInfoSave(Stick),
   StickEndCalibration,
 
+  // --- Резервные копии ---
+  /// Снять резервную копию кейпада и сохранить в файл (true — автоматическая копия)
+  BackupCreate(bool),
+  /// Копия сохранена (путь к файлу) или произошла ошибка
+  BackupCreated(Result<PathBuf, String>),
+  /// Запись в ПЗУ после автоматической копии завершена (путь к копии или текст ошибки)
+  RomWritten(Result<PathBuf, String>),
+  /// Показать результат операции с резервной копией
+  BackupStatus(String),
+  /// Открыть диалог выбора копии для восстановления
+  BackupRestoreOpen,
+  /// Показать диалог выбора данных для восстановления
+  BackupRestoreLoaded(Backup),
+  /// Переключить восстановление профиля ОЗУ (1..=4)
+  BackupRestoreToggleRam(usize),
+  /// Переключить восстановление профиля ПЗУ (1..=4)
+  BackupRestoreToggleRom(usize),
+  /// Переключить восстановление калибровки стика
+  BackupRestoreToggleCalibration,
+  /// Восстановить выбранные данные на кейпад
+  BackupRestoreApply,
+  /// Закрыть диалог восстановления
+  BackupRestoreCancel,
+
+  // --- Наборы профилей ---
+  /// Создать новый набор
+  BankNew,
+  /// Создать набор из текущих профилей ОЗУ кейпада
+  BankCapture,
+  /// Удалить набор по индексу
+  BankRemove(usize),
+  /// Открыть набор для редактирования
+  BankSelect(usize),
+  /// Переименовать открытый набор
+  BankRename(String),
+  /// Назначить ячейке (1..=4) открытого набора профиль библиотеки по индексу
+  BankSetSlot(usize, usize),
+  /// Записать набор на кейпад (true — с сохранением в ПЗУ)
+  BankWrite(usize, bool),
+  /// Резервная копия перед записью набора в ПЗУ создана (путь или текст ошибки)
+  BankBackupCreated(Result<PathBuf, String>),
+  /// Записать ячейку (1..=4) записываемого набора
+  BankWriteSlot(usize),
+  /// Ячейка записана (номер ячейки, запись подтверждена чтением)
+  BankSlotWritten(usize, bool),
+
+  // --- Шаблоны профилей ---
+  /// Шаблоны загружены (шаблоны, ошибки пропущенных файлов)
+  TemplatesLoaded((Vec<Template>, Vec<String>)),
+  /// Выбрать шаблон по индексу
+  TemplateSelect(usize),
+  /// Выбрать ячейку (1..=4) для записи шаблона
+  TemplateSlotSelect(usize),
+  /// Создать профиль библиотеки по выбранному шаблону
+  TemplateNewProfile,
+  /// Применить выбранный шаблон к профилю ячейки и записать его на кейпад
+  TemplateToSlot,
+
   // --- Информация об устройстве ---
   /// Запросить информацию об устройстве
   GetDeviceInfo,
@@ -231,15 +535,101 @@ impl State {
 
         self.pages = Pages::Profiles;
 
-        // После подключения — запросить профиль
+        // После подключения — запросить профили ОЗУ и ПЗУ
         // Task::done(Message::ProfileReceive)
-        Task::done(Message::ProfileReceiveKeypadVec)
+        Task::done(Message::ProfileReceiveRomVec)
       }
       Message::ChangePage(page) => {
+        // Профили библиотеки могли измениться в редакторе
+        if let Pages::Compare = page {
+          for right in [false, true] {
+            let source = self.compare.side(right).map(|side| side.source);
+            if let Some(source @ Source::Library(_)) = source {
+              *self.compare.side_mut(right) = self
+                .compare_profile(source)
+                .map(|profile| Side { source, profile });
+            }
+          }
+        }
         self.pages = page;
+        match self.pages {
+          // Свои шаблоны могли появиться, пока приложение открыто
+          Pages::Templates => Task::perform(async { templates::load() }, Message::TemplatesLoaded),
+          _ => Task::none(),
+        }
+      }
+      Message::CompareSelect(right, entry) => {
+        *self.compare.side_mut(right) = self.compare_profile(entry.source).map(|profile| Side {
+          source: entry.source,
+          profile,
+        });
+        Task::none()
+      }
+      Message::CompareOpenFile(right) => Profile::open_compare_file_dialog(right),
+      Message::CompareFileLoaded(right, file, profiles) => {
+        let first = self.compare.files.len();
+        self
+          .compare
+          .files
+          .extend(profiles.into_iter().map(|profile| FileProfile {
+            file: file.clone(),
+            profile,
+          }));
+        match self.compare.files.get(first) {
+          Some(loaded) => {
+            *self.compare.side_mut(right) = Some(Side {
+              source: Source::File(first),
+              profile: loaded.profile.clone(),
+            });
+            Task::none()
+          }
+          None => Task::done(Message::LibraryStatus(format!(
+            "В файле {file} нет профилей"
+          ))),
+        }
+      }
+      Message::CompareOnlyDifferences(only) => {
+        self.compare.only_differences = only;
         Task::none()
       }
+      Message::ComparePick(field, to_right) => {
+        let side = self.compare.pick(field, to_right).cloned();
+        self.compare_apply(side)
+      }
+      Message::ComparePickAll(to_right) => {
+        let mut side = None;
+        for field in self.compare.diff() {
+          side = self.compare.pick(field, to_right).cloned();
+        }
+        self.compare_apply(side)
+      }
+      Message::CompareSaveAsNew(right) => {
+        let Some(side) = self.compare.side(right) else {
+          return Task::none();
+        };
+        let idx = self.profiles_local_vec.len();
+        let mut profile = side.profile.clone();
+        profile.meta.mark_created();
+        self.profiles_local_vec.push(profile);
+        *self.compare.side_mut(right) = Some(Side {
+          source: Source::Library(idx),
+          profile: self.profiles_local_vec[idx].clone(),
+        });
+        Task::done(Message::LibrarySave)
+      }
       Message::GetButtonSettings(id, stick) => {
+        let cell = match stick {
+          true => Cell::Stick(id),
+          false => Cell::Button(id),
+        };
+        self.cells.menu = None;
+        // Ctrl+клик добавляет ячейку к выделению без записи комбинации
+        if self.cells.modifiers.command() {
+          self.cells.toggle(cell);
+          return Task::none();
+        }
+        self.cells.selected = vec![cell];
+
         self.button.vec_str.clear();
         self.button.code.clear();
 
@@ -251,8 +641,8 @@ impl State {
 
         self.button.is_stick = stick;
 
-        // Разрешаем запись комбинации
-        if !self.profile_on_keypad {
+        // Разрешаем запись комбинации, если клавиши не выбираются на экранной клавиатуре
+        if !self.profile_on_keypad && self.key_picker.is_none() {
           return Task::done(Message::AllowWriteButtonCombination);
         }
         Task::none()
@@ -269,8 +659,60 @@ impl State {
       }
       Message::WindowSettingsSave => {
         self.window_settings.save();
+        self.library_touched_save();
         Task::none()
       }
+      Message::WindowCloseRequested(id) => {
+        self.window_settings.save();
+        self.library_touched_save();
+
+        let unsaved = self.unsaved_profiles();
+        if unsaved.is_empty() {
+          return window::close(id);
+        }
+
+        let description = format!(
+          "Профили ОЗУ {} не сохранены в ПЗУ и будут потеряны при отключении кейпада.\n\n\
+          Да — сохранить в ПЗУ и выйти\nНет — выйти без сохранения",
+          unsaved
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+        );
+
+        Task::perform(
+          rfd::AsyncMessageDialog::new()
+            .set_level(rfd::MessageLevel::Warning)
+            .set_title("Несохранённые профили")
+            .set_description(description)
+            .set_buttons(rfd::MessageButtons::YesNoCancel)
+            .show(),
+          move |res| Message::WindowCloseConfirmed(id, res),
+        )
+      }
+      Message::WindowCloseConfirmed(id, res) => match res {
+        rfd::MessageDialogResult::Yes => {
+          self.window_close_id = Some(id);
+          Task::batch([
+            Task::done(Message::ProfilesCommitToRom),
+            Task::perform(tokio::time::sleep(WINDOW_CLOSE_TIMEOUT), move |_| {
+              Message::WindowCloseTimeout(id)
+            }),
+          ])
+        }
+        rfd::MessageDialogResult::No => window::close(id),
+        _ => Task::none(),
+      },
+      Message::WindowCloseTimeout(id) => {
+        match self.window_close_id.take_if(|pending| *pending == id) {
+          Some(id) => {
+            warn!("Профили не сохранены в ПЗУ за отведённое время, окно закрывается");
+            window::close(id)
+          }
+          None => Task::none(),
+        }
+      }
       Message::RebootToBootloader => {
         self.keypad.is_open = false;
         self.keypad.port = None;
@@ -347,6 +789,7 @@ impl State {
           false => {
             self.profiles_keypad_vec = res.0.clone();
             if self.profile_on_keypad
+              && self.rom_profile_id.is_none()
               && let Some(active_profile_id) = self.active_profile_id
             {
               self.profile = res.0.get(active_profile_id - 1).unwrap().clone();
@@ -356,15 +799,85 @@ impl State {
 
         Task::none()
       }
+      Message::ProfileReceiveRomVec => {
+        if !self.keypad.is_open {
+          // Кейпад отключён: ждать сохранения перед закрытием окна бессмысленно
+          return match self.window_close_id.take() {
+            Some(id) => window::close(id),
+            None => Task::none(),
+          };
+        };
+
+        self.profile_write = true;
+        let mut buffers = self.buffers.clone();
+        Task::perform(
+          async move { profile_rom_request(&mut buffers).await },
+          |res| match res {
+            Ok(res) => Message::ProfileReceivedRomVec(res),
+            Err(_) => Message::ProfileReceiveRomVec,
+          },
+        )
+      }
+      Message::ProfileReceivedRomVec((ram, rom, active_id)) => {
+        self.profiles_rom_vec = rom;
+
+        // Проверка ПЗУ после записи набора
+        if let Some(progress) = self.bank_progress.take_if(|p| p.verifying_rom) {
+          self.bank_status = Some(self.bank_write_status(progress));
+        }
+
+        // Закрытие окна, отложенное до сохранения профилей в ПЗУ
+        if let Some(id) = self.window_close_id.take() {
+          return window::close(id);
+        }
+
+        if let Some(id) = self.rom_profile_id
+          && let Some(profile) = self.profiles_rom_vec.get(id - 1).cloned()
+        {
+          self.profile = profile;
+        }
+
+        let received = Task::done(Message::ProfileReceivedKeypadVec((ram, active_id)));
+
+        // Автоматическая копия при первом подключении в сеансе
+        match self.backup_session_done {
+          true => received,
+          false => {
+            self.backup_session_done = true;
+            received.chain(Task::done(Message::BackupCreate(true)))
+          }
+        }
+      }
       Message::ProfileNew => {
-        self.profiles_local_vec.push(self.profile.clone());
+        let mut profile = self.profile.clone();
+        profile.meta.mark_created();
+        self.profiles_local_vec.push(profile);
 
-        Task::done(Message::ProfilesExport)
+        Task::done(Message::LibrarySave)
         // Task::none()
       }
       Message::ProfileRemove(idx) => {
-        self.profiles_local_vec.remove(idx);
-        Task::done(Message::ProfilesExport)
+        let removed = self.profiles_local_vec.remove(idx);
+        if !self
+          .profiles_local_vec
+          .iter()
+          .any(|p| p.name == removed.name)
+          && self.settings.library_view.forget(&removed.name)
+        {
+          self.settings.save();
+        }
+        self.library_rename = None;
+        self.compare.library_removed(idx);
+        // Истории следующих профилей библиотеки сдвигаются вместе с индексами
+        self.histories = std::mem::take(&mut self.histories)
+          .into_iter()
+          .filter_map(|entry| match entry {
+            (i, _) if i == idx => None,
+            (i, history) if i > idx => Some((i - 1, history)),
+            entry => Some(entry),
+          })
+          .collect();
+        Task::done(Message::LibrarySave)
       }
       Message::ProfileSave(profile) => {
         if self.profile_on_keypad {
@@ -372,12 +885,86 @@ impl State {
         }
 
         let (idx, profile) = profile;
+        self.library_store(idx, profile)
+      }
+      Message::LibrarySearch(query) => {
+        self.library_query = query;
+        Task::none()
+      }
+      Message::LibraryTag(tag) => {
+        self.library_tag = tag;
+        Task::none()
+      }
+      Message::LibrarySort(sort) => {
+        self.settings.library_view.sort = sort;
+        self.settings.save();
+        Task::none()
+      }
+      Message::LibraryFavorite(idx) => {
+        if let Some(profile) = self.profiles_local_vec.get(idx) {
+          self.settings.library_view.toggle_favorite(&profile.name);
+          self.settings.save();
+        }
+        Task::none()
+      }
+      Message::LibraryRenameStart(idx) => {
+        self.library_rename = self
+          .profiles_local_vec
+          .get(idx)
+          .map(|profile| (idx, profile.name.clone()));
+        Task::none()
+      }
+      Message::LibraryRenameInput(name) => {
+        // Как и при вводе в редакторе, имя ограничено длиной поля устройства
+        if name.chars().count() > NAME_LEN {
+          return Task::none();
+        }
+        if let Some((_, input)) = &mut self.library_rename {
+          *input = name;
+        }
+        Task::none()
+      }
+      Message::LibraryRenameSubmit => {
+        let Some((idx, name)) = self.library_rename.take() else {
+          return Task::none();
+        };
+        let name = name.trim().to_string();
+        let Some(mut profile) = self.profiles_local_vec.get(idx).cloned() else {
+          return Task::none();
+        };
+        if name.is_empty() || name == profile.name {
+          return Task::none();
+        }
+        // Имя — ключ профиля в библиотеке: занятое имя не принимается, ввод остаётся открытым
+        if self.profiles_local_vec.iter().any(|p| p.name == name) {
+          self.library_rename = Some((idx, name));
+          return Task::none();
+        }
+
+        profile.name = name;
+        if self.history_id() == Some(idx) {
+          self.profile.name = profile.name.clone();
+        }
+        self.library_store(idx, profile)
+      }
+      Message::LibraryRenameCancel => {
+        self.library_rename = None;
+        Task::none()
+      }
+      Message::LibraryDuplicate(idx) => {
+        let Some(mut profile) = self.profiles_local_vec.get(idx).cloned() else {
+          return Task::none();
+        };
+        // Исходное имя занято самим профилем, поэтому копия получает номер
+        profile.name = self.library_free_name(&profile.name);
+        profile.meta.mark_created();
 
-        self.profiles_local_vec[idx] = profile;
-        Task::done(Message::ProfilesExport)
+        self.profiles_local_vec.push(profile);
+        Task::done(Message::LibrarySave)
       }
       Message::ProfileLoadKeypad(idx) => {
         self.profile_on_keypad = true;
+        self.rom_profile_id = None;
 
         if let Some(profile) = self.profiles_keypad_vec.get(idx - 1).cloned() {
           self.active_profile_id = Some(idx);
@@ -388,52 +975,331 @@ impl State {
       }
       Message::ProfileLoadLocal(idx) => {
         self.profile_on_keypad = false;
+        self.rom_profile_id = None;
 
         if let Some(profile) = self.profiles_local_vec.get(idx).cloned() {
+          // Время открытия записывается вместе с параметрами окна, а не на каждый клик
+          self.settings.library_view.touch(&profile.name);
+          self.library_touched = true;
           self.local_profile_id = Some(idx);
           self.profile = profile;
+          self.history_open();
+        }
+        Task::none()
+      }
+      Message::ProfileLoadRom(idx) => {
+        if let Some(profile) = self.profiles_rom_vec.get(idx - 1).cloned() {
+          self.profile_on_keypad = true;
+          self.rom_profile_id = Some(idx);
+          self.profile = profile;
         }
         Task::none()
       }
+      Message::HistoryUndo => {
+        let profile = self
+          .history_id()
+          .and_then(|id| self.histories.get_mut(&id))
+          .and_then(|history| history.undo().cloned());
+        self.history_apply(profile)
+      }
+      Message::HistoryRedo => {
+        let profile = self
+          .history_id()
+          .and_then(|id| self.histories.get_mut(&id))
+          .and_then(|history| history.redo().cloned());
+        self.history_apply(profile)
+      }
+      Message::HistoryJump(position) => {
+        let profile = self
+          .history_id()
+          .and_then(|id| self.histories.get_mut(&id))
+          .and_then(|history| history.jump(position).cloned());
+        self.history_apply(profile)
+      }
       // Открыть диалог импорта профиля
       Message::ProfileImport => Profile::open_load_file_dialog(),
-      Message::ProfileImported(profiles) => {
-        profiles
+      Message::ExportOpen => {
+        self.export = Some(ExportSelection::new(
+          self.profiles_local_vec.len(),
+          self.banks.len(),
+        ));
+        Task::none()
+      }
+      Message::ExportToggleProfile(idx) => {
+        if let Some(selected) = self.export.as_mut().and_then(|e| e.profiles.get_mut(idx)) {
+          *selected = !*selected;
+        }
+        Task::none()
+      }
+      Message::ExportToggleBank(idx) => {
+        if let Some(selected) = self.export.as_mut().and_then(|e| e.banks.get_mut(idx)) {
+          *selected = !*selected;
+        }
+        Task::none()
+      }
+      Message::ExportToggleSlot(id) => {
+        if let Some(export) = self.export.as_mut() {
+          export.slots[id - 1] = !export.slots[id - 1];
+        }
+        Task::none()
+      }
+      Message::ExportSelectFormat(format) => {
+        if let Some(export) = self.export.as_mut() {
+          export.format = format;
+        }
+        Task::none()
+      }
+      Message::ExportApply => {
+        let Some(export) = self.export.take() else {
+          return Task::none();
+        };
+
+        let selected = |flags: &[bool], idx: usize| flags.get(idx).copied().unwrap_or(false);
+        // Профили, на которые ссылаются выбранные наборы, экспортируются вместе с ними
+        let referenced = |profile: &Profile| {
+          self
+            .banks
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| selected(&export.banks, *idx))
+            .any(|(_, bank)| bank.profiles.contains(&Some(profile.name.clone())))
+        };
+        let profiles = self
+          .profiles_local_vec
           .iter()
-          .for_each(|profile| self.profiles_local_vec.push(profile.clone()));
+          .enumerate()
+          .filter(|(idx, profile)| selected(&export.profiles, *idx) || referenced(profile))
+          .map(|(_, profile)| profile)
+          .chain(
+            self
+              .profiles_keypad_vec
+              .iter()
+              .enumerate()
+              .filter(|(idx, _)| export.slots[*idx])
+              .map(|(_, profile)| profile),
+          )
+          .cloned()
+          .collect();
+        let banks = self
+          .banks
+          .iter()
+          .enumerate()
+          .filter(|(idx, _)| selected(&export.banks, *idx))
+          .map(|(_, bank)| bank.clone())
+          .collect();
+
+        Profile::open_save_file_dialog(profiles, banks, export.format, self.settings.layout)
+      }
+      Message::ExportCancel => {
+        self.export = None;
+        Task::none()
+      }
+      Message::ProfileImported(library) => {
+        let (added, skipped) = library.merge_into(&mut self.profiles_local_vec, &mut self.banks);
+        self.library_status = Some(format!(
+          "Импортировано: {added}, пропущено дубликатов: {skipped}"
+        ));
 
-        Task::done(Message::ProfilesExport)
+        Task::done(Message::LibrarySave)
+      }
+      Message::QmkImport => Profile::open_qmk_import_dialog(),
+      Message::QmkImported(import) => {
+        let mut profile = import.profile;
+        profile.name = self.library_free_name(&profile.name);
+        profile.meta.mark_created();
+
+        let mut status = format!("Раскладка QMK импортирована в профиль «{}»", profile.name);
+        if import.layers > 1 {
+          status.push_str(&format!(
+            ", перенесён только первый из {} слоёв",
+            import.layers
+          ));
+        }
+        if !import.skipped.is_empty() {
+          status.push_str(&format!(". Не перенесены: {}", import.skipped.join("; ")));
+        }
+        self.library_status = Some(status);
+        self.profiles_local_vec.push(profile);
+        Task::done(Message::LibrarySave)
       }
-      Message::ProfilesExport => {
-        let profiles = self.profiles_local_vec.clone();
+      Message::QmkExport => Profile::open_qmk_export_dialog(self.profile.clone()),
+      Message::LibrarySave => {
+        // Нечитаемый файл библиотеки не перезаписывается
+        if self.library_locked {
+          return Task::none();
+        }
+
+        let library = Library::new(self.profiles_local_vec.clone(), self.banks.clone());
+        let storage = self.settings.storage.clone();
+        let snapshot = self.library_files.clone();
 
         Task::perform(
           async move {
             tokio::task::spawn_blocking(move || {
-              trace!("message: ProfilesExport: сохранение профилей в файл");
-              // debug!("{:?}", profiles);
-              Profile::save(profiles)
+              trace!("message: LibrarySave: сохранение библиотеки в файл");
+              // Изменённые извне файлы сохранение не затрагивает, после него библиотека перечитывается
+              let is_changed = Library::signature(&storage) != snapshot.signature;
+              library
+                .save(&storage, &snapshot)
+                .map(|snapshot| (snapshot, is_changed))
             })
             .await
           },
-          |_| Message::ProfileSaved,
+          |res| match res {
+            Ok(Ok((snapshot, is_changed))) => Message::LibrarySaved(snapshot, is_changed),
+            Ok(Err(err)) => {
+              Message::LibraryStatus(format!("Не удалось сохранить библиотеку: {err}"))
+            }
+            Err(_) => Message::ProfileSaved,
+          },
         )
       }
       Message::ProfileSaved => Task::none(),
       Message::ProfilesListLoad => Task::perform(
-        async move { Profile::load().await },
-        Message::ProfilesListSave,
+        Library::load(self.settings.storage.clone()),
+        |res| match res {
+          Err(err) if err.is::<LibraryError>() => Message::LibraryLocked(err.to_string()),
+          res => Message::LibraryLoaded(res.map_err(|err| err.to_string())),
+        },
       ),
-      Message::ProfilesListSave(vec) => {
-        if self.profiles_local_vec == vec {
+      Message::LibrarySaved(snapshot, is_changed) => {
+        self.library_files = snapshot;
+        match is_changed {
+          true => Task::done(Message::ProfilesListLoad),
+          false => Task::none(),
+        }
+      }
+      Message::LibraryWatch => {
+        let storage = self.settings.storage.clone();
+        Task::perform(
+          async move { tokio::task::spawn_blocking(move || Library::signature(&storage)).await },
+          |res| Message::LibraryWatched(res.unwrap_or_default()),
+        )
+      }
+      Message::LibraryWatched(signature) => {
+        if signature == self.library_files.signature {
+          return Task::none();
+        }
+
+        info!("library: директория профилей изменена извне, перечитываю");
+        self.library_files.signature = signature;
+        Task::done(Message::ProfilesListLoad)
+      }
+      Message::StorageSelectDirectory => Settings::open_storage_dir_dialog(),
+      Message::LintShortcutsInput(text) => {
+        self.settings.lint.desktop_shortcuts = text
+          .split(',')
+          .map(str::trim)
+          .filter(|shortcut| !shortcut.is_empty())
+          .map(String::from)
+          .collect();
+        self.lint_shortcuts_text = text;
+        self.settings.save();
+        Task::none()
+      }
+      Message::NameEncodingSelect(encoding) => {
+        self.buffers.set_name_encoding(encoding);
+        self.settings.name_encoding = encoding;
+        self.settings.save();
+        Task::none()
+      }
+      Message::LayoutSelect(layout) => {
+        self.settings.layout = layout;
+        self.settings.save();
+        Task::none()
+      }
+      Message::StorageChanged(storage) => {
+        if self.settings.storage == storage {
           return Task::none();
         }
 
-        self.profiles_local_vec = vec;
-        Task::done(Message::ProfilesExport)
+        self.settings.storage = storage.clone();
+        self.settings.save();
+        self.library_files = DirSnapshot::default();
+
+        // Директория с профилями подключается как есть, пустая заполняется текущей библиотекой
+        Task::perform(
+          async move {
+            match storage {
+              StorageBackend::Directory(dir) => {
+                Library::load_dir(&dir).is_ok_and(|snapshot| !snapshot.files.is_empty())
+              }
+              StorageBackend::Single => false,
+            }
+          },
+          |has_profiles| match has_profiles {
+            true => Message::ProfilesListLoad,
+            false => Message::LibrarySave,
+          },
+        )
+      }
+      Message::LibraryLoaded(res) => match res {
+        Ok((library, snapshot)) => {
+          self.library_damaged = false;
+          self.library_files = snapshot;
+          self.library_locked = false;
+          let previous = std::mem::replace(&mut self.profiles_local_vec, library.profiles);
+          self.banks = library.banks;
+          // Библиотека могла измениться извне: индексы профилей сопоставляются заново,
+          // выбор, сравнение и история исчезнувших профилей сбрасываются
+          let remap = previous
+            .iter()
+            .map(|profile| self.library_find(profile))
+            .collect::<Vec<_>>();
+          let remap = |idx: usize| remap.get(idx).copied().flatten();
+          self.local_profile_id = self.local_profile_id.and_then(remap);
+          self.compare.library_remap(remap);
+          self.bank_id.take_if(|id| *id >= self.banks.len());
+          self.histories = std::mem::take(&mut self.histories)
+            .into_values()
+            .filter_map(|history| Some((self.library_find(history.current())?, history)))
+            .collect();
+          Task::done(Message::LibrarySave)
+        }
+        Err(err) => {
+          // Если файл не удалось перенести в карантин, он не перезаписывается
+          self.library_damaged = true;
+          self.library_locked = Library::path().is_ok_and(|path| path.exists());
+          Task::done(Message::LibraryStatus(format!(
+            "Не удалось загрузить библиотеку профилей: {err}"
+          )))
+        }
+      },
+      Message::LibraryLocked(err) => {
+        // Восстановление из копии затёрло бы более новую библиотеку, поэтому не предлагается
+        self.library_damaged = false;
+        self.library_locked = true;
+        Task::done(Message::LibraryStatus(format!(
+          "Библиотека профилей не загружена и не будет изменена: {err}"
+        )))
+      }
+      Message::LibraryStatus(status) => {
+        info!("library: {status}");
+        self.library_status = Some(status);
+        Task::none()
       }
+      Message::LibraryRestore => Task::perform(Library::restore_latest(), |res| {
+        Message::LibraryRestored(res.map_err(|err| err.to_string()))
+      }),
+      Message::LibraryRestored(res) => match res {
+        Ok((library, path)) => {
+          self.library_status = Some(format!("Библиотека восстановлена из {}", path.display()));
+          Task::done(Message::LibraryLoaded(Ok((
+            library,
+            self.library_files.clone(),
+          ))))
+        }
+        Err(err) => Task::done(Message::LibraryStatus(format!(
+          "Не удалось восстановить библиотеку: {err}"
+        ))),
+      },
       Message::ProfileActiveWriteToRam(num) => {
+        if !self.lint_before_write() {
+          return Task::none();
+        }
         self.profile_write = true;
+        self.history_record(Change::SlotWrite(num as usize, false));
+        self.slot_link_written(num as usize);
 
         let mut buf = self.buffers.clone();
         let profile = self.profile.clone();
@@ -450,20 +1316,153 @@ impl State {
         )
       }
       Message::ProfileActiveWriteToRom(num) => {
-        let mut buf = self.buffers.clone();
+        if !self.lint_before_write() {
+          return Task::none();
+        }
+        self.history_record(Change::SlotWrite(num as usize, true));
+        self.slot_link_written(num as usize);
+
         let profile = self.profile.clone();
-        Task::perform(
-          async move {
-            tokio::task::spawn_blocking(move || {
-              let _ = Keypad::profile_send(&mut buf, profile);
-              buf.send().push(&profile::Command::WriteActiveToFlash(num));
-              buf.send().push(&profile::Command::LoadFlashToRam)
-            })
-            .await
-          },
-          // |_| Message::ProfileRequestActiveNum,
-          |_| Message::None,
-        )
+        self.rom_write_task(move |buf| {
+          Keypad::profile_send(buf, profile)?;
+          buf.send().push(&profile::Command::WriteActiveToFlash(num));
+          // Остальные профили ОЗУ не трогаем: LoadFlashToRam затёр бы несохранённые изменения
+          buf.send().push(&profile::Command::WriteActiveToRam(num));
+          Ok(())
+        })
+      }
+      Message::ProfilesCommitToRom => {
+        let unsaved = self.unsaved_profiles();
+        if !self.keypad.is_open || unsaved.is_empty() {
+          return match self.window_close_id.take() {
+            Some(id) => window::close(id),
+            None => Task::none(),
+          };
+        }
+
+        // Профили ОЗУ уже записаны на устройство и проверялись при записи:
+        // сохранение в ПЗУ их не меняет, поэтому повторно они не проверяются
+        let active_id = self.keypad_active_id();
+        self.rom_write_task(move |buf| {
+          profile_ram_commit(buf, &unsaved, active_id);
+          Ok(())
+        })
+      }
+      Message::SlotSelectFrom(id) => {
+        self.slot_from = id;
+        Task::none()
+      }
+      Message::SlotSelectTo(id) => {
+        self.slot_to = id;
+        Task::none()
+      }
+      Message::SlotCopy(from, to) => {
+        if from == to {
+          return Task::none();
+        }
+
+        // В режиме ПЗУ источник берётся из ПЗУ, а не из ОЗУ
+        let source = self.slot_rom_source(from);
+        if self.is_rom && source.is_none() {
+          return Task::none();
+        }
+
+        self.slot_link_set(to, self.settings.slot_links[from - 1].clone());
+        let active_id = self.keypad_active_id();
+        self.keypad_write_task(self.is_rom, move |buf, to_rom| {
+          slot_copy(buf, (from, source), to, to_rom, active_id)
+        })
+      }
+      Message::SlotSwap(a, b) => {
+        if a == b {
+          return Task::none();
+        }
+        let profile_a = self.slot_rom_source(a);
+        let profile_b = match self.is_rom {
+          true => self.slot_rom_source(b),
+          false => self.profiles_keypad_vec.get(b - 1).cloned(),
+        };
+        let Some(profile_b) = profile_b else {
+          return Task::none();
+        };
+        if self.is_rom && profile_a.is_none() {
+          return Task::none();
+        }
+
+        self.settings.slot_links.swap(a - 1, b - 1);
+        self.settings.save();
+        let active_id = self.keypad_active_id();
+        self.keypad_write_task(self.is_rom, move |buf, to_rom| {
+          slot_swap(buf, (a, b), (profile_a, profile_b), to_rom, active_id)
+        })
+      }
+      Message::SlotClear(id) => {
+        self.slot_link_set(id, None);
+        let active_id = self.keypad_active_id();
+        self.keypad_write_task(self.is_rom, move |buf, to_rom| {
+          slot_write(buf, id, Profile::default(), to_rom, active_id)
+        })
+      }
+      Message::SlotDuplicate(id) => {
+        let profiles = match self.is_rom {
+          true => &self.profiles_rom_vec,
+          false => &self.profiles_keypad_vec,
+        };
+
+        let Some(mut profile) = profiles.get(id - 1).cloned() else {
+          return Task::none();
+        };
+        profile.name = profile.name.trim_end_matches('\0').to_string();
+        profile.meta.mark_created();
+
+        self.slot_link_set(id, Some(SlotLink::new(&profile)));
+        self.profiles_local_vec.push(profile);
+        Task::done(Message::LibrarySave)
+      }
+      Message::SlotPush(id) => {
+        let Some((status, Some(idx))) = self.slot_sync(id) else {
+          return Task::none();
+        };
+        if !status.is_diverged() {
+          return Task::none();
+        }
+
+        let write = match self.is_rom {
+          true => Message::ProfileActiveWriteToRom(id as u8),
+          false => Message::ProfileActiveWriteToRam(id as u8),
+        };
+        Task::done(Message::ProfileLoadLocal(idx)).chain(Task::done(write))
+      }
+      Message::SlotPull(id) => {
+        let (Some((status, Some(idx))), Some(slot)) =
+          (self.slot_sync(id), self.profiles_keypad_vec.get(id - 1))
+        else {
+          return Task::none();
+        };
+        if !status.is_diverged() {
+          return Task::none();
+        }
+
+        // Имя на устройстве может быть обрезано: полное имя из библиотеки сохраняется
+        let mut profile = slot.clone();
+        let library = &self.profiles_local_vec[idx];
+        let named = Profile {
+          name: library.name.clone(),
+          ..profile.clone()
+        };
+        if profile.device_eq(&named, self.settings.name_encoding) {
+          profile.name = library.name.clone();
+        }
+        // Метаданные на устройстве не хранятся и берутся из библиотеки
+        profile.meta = library.meta.clone();
+
+        if self.history_id() == Some(idx) {
+          self.profile = profile.clone();
+        }
+        let link = SlotLink::new(&profile);
+        let save = self.library_store(idx, profile);
+        self.slot_link_set(id, Some(link));
+        save
       }
       Message::ProfileRequestActiveNum => {
         let mut buf = self.buffers.clone();
@@ -480,7 +1479,8 @@ impl State {
         Task::none()
       }
       Message::ProfileUpdateName(string) => {
-        if string.len() >= 16 {
+        // Байтовую длину показывает счётчик, лишнее обрезается при записи на устройство
+        if string.chars().count() > NAME_LEN {
           return Task::none();
         }
 
@@ -495,23 +1495,13 @@ impl State {
         self.is_rom = !self.is_rom;
 
         match self.is_rom {
-          true => {
-            let buf = self.buffers.clone();
-            Task::perform(
-              async move {
-                tokio::task::spawn_blocking(move || {
-                  buf.send().push(&profile::Command::LoadFlashToRam)
-                })
-                .await
-              },
-              |_| Message::ProfileReceiveKeypadVec,
-            )
-          }
+          true => Task::done(Message::ProfileReceiveRomVec),
           false => Task::none(),
         }
       }
       Message::AllowWriteButtonCombination => {
         self.allow_write = true;
+        self.capture = Chord::default();
         self.time_write = Some(std::time::Instant::now());
         Task::none()
       }
@@ -523,31 +1513,289 @@ impl State {
           self.profile.clone(),
         )))
       }
-      Message::ClearButtonCombination(id, is_stick) => {
-        match is_stick {
-          true => self.profile.stick.word[id - 1] = 0,
-          false => self.profile.buttons[id - 1] = [0u8; 6],
+      Message::CellModifiers(modifiers) => {
+        self.cells.modifiers = modifiers;
+        Task::none()
+      }
+      Message::CellHover(cell) => {
+        self.cells.hovered = Some(cell);
+        Task::none()
+      }
+      Message::CellLeave(cell) => {
+        self.cells.hovered.take_if(|hovered| *hovered == cell);
+        Task::none()
+      }
+      Message::CellPointer(position) => {
+        self.cells.drag_move(position);
+        Task::none()
+      }
+      Message::CellDragStart(cell) => {
+        self.cells.drag_start(cell);
+        Task::none()
+      }
+      Message::CellDragEnd(to) => {
+        // Без заметного сдвига курсора нажатие — обычный клик по ячейке
+        let from = match self.cells.drag_end() {
+          Some(from) if from != to && !self.profile_on_keypad => from,
+          Some(_) => return Task::none(),
+          None => {
+            return Task::done(match to {
+              Cell::Button(id) => Message::GetButtonSettings(id, false),
+              Cell::Stick(id) => Message::GetButtonSettings(id, true),
+            });
+          }
         };
 
-        Task::done(Message::DisallowWriteButtonCombination)
+        // С зажатым Ctrl назначение копируется, без него ячейки меняются местами
+        let mut profile = self.profile.clone();
+        let result = match self.cells.modifiers.command() {
+          true => to.put(&mut profile, from.content(&self.profile)),
+          false => cells::swap(&mut profile, from, to),
+        };
+        self.cells.selected = vec![to];
+        self.cells_apply(profile, result)
       }
-      Message::WriteButtonCombination(code) => {
-        self.time_write = Some(std::time::Instant::now());
-
-        match self.button.is_stick {
-          true => {
-            if !self.button.vec_str.is_empty() {
-              return Task::none();
-            }
-          }
-          false => {
-            if self.button.vec_str.len() >= 6 {
+      Message::CellDragCancel => {
+        self.cells.drag_end();
+        Task::none()
+      }
+      Message::CellMenu(cell) => {
+        if !self.cells.is_selected(cell) {
+          self.cells.selected = vec![cell];
+        }
+        self.cells.menu = Some(cell);
+        self.cells.status = None;
+        Task::none()
+      }
+      Message::CellMenuClose => {
+        self.cells.menu = None;
+        Task::none()
+      }
+      Message::CellCopy => {
+        self.cells.copy(&self.profile);
+        self.cells.menu = None;
+        self.cells.status = Some(format!(
+          "Скопировано назначений: {}",
+          self.cells.clipboard.len()
+        ));
+        Task::none()
+      }
+      Message::CellPaste => {
+        if self.profile_on_keypad || self.cells.clipboard.is_empty() {
+          return Task::none();
+        }
+        let mut profile = self.profile.clone();
+        let result = cells::paste(&mut profile, &self.cells.clipboard, &self.cells.selected);
+        self.cells_apply(profile, result)
+      }
+      Message::CellSwap => {
+        let [a, b] = self.cells.selected[..] else {
+          return Task::none();
+        };
+        if self.profile_on_keypad {
+          return Task::none();
+        }
+        let mut profile = self.profile.clone();
+        let result = cells::swap(&mut profile, a, b);
+        self.cells_apply(profile, result)
+      }
+      Message::CellClear => {
+        if self.profile_on_keypad {
+          return Task::none();
+        }
+        let mut profile = self.profile.clone();
+        for cell in &self.cells.selected {
+          let _ = cell.put(&mut profile, Content::default());
+        }
+        self.cells_apply(profile, Ok(()))
+      }
+      Message::CellApplyToLibrary => {
+        self.cells.menu = None;
+        if self.cells.selected.is_empty() {
+          return Task::none();
+        }
+
+        let assignments = self
+          .cells
+          .selected
+          .iter()
+          .map(|&cell| (cell, cell.get(&self.profile)))
+          .collect::<Vec<_>>();
+        let mut changed = 0;
+        for idx in 0..self.profiles_local_vec.len() {
+          let mut profile = self.profiles_local_vec[idx].clone();
+          for (cell, codes) in &assignments {
+            let _ = cell.set(&mut profile, *codes);
+          }
+          if profile != self.profiles_local_vec[idx] {
+            self.library_commit(idx, profile);
+            changed += 1;
+          }
+        }
+
+        let cells = assignments
+          .iter()
+          .map(|(cell, _)| cell.title())
+          .collect::<Vec<_>>()
+          .join(", ");
+        self.cells.status = Some(format!("{cells}: изменено профилей библиотеки: {changed}"));
+        match changed {
+          0 => Task::none(),
+          _ => Task::done(Message::LibrarySave),
+        }
+      }
+      Message::KeymapToggle => {
+        self.keymap_open = !self.keymap_open;
+        Task::none()
+      }
+      Message::KeymapOpen => Keymap::open_file_dialog(),
+      Message::KeymapLoaded(keymap) => {
+        self.keymap_status = Some(match keymap.skipped.len() {
+          0 => format!("Импортировано команд: {}", keymap.commands.len()),
+          skipped => format!(
+            "Импортировано команд: {}, пропущено сочетаний: {skipped}",
+            keymap.commands.len()
+          ),
+        });
+        self.keymap_query.clear();
+        self.keymap = Some(keymap);
+        Task::none()
+      }
+      Message::KeymapStatus(status) => {
+        self.keymap_status = Some(status);
+        Task::none()
+      }
+      Message::KeymapSearch(query) => {
+        self.keymap_query = query;
+        Task::none()
+      }
+      Message::KeymapAssign(idx) => {
+        let (id, is_stick) = (self.button.id, self.button.is_stick);
+        let Some(command) = self
+          .keymap
+          .as_ref()
+          .and_then(|keymap| keymap.commands.get(idx))
+          .cloned()
+        else {
+          return Task::none();
+        };
+        if id == 0 || self.profile_on_keypad {
+          self.keymap_status = Some("Выберите кнопку".to_string());
+          return Task::none();
+        }
+        if is_stick && command.codes[1..].iter().any(|&c| c != 0) {
+          self.keymap_status = Some("Направлению стика назначается одна клавиша".to_string());
+          return Task::none();
+        }
+
+        self.button_set_codes(command.codes);
+        if !is_stick {
+          self
+            .profile
+            .meta
+            .update_button(id, |meta| meta.caption = command.title.clone());
+        }
+        self.keymap_status = Some(format!("«{}» ({}) назначена", command.title, command.keys));
+        Task::done(Message::ProfileSave((
+          self.local_profile_id.unwrap_or(0),
+          self.profile.clone(),
+        )))
+      }
+      Message::KeymapClose => {
+        self.keymap = None;
+        self.keymap_query.clear();
+        self.keymap_status = None;
+        Task::none()
+      }
+      Message::MetaToggle => {
+        self.meta_open = !self.meta_open;
+        Task::none()
+      }
+      Message::MetaDescription(description) => {
+        self.meta_edit(|meta| meta.description = description)
+      }
+      Message::MetaAuthor(author) => self.meta_edit(|meta| meta.author = author),
+      Message::MetaTagInput(tag) => {
+        self.meta_tag = tag;
+        Task::none()
+      }
+      Message::MetaTagAdd => {
+        let tag = std::mem::take(&mut self.meta_tag);
+        self.meta_edit(|meta| {
+          meta.add_tag(&tag);
+        })
+      }
+      Message::MetaTagRemove(i) => self.meta_edit(|meta| {
+        if i < meta.tags.len() {
+          meta.tags.remove(i);
+        }
+      }),
+      Message::MetaCaption(caption) => self.meta_button_edit(|meta| meta.caption = caption),
+      Message::MetaNote(note) => self.meta_button_edit(|meta| meta.note = note),
+      Message::MetaColor(color) => self.meta_button_edit(|meta| meta.color = color),
+      Message::CapturePressed(physical, key, location) => {
+        // Повторное нажатие удерживаемой клавиши — автоповтор
+        if !self.capture.press(physical, key, location) {
+          return Task::none();
+        }
+        self.time_write = Some(std::time::Instant::now());
+
+        match (self.settings.capture.mode, by_physical(physical)) {
+          (CaptureMode::Sequential, Some(key)) => {
+            Task::done(Message::WriteButtonCombination(Some(key.code)))
+          }
+          _ => Task::none(),
+        }
+      }
+      Message::CaptureReleased(key, location) => {
+        // Отпускание учитывается в любом режиме, чтобы клавишу можно было нажать снова
+        let is_done = self.capture.release(&key, location);
+        if self.settings.capture.mode != CaptureMode::Chord || !is_done {
+          return Task::none();
+        }
+
+        let codes = self.capture.take_codes();
+        self.allow_write = false;
+        self.time_write = None;
+        if codes.is_empty() {
+          return Task::none();
+        }
+
+        let id = self.button.id;
+        match self.button.is_stick {
+          true => self.button.code[id - 1] = codes[0],
+          false => self.button.code = codes.into_iter().take(6).collect(),
+        }
+
+        Task::done(Message::SaveButtonCombination(id))
+      }
+      Message::CaptureModeSelect(mode) => {
+        self.settings.capture.mode = mode;
+        self.settings.save();
+        Task::none()
+      }
+      Message::CaptureNormalizeToggle(normalize) => {
+        self.settings.capture.normalize_modifiers = normalize;
+        self.settings.save();
+        Task::none()
+      }
+      Message::WriteButtonCombination(code) => {
+        self.time_write = Some(std::time::Instant::now());
+
+        match self.button.is_stick {
+          true => {
+            if !self.button.vec_str.is_empty() {
+              return Task::none();
+            }
+          }
+          false => {
+            if self.button.vec_str.len() >= 6 {
               return Task::none();
             }
           }
         }
 
-        let elem = Profile::code_to_title(code.unwrap_or(0));
+        let elem = code_to_title(code.unwrap_or(0));
         let code = code.unwrap_or(0);
 
         if self.button.vec_str.contains(&elem) {
@@ -575,6 +1823,9 @@ impl State {
           }
           false => {
             code.resize(6, 0);
+            if self.settings.capture.normalize_modifiers {
+              normalize_modifiers(&mut code);
+            }
             self.profile.buttons[id - 1] = code.try_into().unwrap();
           }
         }
@@ -589,6 +1840,62 @@ impl State {
         )))
         // Task::none()
       }
+      Message::KeyPickerOpen => {
+        self.allow_write = false;
+        self.time_write = None;
+        self.key_picker = Some(KeyPicker::default());
+        Task::none()
+      }
+      Message::KeyPickerCategory(category) => {
+        if let Some(picker) = self.key_picker.as_mut() {
+          picker.category = category;
+        }
+        Task::none()
+      }
+      Message::KeyPickerKey(code) => match self.button.id {
+        0 => Task::none(),
+        _ => Task::done(Message::WriteButtonCombination(Some(code))),
+      },
+      Message::KeyPickerInput(text) => {
+        if let Some(picker) = self.key_picker.as_mut() {
+          picker.text = text;
+          picker.error = None;
+        }
+        Task::none()
+      }
+      Message::KeyPickerSubmit => {
+        let (id, is_stick) = (self.button.id, self.button.is_stick);
+        let Some(picker) = self.key_picker.as_mut() else {
+          return Task::none();
+        };
+        if id == 0 {
+          picker.error = Some("Выберите кнопку".to_string());
+          return Task::none();
+        }
+
+        let codes = match parse_combination(&picker.text) {
+          Ok(codes) if is_stick && codes[1..].iter().any(|&c| c != 0) => {
+            picker.error = Some("Направлению стика назначается одна клавиша".to_string());
+            return Task::none();
+          }
+          Ok(codes) => codes,
+          Err(err) => {
+            picker.error = Some(format!("{err:#}"));
+            return Task::none();
+          }
+        };
+        picker.text.clear();
+
+        self.button_set_codes(codes);
+        Task::done(Message::ProfileSave((
+          self.local_profile_id.unwrap_or(0),
+          self.profile.clone(),
+        )))
+      }
+      Message::KeyPickerClose => {
+        self.key_picker = None;
+        Task::none()
+      }
       Message::WriteDeadZone(deadzone) => {
         self.profile.stick.deadzone = deadzone;
         Task::done(Message::ProfileSave((
@@ -642,6 +1949,368 @@ impl State {
         self.stick_show_calibrate_parameters = false;
         Task::none()
       }
+      Message::BackupCreate(is_auto) => {
+        if !self.keypad.is_open {
+          return Task::none();
+        }
+
+        self.profile_write = true;
+        let buffers = self.buffers.clone();
+        Task::perform(Self::backup_save(buffers, is_auto), Message::BackupCreated)
+      }
+      Message::BackupCreated(res) => {
+        self.profile_write = false;
+        Task::done(Message::BackupStatus(Self::backup_status_text(res)))
+      }
+      Message::RomWritten(res) => {
+        let status = match res {
+          Ok(path) => Self::backup_status_text(Ok(path)),
+          Err(err) => {
+            // Сохранение перед выходом не удалось: повторно спрашиваем пользователя
+            if let Some(id) = self.window_close_id.take() {
+              return Task::batch([
+                Task::done(Message::BackupStatus(err)),
+                Task::done(Message::ProfileReceiveRomVec),
+                Task::done(Message::WindowCloseRequested(id)),
+              ]);
+            }
+            err
+          }
+        };
+
+        Task::batch([
+          Task::done(Message::BackupStatus(status)),
+          Task::done(Message::ProfileReceiveRomVec),
+        ])
+      }
+      Message::BackupStatus(status) => {
+        info!("backup: {status}");
+        self.backup_status = Some(status);
+        Task::none()
+      }
+      Message::BackupRestoreOpen => Backup::open_load_file_dialog(),
+      Message::BackupRestoreLoaded(backup) => {
+        self.backup_selection = RestoreSelection::new(&backup, &self.device_info);
+        self.backup_restore = Some(backup);
+        Task::none()
+      }
+      Message::BackupRestoreToggleRam(id) => {
+        self.backup_selection.ram[id - 1] = !self.backup_selection.ram[id - 1];
+        Task::none()
+      }
+      Message::BackupRestoreToggleRom(id) => {
+        self.backup_selection.rom[id - 1] = !self.backup_selection.rom[id - 1];
+        Task::none()
+      }
+      Message::BackupRestoreToggleCalibration => {
+        self.backup_selection.calibration = !self.backup_selection.calibration;
+        Task::none()
+      }
+      Message::BackupRestoreApply => {
+        let Some(backup) = self.backup_restore.take() else {
+          return Task::none();
+        };
+
+        let selection = self.backup_selection.clone();
+
+        // Восстанавливаемые профили проверяются, как и любая запись на устройство
+        let restored = (backup.ram.iter().zip(selection.ram))
+          .chain(backup.rom.iter().zip(selection.rom))
+          .filter(|(_, selected)| *selected)
+          .map(|(profile, _)| profile.clone())
+          .collect::<Vec<_>>();
+        if !restored
+          .iter()
+          .all(|profile| self.lint_profile_before_write(profile))
+        {
+          let status = self.library_status.clone().unwrap_or_default();
+          self.backup_restore = Some(backup);
+          return Task::done(Message::BackupStatus(status));
+        }
+
+        if selection.has_rom() {
+          return self.rom_write_task(move |buf| backup_restore(buf, &backup, &selection));
+        }
+
+        self.profile_write = true;
+        let mut buf = self.buffers.clone();
+        Task::perform(
+          async move {
+            tokio::task::spawn_blocking(move || backup_restore(&mut buf, &backup, &selection)).await
+          },
+          |_| Message::ProfileReceiveRomVec,
+        )
+      }
+      Message::BackupRestoreCancel => {
+        self.backup_restore = None;
+        Task::none()
+      }
+      Message::BankNew => {
+        self.banks.push(Bank::default());
+        self.bank_id = Some(self.banks.len() - 1);
+        Task::done(Message::LibrarySave)
+      }
+      Message::BankCapture => {
+        if self.profiles_keypad_vec.len() != 4 {
+          return Task::none();
+        }
+
+        // Ячейка ссылается на совпадающий профиль библиотеки,
+        // профили, которых в библиотеке нет, добавляются в неё
+        let mut bank = Bank {
+          name: format!("Кейпад {}", self.device_info.serial_num),
+          ..Default::default()
+        };
+        for (i, slot) in self.profiles_keypad_vec.clone().into_iter().enumerate() {
+          let encoding = self.settings.name_encoding;
+          let found = self
+            .profiles_local_vec
+            .iter()
+            .find(|profile| profile.device_eq(&slot, encoding))
+            .map(|profile| profile.name.clone());
+          bank.profiles[i] = Some(match found {
+            Some(name) => name,
+            None => {
+              let mut profile = slot;
+              profile.name = self.library_free_name(profile.name.trim_end_matches('\0'));
+              profile.meta.mark_created();
+              let name = profile.name.clone();
+              self.profiles_local_vec.push(profile);
+              name
+            }
+          });
+        }
+
+        self.banks.push(bank);
+        self.bank_id = Some(self.banks.len() - 1);
+        Task::done(Message::LibrarySave)
+      }
+      Message::BankRemove(idx) => {
+        if self.bank_progress.is_some() {
+          return Task::none();
+        }
+
+        self.banks.remove(idx);
+        self.bank_id = match self.bank_id {
+          Some(id) if id == idx => None,
+          Some(id) if id > idx => Some(id - 1),
+          id => id,
+        };
+        Task::done(Message::LibrarySave)
+      }
+      Message::BankSelect(idx) => {
+        self.bank_id = Some(idx);
+        self.bank_status = None;
+        Task::none()
+      }
+      Message::BankRename(name) => {
+        let Some(bank) = self.bank_id.and_then(|id| self.banks.get_mut(id)) else {
+          return Task::none();
+        };
+
+        bank.name = name;
+        Task::done(Message::LibrarySave)
+      }
+      Message::BankSetSlot(slot, idx) => {
+        let (Some(bank), Some(profile)) = (
+          self.bank_id.and_then(|id| self.banks.get_mut(id)),
+          self.profiles_local_vec.get(idx),
+        ) else {
+          return Task::none();
+        };
+
+        bank.profiles[slot - 1] = Some(profile.name.clone());
+        Task::done(Message::LibrarySave)
+      }
+      Message::BankWrite(idx, to_rom) => {
+        if !self.keypad.is_open || self.bank_progress.is_some() {
+          return Task::none();
+        }
+        let Some(bank) = self.banks.get(idx) else {
+          return Task::none();
+        };
+
+        // Профили набора берутся из библиотеки: набор с ненайденными профилями не записывается
+        let profiles = match bank.resolve(&self.profiles_local_vec) {
+          Ok(profiles) => profiles,
+          Err(err) => {
+            self.bank_status = Some(format!("Запись отменена: {err}"));
+            return Task::none();
+          }
+        };
+
+        // Профили набора с ошибками не записываются
+        let blocked = profiles.iter().find_map(|profile| {
+          let problems = lint(profile, &self.settings.lint, self.settings.name_encoding);
+          has_errors(&problems)
+            .then(|| summary(&profile.name, &problems))
+            .flatten()
+        });
+        if let Some(status) = blocked {
+          self.bank_status = Some(format!("Запись отменена. {status}"));
+          return Task::none();
+        }
+
+        self.bank_status = None;
+        self.bank_progress = Some(BankWrite {
+          name: bank.name.clone(),
+          profiles,
+          to_rom,
+          active_id: self.keypad_active_id(),
+          written: 0,
+          failed: Vec::new(),
+          verifying_rom: false,
+        });
+
+        self.profile_write = true;
+        match to_rom {
+          // Как и `rom_write_task`, запись в ПЗУ начинается только после резервной копии
+          true => {
+            let buffers = self.buffers.clone();
+            Task::perform(Self::backup_save(buffers, true), Message::BankBackupCreated)
+          }
+          false => Task::done(Message::BankWriteSlot(1)),
+        }
+      }
+      Message::BankBackupCreated(res) => match res {
+        Ok(path) => Task::batch([
+          Task::done(Message::BackupStatus(Self::backup_status_text(Ok(path)))),
+          Task::done(Message::BankWriteSlot(1)),
+        ]),
+        Err(err) => {
+          self.bank_progress = None;
+          self.profile_write = false;
+          self.bank_status = Some(format!(
+            "Не удалось создать резервную копию, запись в ПЗУ отменена: {err}"
+          ));
+          Task::none()
+        }
+      },
+      Message::BankWriteSlot(slot) => {
+        let Some(progress) = &self.bank_progress else {
+          return Task::none();
+        };
+
+        // Записываются профили, подставленные при начале записи:
+        // перечитывание библиотеки во время записи на неё не влияет
+        let to_rom = progress.to_rom;
+        let profile = progress.profiles[slot - 1].clone();
+
+        let mut buffers = self.buffers.clone();
+        Task::perform(
+          async move { slot_write_verify(&mut buffers, slot, profile, to_rom).await },
+          move |res| Message::BankSlotWritten(slot, matches!(res, Ok(true))),
+        )
+      }
+      Message::BankSlotWritten(slot, verified) => {
+        if let Some(progress) = &self.bank_progress {
+          let link = SlotLink::new(&progress.profiles[slot - 1]);
+          self.slot_link_set(slot, Some(link));
+        }
+        let Some(progress) = &mut self.bank_progress else {
+          return Task::none();
+        };
+
+        progress.written = slot;
+        if !verified {
+          progress.failed.push(slot);
+        }
+
+        if slot < 4 {
+          return Task::done(Message::BankWriteSlot(slot + 1));
+        }
+
+        self
+          .buffers
+          .send()
+          .push(&profile::Command::LoadRamToActive(progress.active_id));
+
+        // Содержимое ПЗУ проверяется после перечитывания профилей
+        match progress.to_rom {
+          true => progress.verifying_rom = true,
+          false => {
+            let progress = self.bank_progress.take().unwrap();
+            self.bank_status = Some(self.bank_write_status(progress));
+          }
+        }
+
+        // Опрос активного профиля возобновляется после перечитывания профилей
+        Task::done(Message::ProfileReceiveRomVec)
+      }
+      Message::TemplatesLoaded((templates, errors)) => {
+        // Выбор сохраняется, если шаблон с тем же названием остался на месте
+        let selected = self
+          .template_id
+          .and_then(|id| self.templates.get(id))
+          .map(|template| template.title.clone());
+        self.template_id = selected.and_then(|title| {
+          templates
+            .iter()
+            .position(|template| template.title == title)
+        });
+        self.templates = templates;
+        self.template_errors = errors;
+        Task::none()
+      }
+      Message::TemplateSelect(idx) => {
+        self.template_id = Some(idx);
+        self.template_status = None;
+        Task::none()
+      }
+      Message::TemplateSlotSelect(id) => {
+        self.template_slot = id;
+        Task::none()
+      }
+      Message::TemplateNewProfile => {
+        let Some(template) = self.template_id.and_then(|id| self.templates.get(id)) else {
+          return Task::none();
+        };
+        let mut profile = match template.new_profile() {
+          Ok(profile) => profile,
+          Err(err) => {
+            self.template_status = Some(format!("Шаблон не применён: {err:#}"));
+            return Task::none();
+          }
+        };
+        profile.name = self.library_free_name(&profile.name);
+
+        let idx = self.profiles_local_vec.len();
+        self.template_status = Some(format!("Создан профиль «{}»", profile.name));
+        self.profiles_local_vec.push(profile);
+        Task::done(Message::LibrarySave)
+          .chain(Task::done(Message::ProfileLoadLocal(idx)))
+          .chain(Task::done(Message::ChangePage(Pages::Profiles)))
+      }
+      Message::TemplateToSlot => {
+        let Some(template) = self.template_id.and_then(|id| self.templates.get(id)) else {
+          return Task::none();
+        };
+        let id = self.template_slot;
+        let profiles = match self.is_rom {
+          true => &self.profiles_rom_vec,
+          false => &self.profiles_keypad_vec,
+        };
+        // Шаблон стика меняет только направления стика профиля ячейки
+        let mut profile = profiles.get(id - 1).cloned().unwrap_or_default();
+        profile.name = profile.name.trim_end_matches('\0').to_string();
+        if let Err(err) = template.apply(&mut profile) {
+          self.template_status = Some(format!("Шаблон не применён: {err:#}"));
+          return Task::none();
+        }
+        let title = template.title.clone();
+        // Шаблон может дать некорректный профиль ячейки: он проверяется, как и любая запись
+        if !self.lint_profile_before_write(&profile) {
+          self.template_status = self.library_status.clone();
+          return Task::none();
+        }
+
+        self.template_status = Some(format!("«{title}» записан в ячейку {id}"));
+        self.slot_link_set(id, None);
+        let active_id = self.keypad_active_id();
+        self.keypad_write_task(self.is_rom, move |buf, to_rom| {
+          slot_write(buf, id, profile, to_rom, active_id)
+        })
+      }
       Message::GetDeviceInfo => {
         let mut buffers = self.buffers.clone();
         Task::perform(
@@ -663,15 +2332,542 @@ impl State {
         Task::none()
       }
       Message::TimerWriteCheck => {
+        // Пока удерживаются клавиши аккорда, отсчёт приостановлен
         if let Some(start_time) = self.time_write
-          && start_time.elapsed() >= Duration::from_secs(2)
+          && !self.capture.is_held()
+          && start_time.elapsed() >= self.settings.capture.mode.timeout()
         {
           self.allow_write = false;
           self.time_write = None;
-          return Task::done(Message::ProfilesExport);
+          return Task::done(Message::LibrarySave);
         }
         Task::none()
       }
     }
   }
+
+  /**
+  Записывает изменение профиля библиотеки в его историю
+
+  Профиль без истории (не открывался из списка) начинает её
+  с состояния до изменения.
+
+  # Аргументы
+  * `idx` - Индекс профиля в библиотеке
+  * `profile` - Состояние профиля после изменения
+  */
+  fn history_track(&mut self, idx: usize, profile: &Profile) {
+    let history = self
+      .histories
+      .entry(idx)
+      .or_insert_with(|| History::new(self.profiles_local_vec[idx].clone()));
+    if let Some(change) = Change::between(history.current(), profile) {
+      history.record(change, profile.clone());
+    }
+  }
+
+  /**
+  Заменяет комбинацию редактируемой кнопки или направления стика
+
+  Направлению стика назначается первая клавиша комбинации.
+
+  # Аргументы
+  * `codes` - Коды новой комбинации
+  */
+  fn button_set_codes(&mut self, codes: [u8; 6]) {
+    let id = self.button.id;
+    self.button.code = match self.button.is_stick {
+      true => {
+        self.profile.stick.word[id - 1] = codes[0];
+        self.profile.stick.word.to_vec()
+      }
+      false => {
+        self.profile.buttons[id - 1] = codes;
+        codes.into_iter().filter(|&c| c != 0).collect()
+      }
+    };
+    self.button.vec_str = codes
+      .into_iter()
+      .filter(|&c| c != 0)
+      .map(code_to_title)
+      .collect();
+  }
+
+  /**
+  Изменяет метаданные редактируемого профиля библиотеки
+
+  # Аргументы
+  * `f` - Изменение метаданных
+  */
+  fn meta_edit(&mut self, f: impl FnOnce(&mut ProfileMeta)) -> Task<Message> {
+    if self.profile_on_keypad {
+      return Task::none();
+    }
+
+    f(&mut self.profile.meta);
+    Task::done(Message::ProfileSave((
+      self.local_profile_id.unwrap_or(0),
+      self.profile.clone(),
+    )))
+  }
+
+  /**
+  Изменяет метаданные выбранной кнопки редактируемого профиля
+
+  Для направлений стика метаданные не хранятся.
+
+  # Аргументы
+  * `f` - Изменение метаданных кнопки
+  */
+  fn meta_button_edit(&mut self, f: impl FnOnce(&mut ButtonMeta)) -> Task<Message> {
+    let id = self.button.id;
+    if id == 0 || self.button.is_stick {
+      return Task::none();
+    }
+    self.meta_edit(|meta| meta.update_button(id, f))
+  }
+
+  /// Записывает настройки, если время открытия профилей ещё не сохранено
+  fn library_touched_save(&mut self) {
+    if std::mem::take(&mut self.library_touched) {
+      self.settings.save();
+    }
+  }
+
+  /**
+  Подбирает имя нового профиля, не совпадающее с профилями библиотеки
+
+  # Аргументы
+  * `base` - Желаемое имя; при совпадении к нему добавляется номер
+  */
+  fn library_free_name(&self, base: &str) -> String {
+    let is_free = |name: &str| !self.profiles_local_vec.iter().any(|p| p.name == name);
+    match is_free(base) {
+      true => base.to_string(),
+      false => (2..)
+        .map(|n| format!("{base} {n}"))
+        .find(|name| is_free(name))
+        .unwrap_or_else(|| base.to_string()),
+    }
+  }
+
+  /**
+  Находит профиль в библиотеке после её перечитывания
+
+  Сначала ищется совпадающий профиль, затем профиль с тем же именем,
+  изменённый вне редактора.
+
+  # Аргументы
+  * `profile` - Профиль до перечитывания
+
+  # Возвращает
+  Новый индекс профиля или `None`, если профиля больше нет
+  */
+  fn library_find(&self, profile: &Profile) -> Option<usize> {
+    let profiles = &self.profiles_local_vec;
+    profiles
+      .iter()
+      .position(|p| p == profile)
+      .or_else(|| profiles.iter().position(|p| p.name == profile.name))
+  }
+
+  /**
+  Сохраняет изменённый профиль библиотеки
+
+  Обновляет время изменения и историю профиля. Переименованный профиль
+  остаётся связанным с ячейками и сохраняет отметку избранного.
+
+  # Аргументы
+  * `idx` - Индекс профиля в библиотеке
+  * `profile` - Состояние профиля после изменения
+  */
+  fn library_store(&mut self, idx: usize, profile: Profile) -> Task<Message> {
+    if idx >= self.profiles_local_vec.len() {
+      return Task::none();
+    }
+
+    self.library_commit(idx, profile);
+    Task::done(Message::LibrarySave)
+  }
+
+  /**
+  Изменяет профиль библиотеки без сохранения библиотеки в файл
+
+  Используется при изменении нескольких профилей, которые затем
+  сохраняются одним `Message::LibrarySave`.
+
+  # Аргументы
+  * `idx` - Индекс профиля в библиотеке
+  * `profile` - Состояние профиля после изменения
+  */
+  fn library_commit(&mut self, idx: usize, mut profile: Profile) {
+    self.library_touch(idx, &mut profile);
+    if self.local_profile_id == Some(idx) {
+      self.profile.meta.modified = profile.meta.modified;
+    }
+    self.history_track(idx, &profile);
+    self.library_replace(idx, profile);
+  }
+
+  /**
+  Заменяет профиль библиотеки, сохраняя связи с его именем
+
+  При переименовании связи ячеек, ссылки наборов и отметки списка библиотеки
+  переносятся на новое имя. История и время изменения не обновляются.
+
+  # Аргументы
+  * `idx` - Индекс профиля в библиотеке
+  * `profile` - Новое состояние профиля
+  */
+  fn library_replace(&mut self, idx: usize, profile: Profile) {
+    let old_name = &self.profiles_local_vec[idx].name;
+    if *old_name != profile.name {
+      let old_name = old_name.clone();
+      let mut renamed = self.settings.library_view.rename(&old_name, &profile.name);
+      for link in self.settings.slot_links.iter_mut().flatten() {
+        if link.profile == old_name {
+          link.profile = profile.name.clone();
+          renamed = true;
+        }
+      }
+      if renamed {
+        self.settings.save();
+      }
+      for bank in &mut self.banks {
+        bank.rename(&old_name, &profile.name);
+      }
+    }
+
+    self.profiles_local_vec[idx] = profile;
+  }
+
+  /**
+  Отмечает время изменения профиля библиотеки
+
+  Время обновляется, только если профиль отличается от сохранённого.
+
+  # Аргументы
+  * `idx` - Индекс профиля в библиотеке
+  * `profile` - Состояние профиля после изменения
+  */
+  fn library_touch(&self, idx: usize, profile: &mut Profile) {
+    if self.profiles_local_vec.get(idx) != Some(profile) {
+      profile.meta.mark_modified();
+    }
+  }
+
+  /**
+  Запоминает связь ячейки с профилем библиотеки
+
+  # Аргументы
+  * `id` - Номер ячейки (1..=4)
+  * `link` - Связь или `None`, если в ячейке не профиль библиотеки
+  */
+  fn slot_link_set(&mut self, id: usize, link: Option<SlotLink>) {
+    if self.settings.slot_links[id - 1] != link {
+      self.settings.slot_links[id - 1] = link;
+      self.settings.save();
+    }
+  }
+
+  /**
+  Запоминает связь ячейки с редактируемым профилем после записи
+
+  Профиль кейпада, записанный в другую ячейку, связи не создаёт.
+
+  # Аргументы
+  * `id` - Номер ячейки (1..=4)
+  */
+  fn slot_link_written(&mut self, id: usize) {
+    let link = self.history_id().map(|_| SlotLink::new(&self.profile));
+    self.slot_link_set(id, link);
+  }
+
+  /**
+  Сохраняет профиль стороны сравнения, получившей перенесённые поля
+
+  Профиль библиотеки обновляется вместе с историей и открытым в редакторе
+  профилем. Профили кейпада и файлов меняются только в сравнении: их можно
+  сохранить в библиотеку как новый профиль.
+
+  # Аргументы
+  * `side` - Изменённая сторона сравнения
+  */
+  fn compare_apply(&mut self, side: Option<Side>) -> Task<Message> {
+    let Some(Side {
+      source: Source::Library(idx),
+      profile,
+    }) = side
+    else {
+      return Task::none();
+    };
+    if idx >= self.profiles_local_vec.len() {
+      return Task::none();
+    }
+
+    if self.history_id() == Some(idx) {
+      self.profile = profile.clone();
+    }
+    self.library_store(idx, profile)
+  }
+
+  /**
+  Применяет к редактируемому профилю результат операции с ячейками
+
+  Режим записи комбинации и контекстное меню закрываются, изменённый
+  профиль сохраняется в библиотеку.
+
+  # Аргументы
+  * `profile` - Профиль после операции
+  * `result` - Результат операции; ошибка выводится под сеткой кнопок
+  */
+  fn cells_apply(&mut self, profile: Profile, result: Result<(), String>) -> Task<Message> {
+    self.allow_write = false;
+    self.time_write = None;
+    self.cells.menu = None;
+    self.cells.status = result.err();
+
+    if profile == self.profile {
+      return Task::none();
+    }
+    self.profile = profile;
+    Task::done(Message::ProfileSave((
+      self.local_profile_id.unwrap_or(0),
+      self.profile.clone(),
+    )))
+  }
+
+  /**
+  Добавляет в историю редактируемого профиля запись о действии
+
+  # Аргументы
+  * `change` - Вид действия
+  */
+  fn history_record(&mut self, change: Change) {
+    let Some(id) = self.history_id() else {
+      return;
+    };
+    let profile = self.profile.clone();
+    self
+      .histories
+      .entry(id)
+      .or_insert_with(|| History::new(profile.clone()))
+      .record(change, profile);
+  }
+
+  /**
+  Применяет состояние профиля из истории
+
+  Режим записи комбинации и экранная клавиатура закрываются,
+  профиль сохраняется в библиотеку.
+
+  # Аргументы
+  * `profile` - Состояние из истории или `None`, если переход невозможен
+  */
+  fn history_apply(&mut self, profile: Option<Profile>) -> Task<Message> {
+    let Some(profile) = profile else {
+      return Task::none();
+    };
+
+    self.allow_write = false;
+    self.time_write = None;
+    self.key_picker = None;
+    self.profile = profile.clone();
+
+    match self.history_id() {
+      Some(idx) if idx < self.profiles_local_vec.len() => {
+        self.library_replace(idx, profile);
+        Task::done(Message::LibrarySave)
+      }
+      _ => Task::none(),
+    }
+  }
+
+  /**
+  Проверяет редактируемый профиль перед записью на устройство
+
+  Итог проверки выводится в строку состояния библиотеки.
+
+  # Возвращает
+  `false`, если в профиле есть ошибки и запись нужно отменить
+  */
+  fn lint_before_write(&mut self) -> bool {
+    let profile = self.profile.clone();
+    self.lint_profile_before_write(&profile)
+  }
+
+  /**
+  Проверяет профиль перед записью на устройство
+
+  Итог проверки выводится в строку состояния библиотеки.
+
+  # Аргументы
+  * `profile` - Записываемый профиль
+
+  # Возвращает
+  `false`, если в профиле есть ошибки и запись нужно отменить
+  */
+  fn lint_profile_before_write(&mut self, profile: &Profile) -> bool {
+    let problems = lint(profile, &self.settings.lint, self.settings.name_encoding);
+    let Some(status) = summary(&profile.name, &problems) else {
+      return true;
+    };
+
+    match has_errors(&problems) {
+      true => {
+        self.library_status = Some(format!("Запись отменена. {status}"));
+        false
+      }
+      false => {
+        self.library_status = Some(status);
+        true
+      }
+    }
+  }
+
+  /**
+  Возвращает профиль ячейки `id` из ПЗУ, если включён режим ПЗУ
+
+  В режиме ОЗУ возвращает `None`: содержимое ячейки копируется
+  средствами устройства из ОЗУ.
+
+  # Аргументы
+  * `id` - Номер ячейки (1..=4)
+  */
+  fn slot_rom_source(&self, id: usize) -> Option<Profile> {
+    match self.is_rom {
+      true => self.profiles_rom_vec.get(id - 1).cloned(),
+      false => None,
+    }
+  }
+
+  /**
+  Создает задачу записи в ПЗУ с предварительным резервным копированием
+
+  Перед вызовом `write` снимается и сохраняется автоматическая копия кейпада.
+  Если копию создать не удалось, запись отменяется. Результат копирования
+  и записи отображается пользователю, после чего профили ОЗУ и ПЗУ перечитываются.
+
+  # Аргументы
+  * `write` - Функция, помещающая команды записи в буфер отправки
+  */
+  fn rom_write_task<F>(&mut self, write: F) -> Task<Message>
+  where
+    F: FnOnce(&mut Buffers) -> anyhow::Result<()> + Send + 'static,
+  {
+    if !self.keypad.is_open {
+      return Task::none();
+    }
+
+    self.profile_write = true;
+    let mut buf = self.buffers.clone();
+    Task::perform(
+      async move {
+        let path = Self::backup_save(buf.clone(), true).await.map_err(|err| {
+          format!("Не удалось создать резервную копию, запись в ПЗУ отменена: {err}")
+        })?;
+
+        match tokio::task::spawn_blocking(move || write(&mut buf)).await {
+          Ok(Ok(())) => Ok(path),
+          Ok(Err(err)) => Err(format!("Не удалось записать в ПЗУ: {err}")),
+          Err(err) => Err(format!("Не удалось записать в ПЗУ: {err}")),
+        }
+      },
+      Message::RomWritten,
+    )
+  }
+
+  /**
+  Создает задачу записи на кейпад в ОЗУ или ПЗУ
+
+  Запись с сохранением в ПЗУ выполняется через `rom_write_task`
+  (с предварительной резервной копией), запись только в ОЗУ — сразу.
+  После записи профили ОЗУ и ПЗУ перечитываются.
+
+  # Аргументы
+  * `to_rom` - Сохранять результат в ПЗУ
+  * `write` - Функция, помещающая команды записи в буфер отправки
+  */
+  fn keypad_write_task<F>(&mut self, to_rom: bool, write: F) -> Task<Message>
+  where
+    F: FnOnce(&mut Buffers, bool) -> anyhow::Result<()> + Send + 'static,
+  {
+    if to_rom {
+      return self.rom_write_task(move |buf| write(buf, true));
+    }
+
+    if !self.keypad.is_open {
+      return Task::none();
+    }
+
+    self.profile_write = true;
+    let mut buf = self.buffers.clone();
+    Task::perform(
+      async move { tokio::task::spawn_blocking(move || write(&mut buf, false)).await },
+      |_| Message::ProfileReceiveRomVec,
+    )
+  }
+
+  /**
+  Снимает резервную копию кейпада и сохраняет её в файл
+
+  # Аргументы
+  * `buffers` - Буферы для обмена данными с устройством
+  * `is_auto` - Копия создаётся автоматически
+
+  # Возвращает
+  Путь к файлу копии или текст ошибки
+  */
+  async fn backup_save(mut buffers: Buffers, is_auto: bool) -> Result<PathBuf, String> {
+    let res: anyhow::Result<PathBuf> = async {
+      let backup = backup_request(&mut buffers).await?;
+      tokio::task::spawn_blocking(move || backup.save(is_auto)).await?
+    }
+    .await;
+
+    res.map_err(|err| err.to_string())
+  }
+
+  /**
+  Формирует текст результата записи набора
+
+  Для записи в ПЗУ дополнительно сравнивает набор с перечитанными профилями ПЗУ.
+  */
+  fn bank_write_status(&self, mut progress: BankWrite) -> String {
+    if progress.to_rom {
+      for (i, profile) in progress.profiles.iter().enumerate() {
+        let verified = self
+          .profiles_rom_vec
+          .get(i)
+          .is_some_and(|rom| rom.device_eq(profile, self.settings.name_encoding));
+        if !verified && !progress.failed.contains(&(i + 1)) {
+          progress.failed.push(i + 1);
+        }
+      }
+      progress.failed.sort();
+    }
+
+    let target = if progress.to_rom { "ПЗУ" } else { "ОЗУ" };
+    match progress.failed.is_empty() {
+      true => format!("Набор «{}» записан в {target} и проверен", progress.name),
+      false => format!(
+        "Набор «{}» записан в {target}, не прошли проверку ячейки: {}",
+        progress.name,
+        progress
+          .failed
+          .iter()
+          .map(ToString::to_string)
+          .collect::<Vec<_>>()
+          .join(", ")
+      ),
+    }
+  }
+
+  /// Формирует текст статуса по результату сохранения резервной копии
+  fn backup_status_text(res: Result<PathBuf, String>) -> String {
+    match res {
+      Ok(path) => format!("Резервная копия сохранена: {}", path.display()),
+      Err(err) => format!("Не удалось создать резервную копию: {err}"),
+    }
+  }
 }
@@ -32,6 +32,9 @@ impl State {
     let sidebar = container(
       column![
         create_button_with_svg_and_text(&Icon::Profiles, Message::ChangePage(Pages::Profiles)),
+        create_button_with_svg_and_text(&Icon::Banks, Message::ChangePage(Pages::Banks)),
+        create_button_with_svg_and_text(&Icon::Compare, Message::ChangePage(Pages::Compare)),
+        create_button_with_svg_and_text(&Icon::Templates, Message::ChangePage(Pages::Templates)),
         create_button_with_svg_and_text(&Icon::Settings, Message::ChangePage(Pages::Settings)),
         create_button_with_svg_and_text(&Icon::Update, Message::ChangePage(Pages::Updater)),
       ]
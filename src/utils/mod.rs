@@ -19,3 +19,4 @@ pub const BYTE_START: u8 = b's';
 pub const BYTE_END: u8 = b'e';
 
 pub mod logger;
+pub mod time;
@@ -0,0 +1,50 @@
+//! Работа со временем без внешних зависимостей: метки Unix и их форматирование.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Возвращает текущее время в секундах от начала эпохи Unix
+pub fn unix_now() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|d| d.as_secs())
+    .unwrap_or_default()
+}
+
+/**
+Форматирует метку времени Unix в строку вида `2025-10-18 14:05 UTC`
+
+# Аргументы
+* `secs` - Время в секундах от начала эпохи Unix
+*/
+pub fn format_timestamp(secs: u64) -> String {
+  let days = (secs / 86_400) as i64;
+  let rem = secs % 86_400;
+
+  // Преобразование числа дней в григорианскую дату (алгоритм Howard Hinnant)
+  let z = days + 719_468;
+  let era = z.div_euclid(146_097);
+  let doe = z.rem_euclid(146_097);
+  let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+  let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+  let mp = (5 * doy + 2) / 153;
+  let day = doy - (153 * mp + 2) / 5 + 1;
+  let month = if mp < 10 { mp + 3 } else { mp - 9 };
+  let year = yoe + era * 400 + i64::from(month <= 2);
+
+  format!(
+    "{year:04}-{month:02}-{day:02} {:02}:{:02} UTC",
+    rem / 3_600,
+    rem % 3_600 / 60
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn formats_timestamp() {
+    assert_eq!(format_timestamp(0), "1970-01-01 00:00 UTC");
+    assert_eq!(format_timestamp(1_709_210_096), "2024-02-29 12:34 UTC");
+  }
+}